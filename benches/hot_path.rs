@@ -0,0 +1,110 @@
+//! Benchmarks of the per-interaction hot path: signature verification, public
+//! key decoding, and interaction deserialization.
+//!
+//! Run natively with `cargo bench`. The benchmarks can also be timed under
+//! WebAssembly, closer to how they run on Workers, by building them for WASI
+//! and running them with a WASI runtime such as Wasmtime:
+//!
+//! ```sh
+//! cargo bench --target wasm32-wasip1 --no-run
+//! wasmtime run --dir=. target/wasm32-wasip1/release/deps/hot_path-*.wasm -- --bench
+//! ```
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use ed25519_dalek::{Keypair, PublicKey, SecretKey, Signer};
+use twilight_model::application::interaction::Interaction;
+
+const TIMESTAMP: &str = "1682410000";
+
+const PING: &str = r#"{"application_id":"100000000000000001","id":"100000000000000002","token":"token","type":1,"user":{"avatar":null,"discriminator":"0001","id":"100000000000000003","public_flags":0,"username":"user"},"version":1}"#;
+
+const COMMAND: &str = r#"{"app_permissions":"442368","application_id":"100000000000000001","channel_id":"100000000000000004","data":{"guild_id":"100000000000000005","id":"100000000000000006","name":"ban","options":[{"name":"user","type":6,"value":"100000000000000007"},{"name":"reason","type":3,"value":"spam"}],"resolved":{"users":{"100000000000000007":{"avatar":null,"discriminator":"0002","id":"100000000000000007","public_flags":0,"username":"spammer"}}},"type":1},"guild_id":"100000000000000005","guild_locale":"en-US","id":"100000000000000002","locale":"en-US","member":{"avatar":null,"communication_disabled_until":null,"deaf":false,"flags":0,"joined_at":"2021-01-01T00:00:00.000000+00:00","mute":false,"nick":null,"pending":false,"permissions":"2199023255551","premium_since":null,"roles":["100000000000000008"],"user":{"avatar":null,"discriminator":"0001","id":"100000000000000003","public_flags":0,"username":"user"}},"token":"token","type":2,"version":1}"#;
+
+/// Create a modal submission with the given number of text inputs, each with
+/// a long value, to represent a large payload.
+fn modal(inputs: usize) -> String {
+    let components = (0..inputs)
+        .map(|index| {
+            format!(
+                r#"{{"type":1,"components":[{{"custom_id":"input-{index}","type":4,"value":"{}"}}]}}"#,
+                "lorem ipsum dolor sit amet ".repeat(150),
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(",");
+
+    format!(
+        r#"{{"application_id":"100000000000000001","channel_id":"100000000000000004","data":{{"components":[{components}],"custom_id":"modal"}},"guild_id":"100000000000000005","id":"100000000000000002","locale":"en-US","member":{{"avatar":null,"communication_disabled_until":null,"deaf":false,"flags":0,"joined_at":"2021-01-01T00:00:00.000000+00:00","mute":false,"nick":null,"pending":false,"permissions":"2199023255551","premium_since":null,"roles":[],"user":{{"avatar":null,"discriminator":"0001","id":"100000000000000003","public_flags":0,"username":"user"}}}},"token":"token","type":5,"version":1}}"#
+    )
+}
+
+fn payloads() -> [(&'static str, String); 3] {
+    [
+        ("ping", PING.to_owned()),
+        ("command", COMMAND.to_owned()),
+        ("modal", modal(5)),
+    ]
+}
+
+fn keypair() -> Keypair {
+    let secret = SecretKey::from_bytes(&[7; 32]).expect("secret key is valid");
+    let public = PublicKey::from(&secret);
+
+    Keypair { secret, public }
+}
+
+fn verify(c: &mut Criterion) {
+    let keypair = keypair();
+    let public_key = hex::encode(keypair.public.as_bytes());
+    let mut group = c.benchmark_group("verify");
+
+    for (name, body) in payloads() {
+        let message = [TIMESTAMP.as_bytes(), body.as_bytes()].concat();
+        let signature = hex::encode(keypair.sign(&message).to_bytes());
+
+        group.throughput(Throughput::Bytes(body.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(name), &body, |b, body| {
+            b.iter(|| {
+                twilight_cloudflare_workers::verify(
+                    black_box(&public_key),
+                    black_box(TIMESTAMP),
+                    black_box(&signature),
+                    black_box(body.as_bytes()),
+                )
+            });
+        });
+    }
+
+    group.finish();
+}
+
+fn public_key(c: &mut Criterion) {
+    let public_key = hex::encode(keypair().public.as_bytes());
+
+    c.bench_function("public_key/hex", |b| {
+        b.iter(|| <[u8; 32] as hex::FromHex>::from_hex(black_box(&public_key)));
+    });
+    c.bench_function("public_key/parse", |b| {
+        let bytes = keypair().public.to_bytes();
+
+        b.iter(|| PublicKey::from_bytes(black_box(&bytes)));
+    });
+}
+
+fn deserialize(c: &mut Criterion) {
+    let mut group = c.benchmark_group("deserialize");
+
+    for (name, body) in payloads() {
+        serde_json::from_str::<Interaction>(&body).expect("payload is a valid interaction");
+
+        group.throughput(Throughput::Bytes(body.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(name), &body, |b, body| {
+            b.iter(|| serde_json::from_str::<Interaction>(black_box(body)));
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, verify, public_key, deserialize);
+criterion_main!(benches);
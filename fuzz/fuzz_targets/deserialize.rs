@@ -0,0 +1,14 @@
+//! Parse arbitrary bodies as interactions, lazily and fully.
+//!
+//! Run with `cargo fuzz run deserialize` from the repository root.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use twilight_cloudflare_workers::LazyInteraction;
+
+fuzz_target!(|body: &[u8]| {
+    if let Ok(interaction) = LazyInteraction::new(body.to_vec()) {
+        let _ = interaction.into_interaction();
+    }
+});
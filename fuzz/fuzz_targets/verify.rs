@@ -0,0 +1,28 @@
+//! Verify arbitrary public keys, timestamps, signatures, and bodies.
+//!
+//! Run with `cargo fuzz run verify` from the repository root. Inputs are split
+//! on newlines into the public key, timestamp, signature, and body.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let mut parts = data.splitn(4, |byte| *byte == b'\n');
+    let (Some(public_key), Some(timestamp), Some(signature)) =
+        (parts.next(), parts.next(), parts.next())
+    else {
+        return;
+    };
+    let body = parts.next().unwrap_or_default();
+
+    let (Ok(public_key), Ok(timestamp), Ok(signature)) = (
+        std::str::from_utf8(public_key),
+        std::str::from_utf8(timestamp),
+        std::str::from_utf8(signature),
+    ) else {
+        return;
+    };
+
+    let _ = twilight_cloudflare_workers::verify(public_key, timestamp, signature, body);
+});
@@ -0,0 +1,270 @@
+//! Accessors of interactions, hiding the differences between where Discord
+//! provides data in different types of interactions.
+//!
+//! ```ignore
+//! use twilight_cloudflare_workers::accessors::InteractionExt;
+//!
+//! match interaction.command_name() {
+//!     Some("ping") => ping(&interaction).await,
+//!     Some(name) => unknown_command(name),
+//!     None => {}
+//! }
+//! ```
+
+use twilight_model::{
+    application::{
+        command::CommandOptionType,
+        interaction::{
+            application_command::{CommandDataOption, CommandOptionValue},
+            Interaction, InteractionData,
+        },
+    },
+    id::{
+        marker::{ChannelMarker, GuildMarker},
+        Id,
+    },
+};
+
+/// Option being typed in of an autocomplete interaction, returned by
+/// [`InteractionExt::focused_option`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FocusedOption<'a> {
+    /// Type of the option.
+    pub kind: CommandOptionType,
+    /// Name of the option.
+    pub name: &'a str,
+    /// Names of the subcommand group and subcommand the option is of, if any.
+    pub path: Vec<&'a str>,
+    /// Partial value of the option typed in so far.
+    pub value: &'a str,
+}
+
+/// Extension trait with accessors of interactions.
+///
+/// The user who invoked an interaction is provided by Twilight's own
+/// [`Interaction::author`] and [`Interaction::author_id`], whether it was
+/// invoked in a guild or a DM, and whether it was invoked in a DM by
+/// [`Interaction::is_dm`].
+pub trait InteractionExt {
+    /// ID of the channel the interaction was invoked in.
+    ///
+    /// This is the ID of the interaction's channel, falling back to its
+    /// deprecated channel ID.
+    fn channel_id(&self) -> Option<Id<ChannelMarker>>;
+
+    /// Name of the command of application command and autocomplete
+    /// interactions.
+    fn command_name(&self) -> Option<&str>;
+
+    /// Custom ID of the component of message component interactions or of the
+    /// modal of modal submit interactions.
+    fn custom_id(&self) -> Option<&str>;
+
+    /// Option being typed in of autocomplete interactions, including options
+    /// of subcommands.
+    ///
+    /// ```ignore
+    /// let Some(focused) = interaction.focused_option() else {
+    ///     return Ok(choices(Vec::new()));
+    /// };
+    ///
+    /// match (focused.path.as_slice(), focused.name) {
+    ///     (["timezone", "set"], "zone") => Ok(choices(timezones(focused.value))),
+    ///     _ => Ok(choices(Vec::new())),
+    /// }
+    /// ```
+    fn focused_option(&self) -> Option<FocusedOption<'_>>;
+
+    /// ID of the guild the interaction was invoked in.
+    ///
+    /// This is the guild ID of the interaction, falling back to the guild ID
+    /// of its channel.
+    fn guild_id(&self) -> Option<Id<GuildMarker>>;
+
+    /// Locale to respond to the interaction in.
+    ///
+    /// This is the locale of the user who invoked the interaction, falling
+    /// back to the locale of the guild.
+    fn locale(&self) -> Option<&str>;
+}
+
+impl InteractionExt for Interaction {
+    fn channel_id(&self) -> Option<Id<ChannelMarker>> {
+        #[allow(deprecated)]
+        self.channel
+            .as_ref()
+            .map(|channel| channel.id)
+            .or(self.channel_id)
+    }
+
+    fn command_name(&self) -> Option<&str> {
+        match &self.data {
+            Some(InteractionData::ApplicationCommand(data)) => Some(&data.name),
+            _ => None,
+        }
+    }
+
+    fn custom_id(&self) -> Option<&str> {
+        match &self.data {
+            Some(InteractionData::MessageComponent(data)) => Some(&data.custom_id),
+            Some(InteractionData::ModalSubmit(data)) => Some(&data.custom_id),
+            _ => None,
+        }
+    }
+
+    fn focused_option(&self) -> Option<FocusedOption<'_>> {
+        let Some(InteractionData::ApplicationCommand(data)) = &self.data else {
+            return None;
+        };
+
+        let mut path = Vec::new();
+
+        focused(&data.options, &mut path).map(|(name, value, kind)| FocusedOption {
+            kind,
+            name,
+            path,
+            value,
+        })
+    }
+
+    fn guild_id(&self) -> Option<Id<GuildMarker>> {
+        self.guild_id
+            .or_else(|| self.channel.as_ref().and_then(|channel| channel.guild_id))
+    }
+
+    fn locale(&self) -> Option<&str> {
+        self.locale.as_deref().or(self.guild_locale.as_deref())
+    }
+}
+
+/// Find the focused option among options, pushing the names of the
+/// subcommands it is nested under onto the path.
+fn focused<'a>(
+    options: &'a [CommandDataOption],
+    path: &mut Vec<&'a str>,
+) -> Option<(&'a str, &'a str, CommandOptionType)> {
+    options.iter().find_map(|option| match &option.value {
+        CommandOptionValue::Focused(value, kind) => {
+            Some((option.name.as_str(), value.as_str(), *kind))
+        }
+        CommandOptionValue::SubCommand(options) | CommandOptionValue::SubCommandGroup(options) => {
+            path.push(&option.name);
+
+            let found = focused(options, path);
+
+            if found.is_none() {
+                path.pop();
+            }
+
+            found
+        }
+        _ => None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FocusedOption, InteractionExt};
+    use serde_json::{json, Value};
+    use twilight_model::application::{command::CommandOptionType, interaction::Interaction};
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    /// Autocomplete interaction of a command with options.
+    fn autocomplete(options: &Value) -> Interaction {
+        serde_json::from_value(json!({
+            "application_id": "1",
+            "data": {
+                "id": "3",
+                "name": "search",
+                "options": options,
+                "type": 1,
+            },
+            "id": "2",
+            "token": "token",
+            "type": 4,
+            "version": 1,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn focused_option_top_level() {
+        let interaction = autocomplete(&json!([
+            { "name": "limit", "type": 4, "value": 5 },
+            { "focused": true, "name": "query", "type": 3, "value": "ab" },
+        ]));
+
+        assert_eq!(
+            interaction.focused_option(),
+            Some(FocusedOption {
+                kind: CommandOptionType::String,
+                name: "query",
+                path: Vec::new(),
+                value: "ab",
+            })
+        );
+    }
+
+    #[test]
+    fn focused_option_subcommand_group() {
+        let interaction = autocomplete(&json!([{
+            "name": "tags",
+            "options": [
+                {
+                    "name": "list",
+                    "options": [{ "name": "query", "type": 3, "value": "unfocused" }],
+                    "type": 1,
+                },
+                {
+                    "name": "show",
+                    "options": [
+                        { "name": "public", "type": 5, "value": true },
+                        { "focused": true, "name": "tag", "type": 3, "value": "wel" },
+                    ],
+                    "type": 1,
+                },
+            ],
+            "type": 2,
+        }]));
+
+        assert_eq!(
+            interaction.focused_option(),
+            Some(FocusedOption {
+                kind: CommandOptionType::String,
+                name: "tag",
+                path: vec!["tags", "show"],
+                value: "wel",
+            })
+        );
+    }
+
+    #[test]
+    fn focused_option_subcommand() {
+        let interaction = autocomplete(&json!([{
+            "name": "show",
+            "options": [{ "focused": true, "name": "count", "type": 4, "value": "1" }],
+            "type": 1,
+        }]));
+
+        let focused = interaction.focused_option().unwrap();
+        assert_eq!(focused.kind, CommandOptionType::Integer);
+        assert_eq!(focused.path, ["show"]);
+        assert_eq!(focused.value, "1");
+    }
+
+    #[test]
+    fn focused_option_none() {
+        let interaction = autocomplete(&json!([{
+            "name": "tags",
+            "options": [{
+                "name": "show",
+                "options": [{ "name": "tag", "type": 3, "value": "welcome" }],
+                "type": 1,
+            }],
+            "type": 2,
+        }]));
+
+        assert_eq!(interaction.focused_option(), None);
+    }
+}
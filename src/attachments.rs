@@ -0,0 +1,460 @@
+//! Downloading the attachments of interactions, such as files provided to
+//! command options, with a maximum size and allowed content types.
+//!
+//! ```ignore
+//! use twilight_cloudflare_workers::attachments::Download;
+//!
+//! let bytes = Download::new(&attachment)
+//!     .content_types(["image/png", "image/jpeg"])
+//!     .max_size(8 * 1024 * 1024)
+//!     .bytes()
+//!     .await?;
+//! ```
+
+use core::fmt::{Display, Formatter, Result as FmtResult};
+use futures_util::{stream, Stream, StreamExt};
+use std::error::Error;
+use twilight_model::channel::Attachment;
+use worker::{Fetch, Response, Url};
+
+/// Download of an attachment from Discord's CDN.
+///
+/// The size and content type of the attachment are checked before it is
+/// requested, and its size is checked again while it is read.
+#[derive(Clone, Debug, PartialEq)]
+#[must_use = "downloads have no effect if unused"]
+pub struct Download<'a> {
+    attachment: &'a Attachment,
+    content_types: Vec<String>,
+    max_size: Option<u64>,
+}
+
+impl<'a> Download<'a> {
+    /// Create a new download of an attachment, such as one resolved from a
+    /// command option.
+    pub const fn new(attachment: &'a Attachment) -> Self {
+        Self {
+            attachment,
+            content_types: Vec::new(),
+            max_size: None,
+        }
+    }
+
+    /// Set the content types attachments may have, such as `text/csv`.
+    ///
+    /// Content types whose subtype is `*`, such as `image/*`, allow any
+    /// subtype. Attachments without a content type are not allowed.
+    ///
+    /// Defaults to allowing any content type.
+    pub fn content_types(
+        mut self,
+        content_types: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        self.content_types = content_types.into_iter().map(Into::into).collect();
+
+        self
+    }
+
+    /// Set the maximum size of attachments in bytes.
+    ///
+    /// Defaults to no maximum size.
+    pub const fn max_size(mut self, max_size: u64) -> Self {
+        self.max_size = Some(max_size);
+
+        self
+    }
+
+    /// Download the attachment, returning its contents.
+    ///
+    /// # Errors
+    ///
+    /// Refer to the documentation for [`stream`].
+    ///
+    /// [`stream`]: Self::stream
+    pub async fn bytes(self) -> Result<Vec<u8>, DownloadError> {
+        let size = self.attachment.size;
+        let stream = self.stream().await?;
+        futures_util::pin_mut!(stream);
+        let mut bytes = Vec::with_capacity(usize::try_from(size).unwrap_or_default());
+
+        while let Some(chunk) = stream.next().await {
+            bytes.extend_from_slice(&chunk?);
+        }
+
+        Ok(bytes)
+    }
+
+    /// Download the attachment, returning a stream of the chunks of its
+    /// contents.
+    ///
+    /// The stream ends after yielding an error.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of type [`ContentTypeNotAllowed`] if the content type
+    /// of the attachment is not allowed.
+    ///
+    /// Returns an error of type [`Requesting`] if the request could not be
+    /// sent or the response could not be read.
+    ///
+    /// Returns an error of type [`Status`] if Discord's CDN responded with an
+    /// unsuccessful status code.
+    ///
+    /// Returns an error of type [`TooLarge`] if the attachment is larger than
+    /// the maximum size.
+    ///
+    /// [`ContentTypeNotAllowed`]: DownloadErrorType::ContentTypeNotAllowed
+    /// [`Requesting`]: DownloadErrorType::Requesting
+    /// [`Status`]: DownloadErrorType::Status
+    /// [`TooLarge`]: DownloadErrorType::TooLarge
+    pub async fn stream(
+        self,
+    ) -> Result<impl Stream<Item = Result<Vec<u8>, DownloadError>>, DownloadError> {
+        let mut response = self.send().await?;
+        let max_size = self.max_size;
+        let chunks = response.stream().map_err(DownloadError::requesting)?;
+
+        Ok(stream::unfold(
+            (chunks, 0_u64, false),
+            move |(mut chunks, read, done)| async move {
+                if done {
+                    return None;
+                }
+
+                let chunk = match chunks.next().await? {
+                    Ok(chunk) => chunk,
+                    Err(source) => {
+                        return Some((Err(DownloadError::requesting(source)), (chunks, read, true)))
+                    }
+                };
+                let read = read.saturating_add(u64::try_from(chunk.len()).unwrap_or(u64::MAX));
+
+                if let Some(max_size) = exceeded(max_size, read) {
+                    return Some((
+                        Err(DownloadError::too_large(max_size)),
+                        (chunks, read, true),
+                    ));
+                }
+
+                Some((Ok(chunk), (chunks, read, false)))
+            },
+        ))
+    }
+
+    /// Whether the content type of the attachment is allowed.
+    fn content_type_allowed(&self) -> bool {
+        if self.content_types.is_empty() {
+            return true;
+        }
+
+        let Some(content_type) = &self.attachment.content_type else {
+            return false;
+        };
+
+        // Ignore parameters such as the charset of text attachments.
+        let essence = content_type.split(';').next().unwrap_or_default().trim();
+
+        self.content_types.iter().any(|allowed| {
+            allowed.strip_suffix("/*").map_or_else(
+                || allowed.eq_ignore_ascii_case(essence),
+                |kind| {
+                    essence
+                        .split_once('/')
+                        .is_some_and(|(essence_kind, _)| essence_kind.eq_ignore_ascii_case(kind))
+                },
+            )
+        })
+    }
+
+    /// Check the content type and declared size of the attachment before it
+    /// is requested.
+    fn check(&self) -> Result<(), DownloadError> {
+        if !self.content_type_allowed() {
+            return Err(DownloadError {
+                kind: DownloadErrorType::ContentTypeNotAllowed {
+                    content_type: self.attachment.content_type.clone(),
+                },
+                source: None,
+            });
+        }
+
+        if let Some(max_size) = exceeded(self.max_size, self.attachment.size) {
+            return Err(DownloadError::too_large(max_size));
+        }
+
+        Ok(())
+    }
+
+    /// Check the attachment and request it, returning the response if it is
+    /// successful.
+    async fn send(&self) -> Result<Response, DownloadError> {
+        self.check()?;
+
+        let url = Url::parse(&self.attachment.url).map_err(|source| DownloadError {
+            kind: DownloadErrorType::Requesting,
+            source: Some(Box::new(source)),
+        })?;
+        let response = Fetch::Url(url)
+            .send()
+            .await
+            .map_err(DownloadError::requesting)?;
+        let status = response.status_code();
+
+        if !(200..300).contains(&status) {
+            return Err(DownloadError {
+                kind: DownloadErrorType::Status { status },
+                source: None,
+            });
+        }
+
+        // Reject the attachment before reading any of it if the declared
+        // length is already too large.
+        let content_length = response
+            .headers()
+            .get("Content-Length")
+            .ok()
+            .flatten()
+            .and_then(|value| value.parse::<u64>().ok());
+
+        if let Some(max_size) = content_length.and_then(|length| exceeded(self.max_size, length)) {
+            return Err(DownloadError::too_large(max_size));
+        }
+
+        Ok(response)
+    }
+}
+
+/// Maximum size a size in bytes exceeds, if any.
+fn exceeded(max_size: Option<u64>, size: u64) -> Option<u64> {
+    max_size.filter(|max_size| size > *max_size)
+}
+
+/// Attachment could not be downloaded.
+#[derive(Debug)]
+pub struct DownloadError {
+    kind: DownloadErrorType,
+    source: Option<Box<dyn Error>>,
+}
+
+impl DownloadError {
+    /// Immutable reference to the type of error that occurred.
+    #[must_use = "retrieving the type has no effect if left unused"]
+    pub const fn kind(&self) -> &DownloadErrorType {
+        &self.kind
+    }
+
+    /// Consume the error, returning the source error if there is any.
+    #[must_use = "consuming the error and retrieving the source has no effect if left unused"]
+    pub fn into_source(self) -> Option<Box<dyn Error>> {
+        self.source
+    }
+
+    /// Consume the error, returning the owned error type and the source error.
+    #[must_use = "consuming the error into its parts has no effect if left unused"]
+    pub fn into_parts(self) -> (DownloadErrorType, Option<Box<dyn Error>>) {
+        (self.kind, self.source)
+    }
+
+    /// Create an error of type [`DownloadErrorType::Requesting`].
+    fn requesting(source: worker::Error) -> Self {
+        Self {
+            kind: DownloadErrorType::Requesting,
+            source: Some(Box::new(source)),
+        }
+    }
+
+    /// Create an error of type [`DownloadErrorType::TooLarge`].
+    const fn too_large(max_size: u64) -> Self {
+        Self {
+            kind: DownloadErrorType::TooLarge { max_size },
+            source: None,
+        }
+    }
+}
+
+impl Display for DownloadError {
+    #[cfg(feature = "minimal-errors")]
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.write_str("error ")?;
+
+        Display::fmt(&self.kind.code(), f)
+    }
+
+    #[cfg(not(feature = "minimal-errors"))]
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match &self.kind {
+            DownloadErrorType::ContentTypeNotAllowed { content_type } => {
+                f.write_str("attachment content type ")?;
+
+                match content_type {
+                    Some(content_type) => write!(f, "{content_type:?}")?,
+                    None => f.write_str("(none)")?,
+                }
+
+                f.write_str(" is not allowed")
+            }
+            DownloadErrorType::Requesting => f.write_str("failed to download attachment"),
+            DownloadErrorType::Status { status } => {
+                f.write_str("attachment download responded with status code ")?;
+
+                Display::fmt(status, f)
+            }
+            DownloadErrorType::TooLarge { max_size } => {
+                f.write_str("attachment is larger than the maximum size of ")?;
+                Display::fmt(max_size, f)?;
+
+                f.write_str(" bytes")
+            }
+        }
+    }
+}
+
+impl Error for DownloadError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.source
+            .as_ref()
+            .map(|source| &**source as &(dyn Error + 'static))
+    }
+}
+
+/// Type of [`DownloadError`] that occurred.
+#[derive(Debug)]
+pub enum DownloadErrorType {
+    /// Content type of the attachment is not allowed.
+    ContentTypeNotAllowed {
+        /// Content type of the attachment, if it has one.
+        content_type: Option<String>,
+    },
+    /// Failed to send the request or read its response.
+    Requesting,
+    /// Discord's CDN responded with an unsuccessful status code.
+    Status {
+        /// Status code of the response.
+        status: u16,
+    },
+    /// Attachment is larger than the maximum size.
+    TooLarge {
+        /// Maximum size of attachments in bytes.
+        max_size: u64,
+    },
+}
+
+impl DownloadErrorType {
+    /// Numeric code of the type of error, which errors are displayed as with
+    /// the `minimal-errors` feature.
+    ///
+    /// Refer to [`ProcessRequestErrorType::code`] for more information.
+    ///
+    /// [`ProcessRequestErrorType::code`]: crate::ProcessRequestErrorType::code
+    #[must_use = "retrieving the code is not useful on its own"]
+    pub const fn code(&self) -> u16 {
+        match self {
+            Self::ContentTypeNotAllowed { .. } => 37,
+            Self::Requesting => 38,
+            Self::Status { .. } => 39,
+            Self::TooLarge { .. } => 40,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{exceeded, Download, DownloadErrorType};
+    use serde_json::json;
+    use twilight_model::channel::Attachment;
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    fn attachment(content_type: Option<&str>, size: u64) -> Attachment {
+        serde_json::from_value(json!({
+            "content_type": content_type,
+            "filename": "file",
+            "id": "1",
+            "proxy_url": "https://media.discordapp.net/attachments/1/2/file",
+            "size": size,
+            "url": "https://cdn.discordapp.com/attachments/1/2/file",
+        }))
+        .unwrap()
+    }
+
+    fn allowed(content_type: Option<&str>, content_types: &[&str]) -> bool {
+        Download::new(&attachment(content_type, 0))
+            .content_types(content_types.iter().copied())
+            .content_type_allowed()
+    }
+
+    #[test]
+    fn content_type_exact() {
+        assert!(allowed(Some("image/png"), &["image/png", "image/jpeg"]));
+        assert!(allowed(Some("image/jpeg"), &["image/png", "image/jpeg"]));
+        assert!(!allowed(Some("image/gif"), &["image/png", "image/jpeg"]));
+        assert!(!allowed(Some("image/pngx"), &["image/png"]));
+    }
+
+    #[test]
+    fn content_type_any() {
+        assert!(allowed(None, &[]));
+        assert!(allowed(Some("application/zip"), &[]));
+        assert!(!allowed(None, &["image/png"]));
+        assert!(!allowed(None, &["image/*"]));
+    }
+
+    #[test]
+    fn content_type_wildcard() {
+        assert!(allowed(Some("image/png"), &["image/*"]));
+        assert!(allowed(Some("image/svg+xml"), &["image/*"]));
+        assert!(!allowed(Some("imagery/png"), &["image/*"]));
+        assert!(!allowed(Some("image"), &["image/*"]));
+        assert!(!allowed(Some("text/plain"), &["image/*"]));
+    }
+
+    #[test]
+    fn content_type_parameters() {
+        assert!(allowed(Some("text/csv; charset=utf-8"), &["text/csv"]));
+        assert!(allowed(Some("text/csv;charset=utf-8"), &["text/*"]));
+        assert!(!allowed(Some("text/plain; charset=utf-8"), &["text/csv"]));
+    }
+
+    #[test]
+    fn content_type_case() {
+        assert!(allowed(Some("Image/PNG"), &["image/png"]));
+        assert!(allowed(Some("image/png"), &["IMAGE/PNG"]));
+        assert!(allowed(Some("TEXT/CSV; charset=UTF-8"), &["text/*"]));
+        assert!(allowed(Some("text/csv"), &["Text/*"]));
+    }
+
+    #[test]
+    fn size_limit() {
+        assert_eq!(None, exceeded(None, u64::MAX));
+        assert_eq!(None, exceeded(Some(1024), 1023));
+        assert_eq!(None, exceeded(Some(1024), 1024));
+        assert_eq!(Some(1024), exceeded(Some(1024), 1025));
+        assert_eq!(None, exceeded(Some(0), 0));
+    }
+
+    #[test]
+    fn check() {
+        let png = attachment(Some("image/png"), 1024);
+
+        assert!(Download::new(&png).max_size(1024).check().is_ok());
+        assert!(matches!(
+            Download::new(&png)
+                .max_size(1023)
+                .check()
+                .unwrap_err()
+                .kind(),
+            DownloadErrorType::TooLarge { max_size: 1023 }
+        ));
+        assert!(matches!(
+            Download::new(&png)
+                .content_types(["text/*"])
+                .max_size(1023)
+                .check()
+                .unwrap_err()
+                .kind(),
+            DownloadErrorType::ContentTypeNotAllowed {
+                content_type: Some(content_type),
+            } if content_type == "image/png"
+        ));
+    }
+}
@@ -0,0 +1,265 @@
+//! Autocomplete choices served from datasets embedded in the Worker, such as
+//! timezones, currencies, or the items of a game, without any I/O per
+//! request.
+//!
+//! ```ignore
+//! use twilight_cloudflare_workers::{accessors::InteractionExt, autocomplete::StaticChoices};
+//!
+//! const CURRENCIES: StaticChoices = StaticChoices::new(include!("currencies.in"));
+//!
+//! let focused = interaction.focused_option()?;
+//!
+//! return Ok(twilight_cloudflare_workers::response(&CURRENCIES.response(focused.value)));
+//! ```
+
+use core::cmp::Ordering;
+use twilight_model::{
+    application::command::{CommandOptionChoice, CommandOptionChoiceValue},
+    http::interaction::{InteractionResponse, InteractionResponseData, InteractionResponseType},
+};
+
+/// Maximum number of choices Discord accepts in an autocomplete response.
+const CHOICES_LENGTH_MAX: usize = 25;
+
+/// Autocomplete choices of a dataset embedded in the Worker, matched by a
+/// prefix of their names.
+///
+/// Choices are sorted by name ignoring ASCII case, so that the choices
+/// matching a prefix are found with a binary search.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[must_use = "choices have no effect if unused"]
+pub struct StaticChoices {
+    choices: &'static [(&'static str, &'static str)],
+}
+
+impl StaticChoices {
+    /// Create new choices from the names and values of a dataset, sorted by
+    /// name ignoring ASCII case.
+    ///
+    /// Creating the choices in a constant checks that they are sorted when
+    /// the Worker is compiled:
+    ///
+    /// ```
+    /// use twilight_cloudflare_workers::autocomplete::StaticChoices;
+    ///
+    /// const COLORS: StaticChoices =
+    ///     StaticChoices::new(&[("Blue", "blue"), ("green", "green"), ("Red", "red")]);
+    ///
+    /// assert_eq!(COLORS.choices("r").len(), 1);
+    /// ```
+    ///
+    /// Choices that aren't sorted fail to compile:
+    ///
+    /// ```compile_fail
+    /// use twilight_cloudflare_workers::autocomplete::StaticChoices;
+    ///
+    /// const COLORS: StaticChoices =
+    ///     StaticChoices::new(&[("Red", "red"), ("green", "green"), ("Blue", "blue")]);
+    ///
+    /// assert_eq!(COLORS.choices("r").len(), 1);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the choices are not sorted by name.
+    pub const fn new(choices: &'static [(&'static str, &'static str)]) -> Self {
+        let mut index = 1;
+
+        while index < choices.len() {
+            if matches!(
+                compare(choices[index - 1].0.as_bytes(), choices[index].0.as_bytes()),
+                Ordering::Greater
+            ) {
+                panic!("choices are not sorted by name");
+            }
+
+            index += 1;
+        }
+
+        Self { choices }
+    }
+
+    /// Names and values of the choices whose names start with a prefix,
+    /// ignoring ASCII case.
+    pub fn matching<'a>(
+        &self,
+        prefix: &'a str,
+    ) -> impl Iterator<Item = (&'static str, &'static str)> + 'a {
+        let start = self
+            .choices
+            .partition_point(|(name, _)| compare(name.as_bytes(), prefix.as_bytes()).is_lt());
+
+        self.choices[start..]
+            .iter()
+            .copied()
+            .take_while(move |(name, _)| {
+                name.len() >= prefix.len()
+                    && name.as_bytes()[..prefix.len()].eq_ignore_ascii_case(prefix.as_bytes())
+            })
+    }
+
+    /// Choices whose names start with a prefix, such as the value of the
+    /// focused option, up to the maximum of 25 that Discord accepts.
+    #[must_use = "retrieving the choices is not useful on its own"]
+    pub fn choices(&self, prefix: &str) -> Vec<CommandOptionChoice> {
+        self.matching(prefix)
+            .take(CHOICES_LENGTH_MAX)
+            .map(|(name, value)| CommandOptionChoice {
+                name: name.to_owned(),
+                name_localizations: None,
+                value: CommandOptionChoiceValue::String(value.to_owned()),
+            })
+            .collect()
+    }
+
+    /// Autocomplete response with the choices whose names start with a
+    /// prefix.
+    #[must_use = "creating the response is not useful on its own"]
+    pub fn response(&self, prefix: &str) -> InteractionResponse {
+        InteractionResponse {
+            kind: InteractionResponseType::ApplicationCommandAutocompleteResult,
+            data: Some(InteractionResponseData {
+                choices: Some(self.choices(prefix)),
+                ..InteractionResponseData::default()
+            }),
+        }
+    }
+}
+
+/// Compare two strings ignoring ASCII case.
+const fn compare(a: &[u8], b: &[u8]) -> Ordering {
+    let mut index = 0;
+
+    while index < a.len() && index < b.len() {
+        let left = a[index].to_ascii_lowercase();
+        let right = b[index].to_ascii_lowercase();
+
+        if left < right {
+            return Ordering::Less;
+        }
+
+        if left > right {
+            return Ordering::Greater;
+        }
+
+        index += 1;
+    }
+
+    if a.len() < b.len() {
+        Ordering::Less
+    } else if a.len() > b.len() {
+        Ordering::Greater
+    } else {
+        Ordering::Equal
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{StaticChoices, CHOICES_LENGTH_MAX};
+    use twilight_model::{
+        application::command::CommandOptionChoiceValue, http::interaction::InteractionResponseType,
+    };
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    const FRUITS: StaticChoices = StaticChoices::new(&[
+        ("apple", "1"),
+        ("Apricot", "2"),
+        ("banana", "3"),
+        ("Band", "4"),
+        ("bandana", "5"),
+        ("Cherry", "6"),
+        ("cherry", "7"),
+    ]);
+
+    fn names(prefix: &str) -> Vec<&'static str> {
+        FRUITS.matching(prefix).map(|(name, _)| name).collect()
+    }
+
+    #[test]
+    fn matching_prefix() {
+        assert_eq!(names("ap"), ["apple", "Apricot"]);
+        assert_eq!(names("ban"), ["banana", "Band", "bandana"]);
+        assert_eq!(names("BAND"), ["Band", "bandana"]);
+        assert_eq!(names("cherry"), ["Cherry", "cherry"]);
+        assert_eq!(names("apple"), ["apple"]);
+    }
+
+    #[test]
+    fn matching_none() {
+        assert!(names("applesauce").is_empty());
+        assert!(names("bb").is_empty());
+        assert!(names("0").is_empty());
+        assert!(names("z").is_empty());
+        assert!(names("é").is_empty());
+    }
+
+    #[test]
+    fn matching_empty_prefix() {
+        assert_eq!(names("").len(), 7);
+    }
+
+    #[test]
+    fn choices_limit() {
+        const NUMBERS: StaticChoices = StaticChoices::new(&[
+            ("n00", "0"),
+            ("n01", "1"),
+            ("n02", "2"),
+            ("n03", "3"),
+            ("n04", "4"),
+            ("n05", "5"),
+            ("n06", "6"),
+            ("n07", "7"),
+            ("n08", "8"),
+            ("n09", "9"),
+            ("n10", "10"),
+            ("n11", "11"),
+            ("n12", "12"),
+            ("n13", "13"),
+            ("n14", "14"),
+            ("n15", "15"),
+            ("n16", "16"),
+            ("n17", "17"),
+            ("n18", "18"),
+            ("n19", "19"),
+            ("n20", "20"),
+            ("n21", "21"),
+            ("n22", "22"),
+            ("n23", "23"),
+            ("n24", "24"),
+            ("n25", "25"),
+            ("n26", "26"),
+        ]);
+
+        let choices = NUMBERS.choices("n");
+        assert_eq!(choices.len(), CHOICES_LENGTH_MAX);
+        assert_eq!(choices[0].name, "n00");
+        assert_eq!(
+            choices[0].value,
+            CommandOptionChoiceValue::String("0".to_owned())
+        );
+        assert_eq!(NUMBERS.choices("n2").len(), 7);
+    }
+
+    #[test]
+    fn response() {
+        let response = FRUITS.response("ch");
+
+        assert_eq!(
+            response.kind,
+            InteractionResponseType::ApplicationCommandAutocompleteResult
+        );
+        assert_eq!(response.data.unwrap().choices.unwrap().len(), 2);
+    }
+
+    // Panics abort on WebAssembly, so they can't be caught by the test.
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    #[should_panic = "choices are not sorted by name"]
+    fn unsorted() {
+        let choices = Box::leak(Box::new([("b", "b"), ("a", "a")]));
+
+        let _ = StaticChoices::new(choices);
+    }
+}
@@ -0,0 +1,180 @@
+//! Broadcasts of a message to many channels, sharded across the messages of
+//! a Cloudflare Queue so that sending them isn't bound by the CPU and
+//! subrequest limits of a single invocation.
+//!
+//! [`broadcast`] sends the channels to a queue in shards of up to
+//! [`CHANNELS_PER_SHARD`], and the queue's consumer delivers each shard with
+//! [`deliver`] through a [`BotClient`], which waits for Discord's rate limits
+//! to reset instead of failing.
+//!
+//! ```ignore
+//! use twilight_cloudflare_workers::broadcast::{self, Shard};
+//!
+//! // When an announcement is made.
+//! broadcast::broadcast(&env.queue("BROADCASTS")?, &data, &channel_ids).await?;
+//!
+//! // In the queue's consumer.
+//! #[event(queue)]
+//! async fn queue(batch: MessageBatch<Shard>, env: Env, _: Context) -> Result<()> {
+//!     let client = BotClient::new(&env.secret("DISCORD_TOKEN")?.to_string());
+//!
+//!     for (channel_id, source) in broadcast::deliver(&client, &batch).await? {
+//!         console_error!("failed to deliver to {channel_id}: {source}");
+//!     }
+//!
+//!     Ok(())
+//! }
+//! ```
+
+use crate::rest::{BotClient, RestError, RestErrorType};
+use serde::{Deserialize, Serialize};
+use twilight_model::{
+    http::interaction::InteractionResponseData,
+    id::{marker::ChannelMarker, Id},
+};
+use worker::{MessageBatch, Queue};
+
+/// Maximum number of channels delivered to by a single queue message.
+pub const CHANNELS_PER_SHARD: usize = 25;
+
+/// Message to deliver to a shard of a broadcast's channels, the body of the
+/// queue messages sent by [`broadcast`].
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Shard {
+    /// Channels to create the message in.
+    pub channel_ids: Vec<Id<ChannelMarker>>,
+    /// Data of the message, as for [`BotClient::create_message`].
+    pub data: InteractionResponseData,
+}
+
+/// Send a message to be broadcast to channels to a queue, in shards of up to
+/// [`CHANNELS_PER_SHARD`] channels each.
+///
+/// # Errors
+///
+/// Returns an error if a shard could not be sent to the queue, in which case
+/// the shards before it have been sent.
+pub async fn broadcast(
+    queue: &Queue,
+    data: &InteractionResponseData,
+    channel_ids: &[Id<ChannelMarker>],
+) -> worker::Result<()> {
+    for shard in shards(data, channel_ids) {
+        queue.send(&shard).await?;
+    }
+
+    Ok(())
+}
+
+/// Deliver the shards of broadcasts in a batch of queue messages, returning
+/// the channels that couldn't be delivered to along with why.
+///
+/// Messages are created one channel at a time, delaying requests that would
+/// be rate limited until the rate limit resets and retrying requests that
+/// were rate limited once. Channels that fail aren't retried with the batch,
+/// so that the other channels of their shards don't receive the message
+/// twice.
+///
+/// # Errors
+///
+/// Returns an error if the messages of the batch could not be deserialized,
+/// in which case nothing is delivered.
+pub async fn deliver(
+    client: &BotClient,
+    batch: &MessageBatch<Shard>,
+) -> worker::Result<Vec<(Id<ChannelMarker>, RestError)>> {
+    let client = client.clone().delay_rate_limited(true);
+    let mut failures = Vec::new();
+
+    for message in batch.messages()? {
+        let Shard { channel_ids, data } = message.body;
+
+        for channel_id in channel_ids {
+            let result = match client.create_message(channel_id, &data).await {
+                Err(source) if matches!(source.kind(), RestErrorType::RateLimited { .. }) => {
+                    client.create_message(channel_id, &data).await
+                }
+                result => result,
+            };
+
+            if let Err(source) = result {
+                failures.push((channel_id, source));
+            }
+        }
+    }
+
+    Ok(failures)
+}
+
+/// Split the channels of a broadcast into shards.
+fn shards(data: &InteractionResponseData, channel_ids: &[Id<ChannelMarker>]) -> Vec<Shard> {
+    channel_ids
+        .chunks(CHANNELS_PER_SHARD)
+        .map(|chunk| Shard {
+            channel_ids: chunk.to_vec(),
+            data: data.clone(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{shards, Shard, CHANNELS_PER_SHARD};
+    use serde_json::json;
+    use twilight_model::{http::interaction::InteractionResponseData, id::Id};
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    fn data() -> InteractionResponseData {
+        InteractionResponseData {
+            content: Some("Announcement".to_owned()),
+            ..InteractionResponseData::default()
+        }
+    }
+
+    #[test]
+    fn shards_split_channels() {
+        let channel_ids = (1..=60).map(Id::new).collect::<Vec<_>>();
+        let shards = shards(&data(), &channel_ids);
+
+        assert_eq!(
+            vec![CHANNELS_PER_SHARD, CHANNELS_PER_SHARD, 10],
+            shards
+                .iter()
+                .map(|shard| shard.channel_ids.len())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            channel_ids,
+            shards
+                .iter()
+                .flat_map(|shard| shard.channel_ids.iter().copied())
+                .collect::<Vec<_>>()
+        );
+        assert!(shards.iter().all(|shard| shard.data == data()));
+    }
+
+    #[test]
+    fn shards_exact_multiple() {
+        let channel_ids = (1..=50).map(Id::new).collect::<Vec<_>>();
+
+        assert_eq!(2, shards(&data(), &channel_ids).len());
+    }
+
+    #[test]
+    fn shards_no_channels() {
+        assert!(shards(&data(), &[]).is_empty());
+    }
+
+    #[test]
+    fn shard_round_trip() {
+        let shard = Shard {
+            channel_ids: vec![Id::new(1), Id::new(2)],
+            data: data(),
+        };
+        let value = serde_json::to_value(&shard).unwrap();
+
+        assert_eq!(json!(["1", "2"]), value["channel_ids"]);
+        assert_eq!(shard, serde_json::from_value(value).unwrap());
+    }
+}
@@ -0,0 +1,1369 @@
+//! Configuration for processing interaction requests.
+
+#[cfg(feature = "twilight-model")]
+use crate::webhook_events::WebhookEvent;
+use crate::{
+    build_response, key, match_path, Endpoint, ErrorBody, LazyInteraction, ProcessRequestError,
+    ProcessRequestErrorType,
+};
+use core::fmt::{Debug, Display, Formatter, Result as FmtResult};
+use serde::Serialize;
+use std::error::Error;
+#[cfg(feature = "twilight-model")]
+use twilight_model::application::interaction::{Interaction, InteractionType};
+use worker::{
+    js_sys::{Array, Reflect},
+    wasm_bindgen::JsValue,
+    Env, Method, Request, Response, ResponseBody, Url,
+};
+
+/// Name of the binding of the application's ID.
+const APPLICATION_ID_BINDING: &str = "APPLICATION_ID";
+
+/// Name of the binding of the application's bot token.
+const BOT_TOKEN_BINDING: &str = "BOT_TOKEN";
+
+/// Number of seconds preflight responses may be cached for.
+const CORS_MAX_AGE: &str = "86400";
+
+/// Name of the binding of the comma separated names of enabled features.
+const FEATURES_BINDING: &str = "FEATURES";
+
+/// Name of the binding of the path of the interactions route.
+const INTERACTIONS_PATH_BINDING: &str = "INTERACTIONS_PATH";
+
+/// Name of the binding of the maximum size of request bodies in bytes.
+const MAX_BODY_SIZE_BINDING: &str = "MAX_BODY_SIZE";
+
+/// Name of the binding of the comma separated IDs of the bot's owners.
+const OWNER_IDS_BINDING: &str = "OWNER_IDS";
+
+/// Name of the binding of the application's public key.
+const PUBLIC_KEY_BINDING: &str = "PUBLIC_KEY";
+
+/// Largest integer a JavaScript number represents exactly.
+const MAX_SAFE_INTEGER: f64 = 9_007_199_254_740_991.0;
+
+/// Configuration for processing interaction requests.
+///
+/// Use [`Config::new`] for a configuration with the default settings, or
+/// [`Config::builder`] to customize the settings.
+#[derive(Clone, Eq, PartialEq)]
+pub struct Config {
+    application_id: Option<String>,
+    bot_token: Option<String>,
+    cors: Vec<(String, Vec<String>)>,
+    error_body: ErrorBody,
+    features: Vec<String>,
+    health_check: Option<(String, String)>,
+    #[cfg(feature = "twilight-model")]
+    interaction_types: Option<Vec<InteractionType>>,
+    invalid_signature_body: Option<ErrorBody>,
+    landing_page: Option<LandingPage>,
+    max_body_size: Option<usize>,
+    owner_ids: Vec<String>,
+    path: String,
+    public_key: String,
+}
+
+impl Config {
+    /// Create a new configuration with the default settings.
+    pub fn new(public_key: impl Into<String>) -> Self {
+        Self {
+            application_id: None,
+            bot_token: None,
+            cors: Vec::new(),
+            error_body: ErrorBody::Description,
+            features: Vec::new(),
+            health_check: None,
+            #[cfg(feature = "twilight-model")]
+            interaction_types: None,
+            invalid_signature_body: None,
+            landing_page: None,
+            max_body_size: None,
+            owner_ids: Vec::new(),
+            path: "/".to_owned(),
+            public_key: public_key.into(),
+        }
+    }
+
+    /// Create a new builder to customize the settings of a configuration.
+    pub fn builder(public_key: impl Into<String>) -> ConfigBuilder {
+        ConfigBuilder::new(public_key)
+    }
+
+    /// ID of the application, if set.
+    ///
+    /// Refer to [`ConfigBuilder::application_id`] for more information.
+    #[must_use = "retrieving the application ID is not useful on its own"]
+    pub fn application_id(&self) -> Option<&str> {
+        self.application_id.as_deref()
+    }
+
+    /// Bot token of the application, if set.
+    ///
+    /// Refer to [`ConfigBuilder::bot_token`] for more information.
+    #[must_use = "retrieving the bot token is not useful on its own"]
+    pub fn bot_token(&self) -> Option<&str> {
+        self.bot_token.as_deref()
+    }
+
+    /// Origins allowed to make cross-origin requests to a path, if any.
+    ///
+    /// Refer to [`ConfigBuilder::cors`] for more information.
+    #[must_use = "retrieving the allowed origins is not useful on its own"]
+    pub fn cors_origins(&self, path: &str) -> Option<&[String]> {
+        self.cors
+            .iter()
+            .find(|(cors_path, _)| cors_path == path)
+            .map(|(_, origins)| origins.as_slice())
+    }
+
+    /// Body of the responses created for errors.
+    ///
+    /// Refer to [`ConfigBuilder::error_body`] for more information.
+    #[must_use = "retrieving the error body is not useful on its own"]
+    pub const fn error_body(&self) -> &ErrorBody {
+        &self.error_body
+    }
+
+    /// Whether a feature is enabled.
+    ///
+    /// Refer to [`ConfigBuilder::features`] for more information.
+    #[must_use = "checking whether a feature is enabled is not useful on its own"]
+    pub fn feature(&self, name: &str) -> bool {
+        self.features.iter().any(|feature| feature == name)
+    }
+
+    /// Names of the enabled features.
+    ///
+    /// Refer to [`ConfigBuilder::features`] for more information.
+    #[must_use = "retrieving the features is not useful on its own"]
+    pub fn features(&self) -> &[String] {
+        &self.features
+    }
+
+    /// Path of the health check route, if any.
+    ///
+    /// Refer to [`ConfigBuilder::health_check`] for more information.
+    #[must_use = "retrieving the health check path is not useful on its own"]
+    pub fn health_check_path(&self) -> Option<&str> {
+        self.health_check.as_ref().map(|(path, _)| path.as_str())
+    }
+
+    /// Types of interactions accepted, if restricted.
+    ///
+    /// Refer to [`ConfigBuilder::interaction_types`] for more information.
+    #[cfg(feature = "twilight-model")]
+    #[must_use = "retrieving the interaction types is not useful on its own"]
+    pub fn interaction_types(&self) -> Option<&[InteractionType]> {
+        self.interaction_types.as_deref()
+    }
+
+    /// Body of the responses created for requests whose signature is invalid.
+    ///
+    /// Refer to [`ConfigBuilder::invalid_signature_body`] for more
+    /// information.
+    #[must_use = "retrieving the error body is not useful on its own"]
+    pub const fn invalid_signature_body(&self) -> &ErrorBody {
+        match &self.invalid_signature_body {
+            Some(body) => body,
+            None => &self.error_body,
+        }
+    }
+
+    /// Response to `GET` requests for the interactions route, if any.
+    ///
+    /// Refer to [`ConfigBuilder::landing_page`] for more information.
+    #[must_use = "retrieving the landing page is not useful on its own"]
+    pub const fn landing_page(&self) -> Option<&LandingPage> {
+        self.landing_page.as_ref()
+    }
+
+    /// Maximum size of request bodies in bytes, if any.
+    ///
+    /// Refer to [`ConfigBuilder::max_body_size`] for more information.
+    #[must_use = "retrieving the maximum body size is not useful on its own"]
+    pub const fn max_body_size(&self) -> Option<usize> {
+        self.max_body_size
+    }
+
+    /// IDs of the bot's owners.
+    ///
+    /// Refer to [`ConfigBuilder::owner_ids`] for more information.
+    #[must_use = "retrieving the owner IDs is not useful on its own"]
+    pub fn owner_ids(&self) -> &[String] {
+        &self.owner_ids
+    }
+
+    /// Path of the interactions route.
+    ///
+    /// Refer to [`ConfigBuilder::path`] for more information.
+    #[must_use = "retrieving the path is not useful on its own"]
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Application's public key for Discord.
+    #[must_use = "retrieving the public key is not useful on its own"]
+    pub fn public_key(&self) -> &str {
+        &self.public_key
+    }
+
+    /// Add the CORS headers for a request to the response to it, if the
+    /// request is from an origin allowed to make cross-origin requests to its
+    /// path.
+    ///
+    /// Refer to [`ConfigBuilder::cors`] for more information.
+    #[must_use = "created responses must be used to actually send the response"]
+    pub fn cors_response(&self, req: &Request, mut response: Response) -> Response {
+        let origin = req.headers().get("Origin").ok().flatten();
+
+        if let Some(origin) = self.allowed_origin(&req.path(), origin) {
+            // Origins are taken from the request's headers, so they are valid
+            // header values.
+            let headers = response.headers_mut();
+            let _ = headers.set("Access-Control-Allow-Origin", &origin);
+            let _ = headers.append("Vary", "Origin");
+        }
+
+        response
+    }
+
+    /// Segments of a request's path matched by the wildcards of the
+    /// interactions route's path, if the request is for the route.
+    ///
+    /// Refer to [`ConfigBuilder::path`] for more information.
+    #[must_use = "retrieving the captures is not useful on its own"]
+    pub fn captures(&self, req: &Request) -> Option<Vec<String>> {
+        let path = req.path();
+
+        match_path(&self.path, &path)
+            .map(|captures| captures.into_iter().map(str::to_owned).collect())
+    }
+
+    /// Create a response for an error with the configured body.
+    #[must_use = "created responses must be used to actually send the response"]
+    pub fn error_response(&self, error: &ProcessRequestError) -> Response {
+        let body = if matches!(
+            error.kind(),
+            ProcessRequestErrorType::InvalidSignature
+                | ProcessRequestErrorType::InvalidTimestamp
+                | ProcessRequestErrorType::SignatureFromHex
+                | ProcessRequestErrorType::SignatureLength { .. }
+        ) {
+            self.invalid_signature_body()
+        } else {
+            &self.error_body
+        };
+
+        error.response_with_body(body)
+    }
+
+    /// Process a request with the configuration, returning the request's
+    /// interaction body if the request is valid.
+    ///
+    /// # Errors
+    ///
+    /// Refer to the documentation for [`crate::request`].
+    #[cfg(feature = "twilight-model")]
+    pub async fn request(&self, req: &mut Request) -> Result<Interaction, ProcessRequestError> {
+        let body = crate::verify_request(
+            req,
+            Endpoint::Interactions { path: &self.path },
+            &self.public_key,
+            self.max_body_size,
+        )
+        .await?;
+        let interaction = crate::parse::deserialize::<Interaction>(body)?;
+        self.accept(interaction.kind)?;
+
+        Ok(interaction)
+    }
+
+    /// Process a request with the configuration if it is for the interactions
+    /// route, returning the request's interaction body if the request is
+    /// valid.
+    ///
+    /// Requests with another method than `POST` or another path return `None`
+    /// with their body left untouched.
+    ///
+    /// # Errors
+    ///
+    /// Refer to the documentation for [`crate::request_if_matched`].
+    #[cfg(feature = "twilight-model")]
+    pub async fn request_if_matched(
+        &self,
+        req: &mut Request,
+    ) -> Result<Option<Interaction>, ProcessRequestError> {
+        if req.method() != Method::Post || match_path(&self.path, &req.path()).is_none() {
+            return Ok(None);
+        }
+
+        self.request(req).await.map(Some)
+    }
+
+    /// Process a request with the configuration, returning the request's
+    /// interaction body if the request is valid, deserializing only what is
+    /// required to route the interaction.
+    ///
+    /// # Errors
+    ///
+    /// Refer to the documentation for [`crate::request_lazy`].
+    pub async fn request_lazy(
+        &self,
+        req: &mut Request,
+    ) -> Result<LazyInteraction, ProcessRequestError> {
+        let (body, timing) = crate::verify_request_timed(
+            req,
+            Endpoint::Interactions { path: &self.path },
+            &self.public_key,
+            self.max_body_size,
+        )
+        .await?;
+
+        let interaction = LazyInteraction::new(body)?.with_timing(timing);
+        #[cfg(feature = "twilight-model")]
+        self.accept(interaction.kind())?;
+
+        Ok(interaction)
+    }
+
+    /// Route a request, processing it if it is for the interactions route.
+    ///
+    /// Requests for other paths are left untouched and returned as
+    /// [`Route::Unmatched`], so that they can be handled by the Worker's own
+    /// routing, while errors for the interactions route are returned as
+    /// responses created by [`error_response`]. Requests for the
+    /// [health check] route, [CORS] preflight requests, and `GET` requests for
+    /// the [landing page] are answered by the configuration.
+    ///
+    /// ```ignore
+    /// match config.route(&mut req).await {
+    ///     Route::Interaction(interaction) => handle(interaction).await,
+    ///     Route::Response(response) => Ok(response),
+    ///     Route::Unmatched => router.run(req, env).await,
+    /// }
+    /// ```
+    ///
+    /// [`error_response`]: Self::error_response
+    /// [CORS]: ConfigBuilder::cors
+    /// [health check]: ConfigBuilder::health_check
+    /// [landing page]: ConfigBuilder::landing_page
+    pub async fn route(&self, req: &mut Request) -> Route {
+        let path = req.path();
+
+        if req.method() == Method::Options && self.cors_origins(&path).is_some() {
+            return Route::Response(self.preflight_response(req));
+        }
+
+        if let Some((health_check_path, version)) = &self.health_check {
+            let method = req.method();
+
+            if path == *health_check_path && matches!(method, Method::Get | Method::Head) {
+                let body = serde_json::to_vec(&HealthCheck {
+                    status: "ok",
+                    version,
+                })
+                .unwrap_or_default();
+
+                // Responses to `HEAD` requests have the headers of the
+                // response to a `GET` request, without its body.
+                let response = if method == Method::Head {
+                    build_response(
+                        ResponseBody::Empty,
+                        200,
+                        &[
+                            ("Content-Length", &body.len().to_string()),
+                            ("Content-Type", "application/json"),
+                        ],
+                    )
+                } else {
+                    build_response(
+                        ResponseBody::Body(body),
+                        200,
+                        &[("Content-Type", "application/json")],
+                    )
+                };
+
+                return Route::Response(response);
+            }
+        }
+
+        if match_path(&self.path, &path).is_none() {
+            return Route::Unmatched;
+        }
+
+        if let Some((status, header, body)) = self.landing_page_parts(&req.method()) {
+            let body = body.map_or(ResponseBody::Empty, |body| {
+                ResponseBody::Body(body.as_bytes().to_vec())
+            });
+
+            return Route::Response(build_response(body, status, &[header]));
+        }
+
+        match self.request_lazy(req).await {
+            Ok(interaction) => Route::Interaction(interaction),
+            Err(source) => Route::Response(self.error_response(&source)),
+        }
+    }
+
+    /// Check whether an interaction type is accepted.
+    #[cfg(feature = "twilight-model")]
+    fn accept(&self, kind: InteractionType) -> Result<(), ProcessRequestError> {
+        match &self.interaction_types {
+            Some(kinds) if kind != InteractionType::Ping && !kinds.contains(&kind) => {
+                Err(ProcessRequestError {
+                    kind: ProcessRequestErrorType::InteractionTypeNotAccepted { kind: kind as u8 },
+                    source: None,
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Status, header, and body of the response with the landing page to a
+    /// request with a method, if there is a landing page and the method is
+    /// `GET`.
+    fn landing_page_parts(
+        &self,
+        method: &Method,
+    ) -> Option<(u16, (&'static str, &str), Option<&str>)> {
+        if *method != Method::Get {
+            return None;
+        }
+
+        Some(match self.landing_page.as_ref()? {
+            LandingPage::Html(html) => (200, ("Content-Type", "text/html"), Some(html)),
+            LandingPage::Redirect(url) => (302, ("Location", url.as_str()), None),
+        })
+    }
+
+    /// Origin of a request if it is allowed to make cross-origin requests to
+    /// the request's path.
+    fn allowed_origin(&self, path: &str, origin: Option<String>) -> Option<String> {
+        let origins = self.cors_origins(path)?;
+        let origin = origin?;
+
+        origins
+            .iter()
+            .any(|allowed| allowed == "*" || *allowed == origin)
+            .then_some(origin)
+    }
+
+    /// Create a response for a CORS preflight request.
+    fn preflight_response(&self, req: &Request) -> Response {
+        let requested = |name| req.headers().get(name).ok().flatten();
+        let headers = self.preflight_headers(
+            &req.path(),
+            requested("Origin"),
+            requested("Access-Control-Request-Method"),
+            requested("Access-Control-Request-Headers"),
+        );
+        let headers = headers
+            .iter()
+            .map(|(name, value)| (*name, value.as_str()))
+            .collect::<Vec<_>>();
+
+        build_response(ResponseBody::Empty, 204, &headers)
+    }
+
+    /// Headers of the response to a CORS preflight request for a path from
+    /// an origin, with the method and headers it requests, or none if the
+    /// origin isn't allowed.
+    fn preflight_headers(
+        &self,
+        path: &str,
+        origin: Option<String>,
+        method: Option<String>,
+        request_headers: Option<String>,
+    ) -> Vec<(&'static str, String)> {
+        let Some(origin) = self.allowed_origin(path, origin) else {
+            return Vec::new();
+        };
+
+        // Origins, requested methods, and requested headers are taken from
+        // the request's headers, so they are valid header values.
+        let mut headers = vec![
+            ("Access-Control-Allow-Origin", origin),
+            ("Vary", "Origin".to_owned()),
+        ];
+
+        if let Some(method) = method {
+            headers.push(("Access-Control-Allow-Methods", method));
+        }
+
+        if let Some(request_headers) = request_headers {
+            headers.push(("Access-Control-Allow-Headers", request_headers));
+        }
+
+        headers.push(("Access-Control-Max-Age", CORS_MAX_AGE.to_owned()));
+
+        headers
+    }
+
+    /// Process a webhook events request with the configuration, returning the
+    /// request's webhook event body if the request is valid.
+    ///
+    /// # Errors
+    ///
+    /// Refer to the documentation for [`crate::webhook_event`].
+    #[cfg(feature = "twilight-model")]
+    pub async fn webhook_event(
+        &self,
+        req: &mut Request,
+    ) -> Result<WebhookEvent, ProcessRequestError> {
+        let body = crate::verify_request(
+            req,
+            Endpoint::WebhookEvents,
+            &self.public_key,
+            self.max_body_size,
+        )
+        .await?;
+
+        crate::webhook_event_from_body(body)
+    }
+}
+
+impl Debug for Config {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        // The bot token is omitted so that it isn't leaked in logs.
+        let mut debug = f.debug_struct("Config");
+        debug
+            .field("application_id", &self.application_id)
+            .field("cors", &self.cors)
+            .field("error_body", &self.error_body)
+            .field("features", &self.features)
+            .field("health_check", &self.health_check);
+        #[cfg(feature = "twilight-model")]
+        debug.field("interaction_types", &self.interaction_types);
+
+        debug
+            .field("invalid_signature_body", &self.invalid_signature_body)
+            .field("landing_page", &self.landing_page)
+            .field("max_body_size", &self.max_body_size)
+            .field("owner_ids", &self.owner_ids)
+            .field("path", &self.path)
+            .field("public_key", &self.public_key)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Body of health check responses.
+#[derive(Serialize)]
+struct HealthCheck<'a> {
+    /// Status of the Worker, which is always `ok`.
+    status: &'static str,
+    /// Version of the Worker.
+    version: &'a str,
+}
+
+/// Response to `GET` requests for the interactions route.
+///
+/// Refer to [`ConfigBuilder::landing_page`] for more information.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum LandingPage {
+    /// Static HTML page.
+    Html(String),
+    /// Redirect to a URL, such as the application's invite link.
+    Redirect(Url),
+}
+
+/// Outcome of routing a request with [`Config::route`].
+#[derive(Debug)]
+pub enum Route {
+    /// Request is a valid interaction request.
+    Interaction(LazyInteraction),
+    /// Request is for the interactions route, but was answered by the
+    /// configuration, such as because it is invalid.
+    Response(Response),
+    /// Request is not for the interactions route.
+    Unmatched,
+}
+
+/// Builder to customize the settings of a [`Config`].
+#[allow(clippy::module_name_repetitions)]
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[must_use = "builders have no effect if unused"]
+pub struct ConfigBuilder(Config);
+
+impl ConfigBuilder {
+    /// Create a new builder with the default settings.
+    pub fn new(public_key: impl Into<String>) -> Self {
+        Self(Config::new(public_key))
+    }
+
+    /// Create a new builder with the settings stored in the bindings of a
+    /// Worker's environment, such as its variables and secrets.
+    ///
+    /// | Binding             | Setting                                  |
+    /// | ------------------- | ---------------------------------------- |
+    /// | `PUBLIC_KEY`        | Public key, which is required            |
+    /// | `APPLICATION_ID`    | [`application_id`]                       |
+    /// | `BOT_TOKEN`         | [`bot_token`]                            |
+    /// | `FEATURES`          | [`features`], separated by commas        |
+    /// | `INTERACTIONS_PATH` | [`path`]                                 |
+    /// | `MAX_BODY_SIZE`     | [`max_body_size`], in bytes              |
+    /// | `OWNER_IDS`         | [`owner_ids`], separated by commas       |
+    ///
+    /// Bindings other than the public key are optional, leaving the default
+    /// settings when they aren't set. Bindings may be secrets or variables,
+    /// including variables that are numbers, or arrays of strings for the
+    /// comma separated bindings. All of the bindings are loaded before
+    /// returning an error, so that every misconfigured binding is reported at
+    /// once when the Worker starts rather than at random points in requests.
+    ///
+    /// ```ignore
+    /// let config = ConfigBuilder::from_env(&env)?
+    ///     .health_check("/healthz", env!("CARGO_PKG_VERSION"))
+    ///     .build();
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of type [`Bindings`] if the public key isn't set, or
+    /// any of the bindings are invalid, such as IDs that aren't snowflakes,
+    /// paths that don't start with a slash, or bindings of another type such
+    /// as KV namespaces.
+    ///
+    /// [`application_id`]: Self::application_id
+    /// [`bot_token`]: Self::bot_token
+    /// [`Bindings`]: EnvErrorType::Bindings
+    /// [`features`]: Self::features
+    /// [`max_body_size`]: Self::max_body_size
+    /// [`owner_ids`]: Self::owner_ids
+    /// [`path`]: Self::path
+    pub fn from_env(env: &Env) -> Result<Self, EnvError> {
+        Self::from_bindings(|name| Binding::new(env, name))
+    }
+
+    /// Create a new builder from the values of the bindings looked up by
+    /// name.
+    ///
+    /// Refer to [`from_env`] for the bindings and errors.
+    ///
+    /// [`from_env`]: Self::from_env
+    fn from_bindings(lookup: impl Fn(&'static str) -> Binding) -> Result<Self, EnvError> {
+        let mut invalid = Vec::new();
+        let mut missing = Vec::new();
+
+        // Bindings of the wrong type are invalid rather than unset.
+        let mut binding = |name| match lookup(name) {
+            Binding::Invalid => {
+                invalid.push(name);
+
+                None
+            }
+            Binding::Missing => None,
+            Binding::Text(value) => Some(value),
+        };
+
+        let public_key = binding(PUBLIC_KEY_BINDING);
+        let application_id = binding(APPLICATION_ID_BINDING);
+        let bot_token = binding(BOT_TOKEN_BINDING);
+        let features = binding(FEATURES_BINDING);
+        let path = binding(INTERACTIONS_PATH_BINDING);
+        let max_body_size = binding(MAX_BODY_SIZE_BINDING);
+        let owner_ids = binding(OWNER_IDS_BINDING);
+
+        match &public_key {
+            Some(public_key) if key::parse(public_key).is_err() => {
+                invalid.push(PUBLIC_KEY_BINDING);
+            }
+            None if !invalid.contains(&PUBLIC_KEY_BINDING) => missing.push(PUBLIC_KEY_BINDING),
+            _ => {}
+        }
+
+        let mut builder = Self::new(public_key.unwrap_or_default());
+
+        if let Some(application_id) = application_id {
+            if is_snowflake(&application_id) {
+                builder = builder.application_id(application_id);
+            } else {
+                invalid.push(APPLICATION_ID_BINDING);
+            }
+        }
+
+        if let Some(bot_token) = bot_token {
+            builder = builder.bot_token(bot_token);
+        }
+
+        if let Some(features) = features {
+            builder = builder.features(split_list(&features));
+        }
+
+        if let Some(path) = path {
+            if path.starts_with('/') {
+                builder = builder.path(path);
+            } else {
+                invalid.push(INTERACTIONS_PATH_BINDING);
+            }
+        }
+
+        if let Some(max_body_size) = max_body_size {
+            match max_body_size.parse() {
+                Ok(max_body_size) => builder = builder.max_body_size(max_body_size),
+                Err(_) => invalid.push(MAX_BODY_SIZE_BINDING),
+            }
+        }
+
+        if let Some(owner_ids) = owner_ids {
+            let owner_ids = split_list(&owner_ids);
+
+            if owner_ids.iter().all(|id| is_snowflake(id)) {
+                builder = builder.owner_ids(owner_ids);
+            } else {
+                invalid.push(OWNER_IDS_BINDING);
+            }
+        }
+
+        if invalid.is_empty() && missing.is_empty() {
+            Ok(builder)
+        } else {
+            Err(EnvError {
+                kind: EnvErrorType::Bindings { invalid, missing },
+                source: None,
+            })
+        }
+    }
+
+    /// Build the configuration.
+    #[must_use = "building a configuration has no effect if left unused"]
+    pub fn build(self) -> Config {
+        self.0
+    }
+
+    /// Set the ID of the application, such as for creating followup clients
+    /// outside of interactions.
+    ///
+    /// Defaults to no application ID.
+    pub fn application_id(mut self, application_id: impl Into<String>) -> Self {
+        self.0.application_id = Some(application_id.into());
+
+        self
+    }
+
+    /// Set the bot token of the application, such as for creating a bot
+    /// client.
+    ///
+    /// The token is omitted from the configuration's debug output.
+    ///
+    /// Defaults to no bot token.
+    pub fn bot_token(mut self, bot_token: impl Into<String>) -> Self {
+        self.0.bot_token = Some(bot_token.into());
+
+        self
+    }
+
+    /// Allow origins to make cross-origin requests to a path, such as for a
+    /// dashboard or linked roles page served by the same Worker.
+    ///
+    /// `OPTIONS` preflight requests for the path are responded to by
+    /// [`Config::route`], allowing the requested method and headers for the
+    /// origins. Use [`Config::cors_response`] to add the CORS headers to the
+    /// responses of the path. An origin of `*` allows any origin.
+    ///
+    /// ```ignore
+    /// let config = Config::builder(public_key)
+    ///     .cors("/api/roles", ["https://dashboard.example.com"])
+    ///     .build();
+    /// ```
+    ///
+    /// Defaults to not allowing cross-origin requests to any path.
+    pub fn cors(
+        mut self,
+        path: impl Into<String>,
+        origins: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        let path = path.into();
+        let origins = origins.into_iter().map(Into::into).collect();
+        self.0.cors.retain(|(cors_path, _)| *cors_path != path);
+        self.0.cors.push((path, origins));
+
+        self
+    }
+
+    /// Set the body of the responses created for errors by
+    /// [`Config::error_response`].
+    ///
+    /// Defaults to [`ErrorBody::Description`].
+    pub fn error_body(mut self, error_body: ErrorBody) -> Self {
+        self.0.error_body = error_body;
+
+        self
+    }
+
+    /// Set the path of a health check route, such as `/healthz`, and the
+    /// version of the Worker reported by it.
+    ///
+    /// `GET` and `HEAD` requests for the path are responded to by
+    /// [`Config::route`] with a status code of 200 (OK) and a JSON body with
+    /// the version, so that uptime monitors don't reach the interactions
+    /// route. Responses to `HEAD` requests have only the headers.
+    ///
+    /// ```ignore
+    /// let config = Config::builder(public_key)
+    ///     .health_check("/healthz", env!("CARGO_PKG_VERSION"))
+    ///     .build();
+    /// ```
+    ///
+    /// Defaults to no health check route.
+    pub fn health_check(mut self, path: impl Into<String>, version: impl Into<String>) -> Self {
+        self.0.health_check = Some((path.into(), version.into()));
+
+        self
+    }
+
+    /// Set the types of interactions accepted, such as to not accept
+    /// autocomplete interactions or modal submissions when the Worker doesn't
+    /// handle them.
+    ///
+    /// Interactions of other types return an error of type
+    /// [`InteractionTypeNotAccepted`], whose response answers the interaction
+    /// with empty autocomplete choices or an ephemeral message, so that they
+    /// never reach the Worker's handlers. Pings are always accepted.
+    ///
+    /// Defaults to accepting all interaction types.
+    ///
+    /// [`InteractionTypeNotAccepted`]: crate::ProcessRequestErrorType::InteractionTypeNotAccepted
+    #[cfg(feature = "twilight-model")]
+    pub fn interaction_types(
+        mut self,
+        interaction_types: impl IntoIterator<Item = InteractionType>,
+    ) -> Self {
+        self.0.interaction_types = Some(interaction_types.into_iter().collect());
+
+        self
+    }
+
+    /// Set the body of the responses created for requests whose signature is
+    /// invalid by [`Config::error_response`].
+    ///
+    /// Discord recommends responding to these requests without a meaningful
+    /// body, such as with [`ErrorBody::Empty`].
+    ///
+    /// Defaults to the body set with [`error_body`].
+    ///
+    /// [`error_body`]: Self::error_body
+    pub fn invalid_signature_body(mut self, invalid_signature_body: ErrorBody) -> Self {
+        self.0.invalid_signature_body = Some(invalid_signature_body);
+
+        self
+    }
+
+    /// Set the response to `GET` requests for the interactions route made by
+    /// [`Config::route`], such as a page with an invite link for people
+    /// opening the endpoint's URL, while `POST` requests continue to be
+    /// processed as interactions.
+    ///
+    /// ```ignore
+    /// let config = Config::builder(public_key)
+    ///     .landing_page(LandingPage::Redirect(Url::parse(INVITE_URL)?))
+    ///     .build();
+    /// ```
+    ///
+    /// Defaults to no landing page, responding with an error of type
+    /// [`MethodNotAllowed`].
+    ///
+    /// [`MethodNotAllowed`]: crate::ProcessRequestErrorType::MethodNotAllowed
+    pub fn landing_page(mut self, landing_page: LandingPage) -> Self {
+        self.0.landing_page = Some(landing_page);
+
+        self
+    }
+
+    /// Set the maximum size of request bodies in bytes.
+    ///
+    /// When set, the body is read as a stream and processing stops as soon as
+    /// the body exceeds the size, returning an error of type
+    /// [`BodyTooLarge`]. This caps the memory used by requests with large
+    /// bodies, such as modal submissions and components with large amounts of
+    /// embedded state.
+    ///
+    /// Defaults to no maximum size.
+    ///
+    /// [`BodyTooLarge`]: crate::ProcessRequestErrorType::BodyTooLarge
+    pub const fn max_body_size(mut self, max_body_size: usize) -> Self {
+        self.0.max_body_size = Some(max_body_size);
+
+        self
+    }
+
+    /// Set the IDs of the bot's owners, such as for restricting commands to
+    /// them.
+    ///
+    /// Defaults to no owners.
+    pub fn owner_ids(mut self, owner_ids: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.0.owner_ids = owner_ids.into_iter().map(Into::into).collect();
+
+        self
+    }
+
+    /// Set the names of the enabled features, such as for toggling commands
+    /// per environment, which are checked with [`Config::feature`].
+    ///
+    /// Defaults to no features.
+    pub fn features(mut self, features: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.0.features = features.into_iter().map(Into::into).collect();
+
+        self
+    }
+
+    /// Set the path of the interactions route, such as when the Worker serves
+    /// other routes or the interactions of multiple applications.
+    ///
+    /// Segments of the path that are `*` match any single segment, such as
+    /// `/interactions/*` for per-tenant suffixes, and the matched segments can
+    /// be retrieved with [`Config::captures`].
+    ///
+    /// Requests with another path return an error of type
+    /// [`RouteIncorrect`].
+    ///
+    /// Defaults to `/`.
+    ///
+    /// [`RouteIncorrect`]: crate::ProcessRequestErrorType::RouteIncorrect
+    pub fn path(mut self, path: impl Into<String>) -> Self {
+        self.0.path = path.into();
+
+        self
+    }
+}
+
+/// Configuration could not be loaded from the environment.
+#[derive(Debug)]
+pub struct EnvError {
+    kind: EnvErrorType,
+    source: Option<Box<dyn Error>>,
+}
+
+impl EnvError {
+    /// Immutable reference to the type of error that occurred.
+    #[must_use = "retrieving the type has no effect if left unused"]
+    pub const fn kind(&self) -> &EnvErrorType {
+        &self.kind
+    }
+
+    /// Consume the error, returning the source error if there is any.
+    #[must_use = "consuming the error and retrieving the source has no effect if left unused"]
+    pub fn into_source(self) -> Option<Box<dyn Error>> {
+        self.source
+    }
+
+    /// Consume the error, returning the owned error type and the source error.
+    #[must_use = "consuming the error into its parts has no effect if left unused"]
+    pub fn into_parts(self) -> (EnvErrorType, Option<Box<dyn Error>>) {
+        (self.kind, self.source)
+    }
+}
+
+impl Display for EnvError {
+    #[cfg(feature = "minimal-errors")]
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.write_str("error ")?;
+
+        Display::fmt(&self.kind.code(), f)
+    }
+
+    #[cfg(not(feature = "minimal-errors"))]
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match &self.kind {
+            EnvErrorType::Bindings { invalid, missing } => {
+                f.write_str("environment bindings could not be loaded")?;
+
+                if !missing.is_empty() {
+                    f.write_str(", missing: ")?;
+                    f.write_str(&missing.join(", "))?;
+                }
+
+                if !invalid.is_empty() {
+                    f.write_str(", invalid: ")?;
+                    f.write_str(&invalid.join(", "))?;
+                }
+
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Error for EnvError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.source
+            .as_ref()
+            .map(|source| &**source as &(dyn Error + 'static))
+    }
+}
+
+/// Type of [`EnvError`] that occurred.
+#[derive(Debug)]
+pub enum EnvErrorType {
+    /// Bindings are missing or invalid.
+    Bindings {
+        /// Names of the bindings that are set but invalid.
+        invalid: Vec<&'static str>,
+        /// Names of the required bindings that aren't set.
+        missing: Vec<&'static str>,
+    },
+}
+
+impl EnvErrorType {
+    /// Numeric code of the type of error, which errors are displayed as with
+    /// the `minimal-errors` feature.
+    ///
+    /// Refer to [`ProcessRequestErrorType::code`] for more information.
+    ///
+    /// [`ProcessRequestErrorType::code`]: crate::ProcessRequestErrorType::code
+    #[must_use = "retrieving the code is not useful on its own"]
+    pub const fn code(&self) -> u16 {
+        match self {
+            Self::Bindings { .. } => 41,
+        }
+    }
+}
+
+/// Value of a binding in the environment.
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum Binding {
+    /// Binding is set to a value that isn't text, such as a KV namespace.
+    Invalid,
+    /// Binding isn't set.
+    Missing,
+    /// Text of the binding.
+    Text(String),
+}
+
+impl Binding {
+    /// Look up a binding in the environment.
+    ///
+    /// Secrets and variables that are strings are used as is. Variables that
+    /// are integers are converted to text, and arrays of strings are joined
+    /// with commas for the bindings that are lists.
+    fn new(env: &Env, name: &str) -> Self {
+        let Ok(value) = Reflect::get(env, &JsValue::from_str(name)) else {
+            return Self::Invalid;
+        };
+
+        if value.is_undefined() {
+            return Self::Missing;
+        }
+
+        if let Some(text) = value.as_string() {
+            return Self::Text(text);
+        }
+
+        if let Some(number) = value.as_f64() {
+            // Larger numbers, such as most snowflakes, aren't represented
+            // exactly, so they must be strings.
+            return if number.fract() == 0.0 && number.abs() <= MAX_SAFE_INTEGER {
+                Self::Text(number.to_string())
+            } else {
+                Self::Invalid
+            };
+        }
+
+        if !Array::is_array(&value) {
+            return Self::Invalid;
+        }
+
+        Array::from(&value)
+            .iter()
+            .map(|item| item.as_string())
+            .collect::<Option<Vec<_>>>()
+            .map_or(Self::Invalid, |items| Self::Text(items.join(",")))
+    }
+}
+
+/// Split a comma separated list, ignoring surrounding whitespace and empty
+/// items.
+fn split_list(list: &str) -> Vec<&str> {
+    list.split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+/// Whether a string is a snowflake ID, made of digits of a nonzero number.
+fn is_snowflake(value: &str) -> bool {
+    value.bytes().all(|byte| byte.is_ascii_digit()) && value.parse::<u64>().is_ok_and(|id| id != 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Binding, Config, ConfigBuilder, EnvErrorType, LandingPage};
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+    use worker::{Method, Url};
+
+    /// Public key of the first test vector of RFC 8032.
+    const PUBLIC_KEY: &str = "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a";
+
+    /// Load a builder from bindings, returning the names of the invalid and
+    /// missing bindings if it fails.
+    fn load(
+        bindings: &[(&str, Binding)],
+    ) -> Result<ConfigBuilder, (Vec<&'static str>, Vec<&'static str>)> {
+        ConfigBuilder::from_bindings(|name| {
+            bindings
+                .iter()
+                .find(|(binding, _)| *binding == name)
+                .map_or(Binding::Missing, |(_, value)| value.clone())
+        })
+        .map_err(|error| {
+            let EnvErrorType::Bindings { invalid, missing } = error.into_parts().0;
+
+            (invalid, missing)
+        })
+    }
+
+    fn text(value: &str) -> Binding {
+        Binding::Text(value.to_owned())
+    }
+
+    #[test]
+    fn all_bindings() {
+        let config = load(&[
+            ("APPLICATION_ID", text("1")),
+            ("BOT_TOKEN", text("token")),
+            ("FEATURES", text("beta, ,economy")),
+            ("INTERACTIONS_PATH", text("/interactions")),
+            ("MAX_BODY_SIZE", text("65536")),
+            ("OWNER_IDS", text("2, 3,")),
+            ("PUBLIC_KEY", text(PUBLIC_KEY)),
+        ])
+        .unwrap()
+        .build();
+
+        assert_eq!(config.application_id(), Some("1"));
+        assert_eq!(config.bot_token(), Some("token"));
+        assert_eq!(config.features(), ["beta", "economy"]);
+        assert!(config.feature("economy"));
+        assert!(!config.feature("eco"));
+        assert_eq!(config.path(), "/interactions");
+        assert_eq!(config.max_body_size(), Some(65536));
+        assert_eq!(config.owner_ids(), ["2", "3"]);
+        assert_eq!(config.public_key(), PUBLIC_KEY);
+    }
+
+    #[test]
+    fn only_public_key() {
+        let config = load(&[("PUBLIC_KEY", text(PUBLIC_KEY))]).unwrap().build();
+
+        assert_eq!(config.application_id(), None);
+        assert!(config.features().is_empty());
+        assert_eq!(config.path(), "/");
+        assert_eq!(config.max_body_size(), None);
+    }
+
+    #[test]
+    fn collects_every_binding() {
+        let (invalid, missing) = load(&[
+            ("APPLICATION_ID", text("app")),
+            ("BOT_TOKEN", Binding::Invalid),
+            ("FEATURES", Binding::Invalid),
+            ("INTERACTIONS_PATH", text("interactions")),
+            ("MAX_BODY_SIZE", text("-1")),
+            ("OWNER_IDS", text("2,owner")),
+        ])
+        .unwrap_err();
+
+        assert_eq!(
+            invalid,
+            [
+                "BOT_TOKEN",
+                "FEATURES",
+                "APPLICATION_ID",
+                "INTERACTIONS_PATH",
+                "MAX_BODY_SIZE",
+                "OWNER_IDS",
+            ]
+        );
+        assert_eq!(missing, ["PUBLIC_KEY"]);
+    }
+
+    fn cors() -> Config {
+        Config::builder(PUBLIC_KEY)
+            .cors("/api/roles", ["https://dashboard.example.com"])
+            .cors("/api/public", ["*"])
+            .build()
+    }
+
+    #[test]
+    fn cors_allowed_origin() {
+        let config = cors();
+
+        assert_eq!(
+            Some("https://dashboard.example.com".to_owned()),
+            config.allowed_origin(
+                "/api/roles",
+                Some("https://dashboard.example.com".to_owned())
+            )
+        );
+        assert_eq!(
+            Some("https://other.example.com".to_owned()),
+            config.allowed_origin("/api/public", Some("https://other.example.com".to_owned()))
+        );
+    }
+
+    #[test]
+    fn cors_rejected_origin() {
+        let config = cors();
+
+        assert_eq!(
+            None,
+            config.allowed_origin("/api/roles", Some("https://other.example.com".to_owned()))
+        );
+        assert_eq!(
+            None,
+            config.allowed_origin(
+                "/api/roles",
+                Some("https://dashboard.example.com.evil".to_owned())
+            )
+        );
+        assert_eq!(None, config.allowed_origin("/api/roles", None));
+        assert_eq!(None, config.allowed_origin("/api/public", None));
+        assert_eq!(
+            None,
+            config.allowed_origin(
+                "/api/other",
+                Some("https://dashboard.example.com".to_owned())
+            )
+        );
+    }
+
+    #[test]
+    fn cors_replaces_origins() {
+        let config = Config::builder(PUBLIC_KEY)
+            .cors("/api/roles", ["https://old.example.com"])
+            .cors("/api/roles", ["https://new.example.com"])
+            .build();
+
+        assert_eq!(
+            Some(&["https://new.example.com".to_owned()][..]),
+            config.cors_origins("/api/roles")
+        );
+        assert_eq!(
+            None,
+            config.allowed_origin("/api/roles", Some("https://old.example.com".to_owned()))
+        );
+    }
+
+    #[test]
+    fn preflight_allowed() {
+        let headers = cors().preflight_headers(
+            "/api/roles",
+            Some("https://dashboard.example.com".to_owned()),
+            Some("PATCH".to_owned()),
+            Some("authorization, content-type".to_owned()),
+        );
+
+        assert_eq!(
+            vec![
+                (
+                    "Access-Control-Allow-Origin",
+                    "https://dashboard.example.com".to_owned()
+                ),
+                ("Vary", "Origin".to_owned()),
+                ("Access-Control-Allow-Methods", "PATCH".to_owned()),
+                (
+                    "Access-Control-Allow-Headers",
+                    "authorization, content-type".to_owned()
+                ),
+                ("Access-Control-Max-Age", "86400".to_owned()),
+            ],
+            headers
+        );
+    }
+
+    #[test]
+    fn preflight_without_requested_headers() {
+        let headers = cors().preflight_headers(
+            "/api/public",
+            Some("https://other.example.com".to_owned()),
+            None,
+            None,
+        );
+
+        assert_eq!(
+            vec![
+                (
+                    "Access-Control-Allow-Origin",
+                    "https://other.example.com".to_owned()
+                ),
+                ("Vary", "Origin".to_owned()),
+                ("Access-Control-Max-Age", "86400".to_owned()),
+            ],
+            headers
+        );
+    }
+
+    #[test]
+    fn preflight_rejected() {
+        let config = cors();
+
+        assert!(config
+            .preflight_headers(
+                "/api/roles",
+                Some("https://other.example.com".to_owned()),
+                Some("PATCH".to_owned()),
+                None,
+            )
+            .is_empty());
+        assert!(config
+            .preflight_headers("/api/roles", None, Some("PATCH".to_owned()), None)
+            .is_empty());
+    }
+
+    #[test]
+    fn landing_page_html() {
+        let config = Config::builder(PUBLIC_KEY)
+            .landing_page(LandingPage::Html("<h1>Bot</h1>".to_owned()))
+            .build();
+
+        assert_eq!(
+            Some((200, ("Content-Type", "text/html"), Some("<h1>Bot</h1>"))),
+            config.landing_page_parts(&Method::Get)
+        );
+    }
+
+    #[test]
+    fn landing_page_redirect() {
+        let url = "https://discord.com/oauth2/authorize?client_id=1&scope=applications.commands";
+        let config = Config::builder(PUBLIC_KEY)
+            .landing_page(LandingPage::Redirect(Url::parse(url).unwrap()))
+            .build();
+
+        assert_eq!(
+            Some((302, ("Location", url), None)),
+            config.landing_page_parts(&Method::Get)
+        );
+    }
+
+    #[test]
+    fn landing_page_only_get() {
+        let config = Config::builder(PUBLIC_KEY)
+            .landing_page(LandingPage::Html("<h1>Bot</h1>".to_owned()))
+            .build();
+
+        for method in [Method::Head, Method::Post, Method::Options] {
+            assert_eq!(None, config.landing_page_parts(&method));
+        }
+    }
+
+    #[test]
+    fn landing_page_unset() {
+        assert_eq!(
+            None,
+            Config::new(PUBLIC_KEY).landing_page_parts(&Method::Get)
+        );
+    }
+
+    #[test]
+    fn public_key_invalid_not_missing() {
+        for public_key in [Binding::Invalid, text("not hex")] {
+            let (invalid, missing) = load(&[("PUBLIC_KEY", public_key)]).unwrap_err();
+
+            assert_eq!(invalid, ["PUBLIC_KEY"]);
+            assert!(missing.is_empty());
+        }
+    }
+}
@@ -0,0 +1,320 @@
+//! Confirmation prompts with buttons to confirm or cancel an action, which
+//! are treated as stale once they have expired.
+//!
+//! The ID of the action and the expiry of the prompt are encoded in the
+//! custom IDs of the buttons, so that clicks can be answered without storing
+//! any state.
+//!
+//! ```ignore
+//! use twilight_cloudflare_workers::confirmation::{self, Confirmation, Outcome};
+//!
+//! // Responding to the command.
+//! let expires_at = Date::now().as_millis() + 60_000;
+//!
+//! let confirmation = Confirmation::new("purge:13", "Delete 13 messages?", expires_at)
+//!     .expect("action ID is short enough");
+//!
+//! return Ok(twilight_cloudflare_workers::response(&confirmation.response()));
+//!
+//! // Responding to the click of a button.
+//! if let Some(answer) = confirmation::answer(&data.custom_id, Date::now().as_millis()) {
+//!     let content = match answer.outcome {
+//!         Outcome::Cancelled => "Cancelled.",
+//!         Outcome::Confirmed => purge(answer.id).await,
+//!         Outcome::Expired => "This confirmation has expired.",
+//!     };
+//!
+//!     return Ok(twilight_cloudflare_workers::response(&answer.response(content)));
+//! }
+//! ```
+
+use twilight_model::{
+    channel::message::{
+        component::{ActionRow, Button, ButtonStyle},
+        Component, MessageFlags,
+    },
+    http::interaction::{InteractionResponse, InteractionResponseData, InteractionResponseType},
+};
+
+/// Custom ID segment of the button cancelling the action.
+const CANCEL: &str = "n";
+
+/// Custom ID segment of the button confirming the action.
+const CONFIRM: &str = "y";
+
+/// Maximum number of characters in a custom ID.
+const CUSTOM_ID_LENGTH_MAX: usize = 100;
+
+/// Maximum length of the ID of an action, keeping the custom IDs of the
+/// buttons within the 100 characters Discord accepts along with the prefix,
+/// the answer, and an expiry of up to 20 digits.
+pub const ID_LENGTH_MAX: usize =
+    CUSTOM_ID_LENGTH_MAX - PREFIX.len() - CONFIRM.len() - U64_DIGITS_MAX - 3;
+
+/// Prefix of the custom IDs of the buttons of confirmation prompts.
+const PREFIX: &str = "confirm";
+
+/// Maximum number of digits of a [`u64`].
+const U64_DIGITS_MAX: usize = 20;
+
+/// Prompt to confirm an action.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[must_use = "confirmations have no effect if unused"]
+pub struct Confirmation<'a> {
+    cancel_label: &'a str,
+    confirm_label: &'a str,
+    expires_at: u64,
+    id: &'a str,
+    prompt: &'a str,
+}
+
+impl<'a> Confirmation<'a> {
+    /// Create a new prompt to confirm an action, which expires at a time in
+    /// milliseconds since the Unix epoch.
+    ///
+    /// The ID of the action is returned with the answer to the prompt.
+    ///
+    /// Returns `None` if the ID is longer than [`ID_LENGTH_MAX`] bytes, as the
+    /// custom IDs of the buttons would be longer than Discord accepts.
+    #[must_use = "creating a confirmation is not useful on its own"]
+    pub const fn new(id: &'a str, prompt: &'a str, expires_at: u64) -> Option<Self> {
+        if id.len() > ID_LENGTH_MAX {
+            return None;
+        }
+
+        Some(Self {
+            cancel_label: "No",
+            confirm_label: "Yes",
+            expires_at,
+            id,
+            prompt,
+        })
+    }
+
+    /// Set the labels of the buttons confirming and cancelling the action.
+    ///
+    /// Defaults to `Yes` and `No`.
+    pub const fn labels(mut self, confirm_label: &'a str, cancel_label: &'a str) -> Self {
+        self.cancel_label = cancel_label;
+        self.confirm_label = confirm_label;
+
+        self
+    }
+
+    /// Ephemeral response with the prompt and the buttons to confirm or
+    /// cancel the action.
+    #[must_use = "creating the response is not useful on its own"]
+    pub fn response(&self) -> InteractionResponse {
+        let button = |answer: &str, label: &str, style| {
+            Component::Button(Button {
+                custom_id: Some(format!("{PREFIX}:{answer}:{}:{}", self.expires_at, self.id)),
+                disabled: false,
+                emoji: None,
+                label: Some(label.to_owned()),
+                style,
+                url: None,
+            })
+        };
+
+        InteractionResponse {
+            kind: InteractionResponseType::ChannelMessageWithSource,
+            data: Some(InteractionResponseData {
+                components: Some(vec![Component::ActionRow(ActionRow {
+                    components: vec![
+                        button(CONFIRM, self.confirm_label, ButtonStyle::Danger),
+                        button(CANCEL, self.cancel_label, ButtonStyle::Secondary),
+                    ],
+                })]),
+                content: Some(self.prompt.to_owned()),
+                flags: Some(MessageFlags::EPHEMERAL),
+                ..InteractionResponseData::default()
+            }),
+        }
+    }
+}
+
+/// Answer to a confirmation prompt, parsed from the custom ID of the clicked
+/// button by [`answer`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Answer<'a> {
+    /// ID of the action.
+    pub id: &'a str,
+    /// Outcome of the prompt.
+    pub outcome: Outcome,
+}
+
+impl Answer<'_> {
+    /// Response updating the prompt's message with content in place of the
+    /// prompt, removing its buttons.
+    #[must_use = "creating the response is not useful on its own"]
+    pub fn response(&self, content: impl Into<String>) -> InteractionResponse {
+        InteractionResponse {
+            kind: InteractionResponseType::UpdateMessage,
+            data: Some(InteractionResponseData {
+                components: Some(Vec::new()),
+                content: Some(content.into()),
+                ..InteractionResponseData::default()
+            }),
+        }
+    }
+}
+
+/// Outcome of a confirmation prompt.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Outcome {
+    /// Action was cancelled.
+    Cancelled,
+    /// Action was confirmed.
+    Confirmed,
+    /// Prompt had expired when one of its buttons was clicked.
+    Expired,
+}
+
+/// Parse the answer to a confirmation prompt from the custom ID of the clicked
+/// button, at the current time in milliseconds since the Unix epoch.
+///
+/// Returns `None` if the custom ID isn't of a confirmation prompt's button.
+#[must_use = "parsing the answer is not useful on its own"]
+pub fn answer(custom_id: &str, now: u64) -> Option<Answer<'_>> {
+    let mut segments = custom_id.splitn(4, ':');
+
+    if segments.next()? != PREFIX {
+        return None;
+    }
+
+    let confirmed = match segments.next()? {
+        CANCEL => false,
+        CONFIRM => true,
+        _ => return None,
+    };
+    let expires_at = segments.next()?.parse::<u64>().ok()?;
+    let id = segments.next()?;
+
+    let outcome = if now >= expires_at {
+        Outcome::Expired
+    } else if confirmed {
+        Outcome::Confirmed
+    } else {
+        Outcome::Cancelled
+    };
+
+    Some(Answer { id, outcome })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{answer, Answer, Confirmation, Outcome, CUSTOM_ID_LENGTH_MAX, ID_LENGTH_MAX};
+    use twilight_model::{
+        channel::message::{Component, MessageFlags},
+        http::interaction::{InteractionResponse, InteractionResponseType},
+    };
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    /// Custom IDs of the confirming and cancelling buttons of a response.
+    fn custom_ids(response: &InteractionResponse) -> Vec<String> {
+        let components = response.data.as_ref().unwrap().components.as_ref().unwrap();
+        let Component::ActionRow(row) = &components[0] else {
+            panic!("expected an action row");
+        };
+
+        row.components
+            .iter()
+            .map(|component| match component {
+                Component::Button(button) => button.custom_id.clone().unwrap(),
+                _ => panic!("expected a button"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn response() {
+        let response = Confirmation::new("purge:13", "Delete 13 messages?", 1000)
+            .unwrap()
+            .response();
+        let data = response.data.as_ref().unwrap();
+
+        assert_eq!(
+            InteractionResponseType::ChannelMessageWithSource,
+            response.kind
+        );
+        assert_eq!(Some("Delete 13 messages?"), data.content.as_deref());
+        assert_eq!(Some(MessageFlags::EPHEMERAL), data.flags);
+        assert_eq!(
+            ["confirm:y:1000:purge:13", "confirm:n:1000:purge:13"],
+            custom_ids(&response).as_slice()
+        );
+    }
+
+    #[test]
+    fn id_length() {
+        let id = "a".repeat(ID_LENGTH_MAX);
+        let response = Confirmation::new(&id, "Prompt", u64::MAX)
+            .unwrap()
+            .response();
+
+        for custom_id in custom_ids(&response) {
+            assert_eq!(CUSTOM_ID_LENGTH_MAX, custom_id.chars().count());
+        }
+
+        assert!(Confirmation::new(&"a".repeat(ID_LENGTH_MAX + 1), "Prompt", 0).is_none());
+    }
+
+    #[test]
+    fn answers() {
+        assert_eq!(
+            Some(Answer {
+                id: "purge:13",
+                outcome: Outcome::Confirmed,
+            }),
+            answer("confirm:y:1000:purge:13", 0)
+        );
+        assert_eq!(
+            Some(Answer {
+                id: "purge:13",
+                outcome: Outcome::Cancelled,
+            }),
+            answer("confirm:n:1000:purge:13", 0)
+        );
+        assert_eq!(
+            Some(Answer {
+                id: "",
+                outcome: Outcome::Confirmed,
+            }),
+            answer("confirm:y:1000:", 0)
+        );
+    }
+
+    #[test]
+    fn answer_expiry() {
+        let outcome = |now| answer("confirm:y:1000:purge", now).unwrap().outcome;
+
+        assert_eq!(Outcome::Confirmed, outcome(999));
+        assert_eq!(Outcome::Expired, outcome(1000));
+        assert_eq!(Outcome::Expired, outcome(1001));
+        assert_eq!(
+            Outcome::Expired,
+            answer("confirm:n:1000:purge", 1000).unwrap().outcome
+        );
+    }
+
+    #[test]
+    fn answer_invalid() {
+        assert_eq!(None, answer("other:y:1000:purge", 0));
+        assert_eq!(None, answer("confirm:x:1000:purge", 0));
+        assert_eq!(None, answer("confirm:y:soon:purge", 0));
+        assert_eq!(None, answer("confirm:y:1000", 0));
+        assert_eq!(None, answer("confirm", 0));
+    }
+
+    #[test]
+    fn answer_response() {
+        let answer = answer("confirm:y:1000:purge", 0).unwrap();
+        let response = answer.response("Deleted.");
+        let data = response.data.unwrap();
+
+        assert_eq!(InteractionResponseType::UpdateMessage, response.kind);
+        assert_eq!(Some("Deleted."), data.content.as_deref());
+        assert_eq!(Some(Vec::new()), data.components);
+    }
+}
@@ -0,0 +1,452 @@
+//! Parsing and formatting of emojis in message content and command options.
+//!
+//! Custom emojis are written in message content as `<:name:id>`, or as
+//! `<a:name:id>` when they are animated. [`CustomEmoji::parse`] parses one,
+//! [`custom_emojis`] finds those in a string, and [`reaction_type`] parses a
+//! custom or unicode emoji provided to a command option into a
+//! [`ReactionType`] for components and reactions.
+//!
+//! ```ignore
+//! use twilight_cloudflare_workers::emoji;
+//!
+//! let Some(emoji) = emoji::reaction_type(option_value) else {
+//!     return respond("That isn't an emoji.");
+//! };
+//!
+//! let button = Button {
+//!     emoji: Some(emoji.clone()),
+//!     ..
+//! };
+//! let content = format!("Reacting with {}", emoji::format(&emoji));
+//! ```
+
+use core::fmt::{Display, Formatter, Result as FmtResult};
+use twilight_model::{
+    channel::message::ReactionType,
+    id::{marker::EmojiMarker, Id},
+};
+
+/// Keycap combining character, ending keycap sequences such as `1️⃣`.
+const KEYCAP: char = '\u{20E3}';
+
+/// Maximum number of characters in the name of a custom emoji.
+const NAME_LENGTH_MAX: usize = 32;
+
+/// Minimum number of characters in the name of a custom emoji.
+const NAME_LENGTH_MIN: usize = 2;
+
+/// Character ending a tag sequence, such as in the flag of England.
+const TAG_END: char = '\u{E007F}';
+
+/// Variation selector requesting the emoji presentation of a character.
+const VARIATION_SELECTOR: char = '\u{FE0F}';
+
+/// Zero width joiner between the emojis of a sequence, such as in `👩‍💻`.
+const ZERO_WIDTH_JOINER: char = '\u{200D}';
+
+/// Custom emoji written in message content.
+#[allow(clippy::module_name_repetitions)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct CustomEmoji<'a> {
+    /// Whether the emoji is animated.
+    pub animated: bool,
+    /// ID of the emoji.
+    pub id: Id<EmojiMarker>,
+    /// Name of the emoji.
+    pub name: &'a str,
+}
+
+impl<'a> CustomEmoji<'a> {
+    /// Parse a custom emoji written as `<:name:id>` or `<a:name:id>`, returning
+    /// `None` if the string is anything else.
+    #[must_use = "parsing the emoji is not useful on its own"]
+    pub fn parse(value: &'a str) -> Option<Self> {
+        let inner = value.strip_prefix('<')?.strip_suffix('>')?;
+        let (animated, rest) = match inner.strip_prefix("a:") {
+            Some(rest) => (true, rest),
+            None => (false, inner.strip_prefix(':')?),
+        };
+        let (name, id) = rest.split_once(':')?;
+
+        if !(NAME_LENGTH_MIN..=NAME_LENGTH_MAX).contains(&name.len())
+            || !name
+                .bytes()
+                .all(|byte| byte.is_ascii_alphanumeric() || byte == b'_')
+            || !id.bytes().all(|byte| byte.is_ascii_digit())
+        {
+            return None;
+        }
+
+        Some(Self {
+            animated,
+            id: id.parse().ok()?,
+            name,
+        })
+    }
+
+    /// Reaction type of the emoji, for components and reactions.
+    #[must_use = "creating the reaction type is not useful on its own"]
+    pub fn reaction_type(&self) -> ReactionType {
+        ReactionType::Custom {
+            animated: self.animated,
+            id: self.id,
+            name: Some(self.name.to_owned()),
+        }
+    }
+}
+
+impl Display for CustomEmoji<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write_custom(f, self.animated, self.name, self.id)
+    }
+}
+
+/// Iterator over the custom emojis in a string, created by
+/// [`custom_emojis`].
+#[derive(Clone, Debug)]
+#[must_use = "iterators have no effect if unused"]
+pub struct CustomEmojis<'a> {
+    rest: &'a str,
+}
+
+impl<'a> Iterator for CustomEmojis<'a> {
+    type Item = CustomEmoji<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let start = self.rest.find('<')?;
+            let candidate = &self.rest[start..];
+
+            // Continue from the next possible start of an emoji, which may be
+            // inside of this candidate if it isn't an emoji.
+            let Some(end) = candidate[1..].find(['<', '>']).map(|end| end + 1) else {
+                self.rest = "";
+
+                return None;
+            };
+
+            if candidate.as_bytes()[end] == b'<' {
+                self.rest = &candidate[end..];
+
+                continue;
+            }
+
+            self.rest = &candidate[end + 1..];
+
+            if let Some(emoji) = CustomEmoji::parse(&candidate[..=end]) {
+                return Some(emoji);
+            }
+        }
+    }
+}
+
+/// Find the custom emojis written in a string, such as in message content or
+/// a command option.
+pub const fn custom_emojis(value: &str) -> CustomEmojis<'_> {
+    CustomEmojis { rest: value }
+}
+
+/// Format a reaction type as it is written in message content.
+///
+/// Custom emojis whose name is no longer available are written with a name of
+/// `_`, since Discord displays custom emojis by their ID.
+#[must_use = "formatting the emoji is not useful on its own"]
+pub fn format(reaction_type: &ReactionType) -> String {
+    struct Format<'a>(&'a ReactionType);
+
+    impl Display for Format<'_> {
+        fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+            match self.0 {
+                ReactionType::Custom { animated, id, name } => {
+                    write_custom(f, *animated, name.as_deref().unwrap_or("_"), *id)
+                }
+                ReactionType::Unicode { name } => f.write_str(name),
+            }
+        }
+    }
+
+    Format(reaction_type).to_string()
+}
+
+/// Parse a custom or unicode emoji, such as one provided to a command option,
+/// into a reaction type.
+///
+/// Surrounding whitespace is ignored. Refer to [`is_unicode`] for how
+/// unicode emojis are validated.
+#[must_use = "parsing the emoji is not useful on its own"]
+pub fn reaction_type(value: &str) -> Option<ReactionType> {
+    let value = value.trim();
+
+    if let Some(emoji) = CustomEmoji::parse(value) {
+        return Some(emoji.reaction_type());
+    }
+
+    is_unicode(value).then(|| ReactionType::Unicode {
+        name: value.to_owned(),
+    })
+}
+
+/// Whether a string is a single unicode emoji, such as `👍`, `👍🏽`, `🇳🇿`, `1️⃣`,
+/// or a sequence of emojis joined with zero width joiners such as `👩‍💻`.
+///
+/// The structure of the emoji and the ranges of its characters are checked,
+/// but not whether the emoji is in a version of Unicode that Discord supports.
+#[must_use = "validating the emoji is not useful on its own"]
+pub fn is_unicode(value: &str) -> bool {
+    let mut chars = value.chars().peekable();
+
+    loop {
+        let Some(first) = chars.next() else {
+            return false;
+        };
+
+        if matches!(first, '0'..='9' | '#' | '*') {
+            chars.next_if_eq(&VARIATION_SELECTOR);
+
+            if chars.next() != Some(KEYCAP) {
+                return false;
+            }
+        } else if is_regional_indicator(first) {
+            // Flags of countries are pairs of regional indicators.
+            if !chars.next().is_some_and(is_regional_indicator) {
+                return false;
+            }
+        } else if is_pictographic(first) {
+            chars.next_if_eq(&VARIATION_SELECTOR);
+            chars.next_if(|c| is_skin_tone(*c));
+
+            // Flags of subdivisions are tag sequences ending with a tag end.
+            let mut tagged = false;
+
+            while chars.next_if(|c| is_tag(*c)).is_some() {
+                tagged = true;
+            }
+
+            if tagged && chars.next() != Some(TAG_END) {
+                return false;
+            }
+        } else {
+            return false;
+        }
+
+        match chars.next() {
+            None => return true,
+            Some(ZERO_WIDTH_JOINER) => {}
+            Some(_) => return false,
+        }
+    }
+}
+
+/// Whether a character is an emoji on its own, or can be one when followed
+/// by a variation selector.
+const fn is_pictographic(c: char) -> bool {
+    matches!(
+        c,
+        '\u{A9}'
+            | '\u{AE}'
+            | '\u{203C}'
+            | '\u{2049}'
+            | '\u{2122}'
+            | '\u{2139}'
+            | '\u{2194}'..='\u{21AA}'
+            | '\u{231A}'..='\u{23FF}'
+            | '\u{24C2}'
+            | '\u{25AA}'..='\u{25FE}'
+            | '\u{2600}'..='\u{27BF}'
+            | '\u{2934}'
+            | '\u{2935}'
+            | '\u{2B05}'..='\u{2B55}'
+            | '\u{3030}'
+            | '\u{303D}'
+            | '\u{3297}'
+            | '\u{3299}'
+            | '\u{1F000}'..='\u{1F1E5}'
+            | '\u{1F200}'..='\u{1F3FA}'
+            | '\u{1F400}'..='\u{1FAFF}'
+    )
+}
+
+/// Whether a character is a regional indicator, pairs of which are flags.
+const fn is_regional_indicator(c: char) -> bool {
+    matches!(c, '\u{1F1E6}'..='\u{1F1FF}')
+}
+
+/// Whether a character is a skin tone modifier.
+const fn is_skin_tone(c: char) -> bool {
+    matches!(c, '\u{1F3FB}'..='\u{1F3FF}')
+}
+
+/// Whether a character is a tag of a tag sequence, other than its end.
+const fn is_tag(c: char) -> bool {
+    matches!(c, '\u{E0020}'..='\u{E007E}')
+}
+
+/// Write a custom emoji as it is written in message content.
+fn write_custom(
+    f: &mut Formatter<'_>,
+    animated: bool,
+    name: &str,
+    id: Id<EmojiMarker>,
+) -> FmtResult {
+    f.write_str(if animated { "<a:" } else { "<:" })?;
+    f.write_str(name)?;
+    f.write_str(":")?;
+    Display::fmt(&id, f)?;
+
+    f.write_str(">")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{custom_emojis, format, is_unicode, reaction_type, CustomEmoji};
+    use twilight_model::{channel::message::ReactionType, id::Id};
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    #[test]
+    fn parse_custom() {
+        assert_eq!(
+            CustomEmoji::parse("<:blob_wave:123>"),
+            Some(CustomEmoji {
+                animated: false,
+                id: Id::new(123),
+                name: "blob_wave",
+            })
+        );
+        assert_eq!(
+            CustomEmoji::parse("<a:party:456>"),
+            Some(CustomEmoji {
+                animated: true,
+                id: Id::new(456),
+                name: "party",
+            })
+        );
+
+        for emoji in ["<:blob_wave:123>", "<a:party:456>"] {
+            assert_eq!(CustomEmoji::parse(emoji).unwrap().to_string(), emoji);
+        }
+    }
+
+    #[test]
+    fn parse_custom_malformed_ids() {
+        for value in [
+            "<:name:>",
+            "<:name:0>",
+            "<:name:-1>",
+            "<:name:12a>",
+            "<:name: 12>",
+            "<:name:1.5>",
+            "<:name:18446744073709551616>",
+            "<:name:1:2>",
+        ] {
+            assert_eq!(CustomEmoji::parse(value), None, "{value:?}");
+        }
+    }
+
+    #[test]
+    fn parse_custom_malformed_names() {
+        let longest = format!("<:{}:1>", "a".repeat(32));
+        assert!(CustomEmoji::parse(&longest).is_some());
+        assert!(CustomEmoji::parse("<:ab:1>").is_some());
+
+        let too_long = format!("<:{}:1>", "a".repeat(33));
+
+        for value in [
+            "<:a:1>",
+            "<::1>",
+            too_long.as_str(),
+            "<:na-me:1>",
+            "<:na me:1>",
+            "<:näme:1>",
+            "<b:name:1>",
+            "<name:1>",
+            ":name:1",
+            "<:name:1",
+            ":name:1>",
+            "<<:name:1>>",
+        ] {
+            assert_eq!(CustomEmoji::parse(value), None, "{value:?}");
+        }
+    }
+
+    #[test]
+    fn find_custom() {
+        let emojis = custom_emojis("hi <:a_b:1> <<a:cc:2>> <:x:3> <:dd:0> <:bad")
+            .map(|emoji| (emoji.animated, emoji.id.get(), emoji.name))
+            .collect::<Vec<_>>();
+
+        assert_eq!(emojis, [(false, 1, "a_b"), (true, 2, "cc")]);
+        assert_eq!(custom_emojis("no emojis > here <").count(), 0);
+    }
+
+    #[test]
+    fn unicode() {
+        for value in [
+            "👍",
+            "👍🏽",
+            "❤️",
+            "©",
+            "🇳🇿",
+            "1️⃣",
+            "1⃣",
+            "#️⃣",
+            "👩‍💻",
+            "👩🏽‍💻",
+            "🏴\u{E0067}\u{E0062}\u{E0065}\u{E006E}\u{E0067}\u{E007F}",
+        ] {
+            assert!(is_unicode(value), "{value:?}");
+        }
+
+        for value in [
+            "",
+            "a",
+            "1",
+            "☺️☺️",
+            "👍👍",
+            "👍 ",
+            "🇳",
+            "🏽",
+            "👩‍",
+            "‍💻",
+            "🏴\u{E0067}\u{E0062}",
+            ":thumbsup:",
+        ] {
+            assert!(!is_unicode(value), "{value:?}");
+        }
+    }
+
+    #[test]
+    fn reaction_types() {
+        assert_eq!(
+            reaction_type(" <a:party:456> "),
+            Some(ReactionType::Custom {
+                animated: true,
+                id: Id::new(456),
+                name: Some("party".to_owned()),
+            })
+        );
+        assert_eq!(
+            reaction_type("👍🏽\n"),
+            Some(ReactionType::Unicode {
+                name: "👍🏽".to_owned()
+            })
+        );
+        assert_eq!(reaction_type("<:name:0>"), None);
+        assert_eq!(reaction_type("thumbsup"), None);
+
+        assert_eq!(
+            format(&ReactionType::Custom {
+                animated: false,
+                id: Id::new(1),
+                name: None,
+            }),
+            "<:_:1>"
+        );
+        assert_eq!(
+            format(&ReactionType::Unicode {
+                name: "🇳🇿".to_owned()
+            }),
+            "🇳🇿"
+        );
+    }
+}
@@ -0,0 +1,335 @@
+//! Components of messages that are disabled or removed after a timeout, so
+//! that users can't click on components the bot no longer handles.
+//!
+//! Messages are scheduled to expire in the storage of a Durable Object with
+//! [`schedule`], which sets the object's alarm to the earliest expiry, and
+//! the object's alarm handler edits the messages that have expired through a
+//! [`BotClient`] with [`expire`]. As messages are edited with a bot token,
+//! they can expire after the 15 minutes an interaction token is valid for,
+//! but must be messages the bot can edit, such as those it created with
+//! [`BotClient::create_message`].
+//!
+//! The module manages the object's alarm, so the object shouldn't set its
+//! alarm for anything else, such as payloads scheduled with the `scheduler`
+//! module.
+//!
+//! ```ignore
+//! use twilight_cloudflare_workers::expiring_components;
+//!
+//! #[durable_object]
+//! pub struct Expiries {
+//!     env: Env,
+//!     state: State,
+//! }
+//!
+//! #[durable_object]
+//! impl DurableObject for Expiries {
+//!     // When a message with components is sent and forwarded to the object.
+//!     async fn fetch(&mut self, mut req: Request) -> Result<Response> {
+//!         let message = req.json::<Message>().await?;
+//!         let after = Duration::from_secs(10 * 60);
+//!         expiring_components::schedule(&mut self.state.storage(), &message, after, true).await?;
+//!
+//!         Response::empty()
+//!     }
+//!
+//!     async fn alarm(&mut self) -> Result<Response> {
+//!         let client = BotClient::new(&self.env.secret("DISCORD_TOKEN")?.to_string());
+//!         expiring_components::expire(&mut self.state.storage(), &client).await?;
+//!
+//!         Response::empty()
+//!     }
+//! }
+//! ```
+//!
+//! [`BotClient`]: crate::rest::BotClient
+//! [`BotClient::create_message`]: crate::rest::BotClient::create_message
+
+use crate::rest::{duration_millis, BotClient, RestError};
+use core::time::Duration;
+use serde::{Deserialize, Serialize};
+use twilight_model::{
+    channel::{message::Component, Message},
+    http::interaction::InteractionResponseData,
+    id::{
+        marker::{ChannelMarker, MessageMarker},
+        Id,
+    },
+};
+use worker::{Date, ListOptions, Storage};
+
+/// Maximum number of messages edited by a single alarm, keeping within the
+/// subrequest limit of an invocation.
+const EXPIRIES_PER_ALARM: usize = 40;
+
+/// Prefix of the keys expiries are stored under, followed by the time they
+/// expire at and the ID of their message.
+const KEY_PREFIX: &str = "expiring-components:";
+
+/// Message whose components expire, stored until it expires.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+struct Expiry {
+    /// ID of the channel the message is in.
+    channel_id: Id<ChannelMarker>,
+    /// Components of the message to edit it with once disabled, or `None` if
+    /// the components are removed.
+    components: Option<Vec<Component>>,
+    /// ID of the message.
+    message_id: Id<MessageMarker>,
+}
+
+/// Schedule the components of a message to be disabled, or removed if
+/// `disable` is `false`, after a duration.
+///
+/// The alarm of the Durable Object the storage belongs to is set to the
+/// expiry if it isn't already set to an earlier time.
+///
+/// # Errors
+///
+/// Returns an error if the expiry could not be stored or the alarm could not
+/// be set.
+pub async fn schedule(
+    storage: &mut Storage,
+    message: &Message,
+    after: Duration,
+    disable: bool,
+) -> worker::Result<()> {
+    let expires_at = Date::now()
+        .as_millis()
+        .saturating_add(duration_millis(after));
+    let expiry = Expiry {
+        channel_id: message.channel_id,
+        components: disable.then(|| disabled(&message.components)),
+        message_id: message.id,
+    };
+    let value = serde_json::to_string(&expiry).map_err(|source| source.to_string())?;
+    storage.put(&key(expires_at, message.id), value).await?;
+
+    let alarm = storage.get_alarm().await?;
+
+    if alarm.map_or(true, |alarm| {
+        u64::try_from(alarm).map_or(true, |alarm| alarm > expires_at)
+    }) {
+        storage.set_alarm(after).await?;
+    }
+
+    Ok(())
+}
+
+/// Edit the messages whose components have expired, returning the messages
+/// that couldn't be edited along with why, and set the alarm of the Durable
+/// Object the storage belongs to for the next expiry.
+///
+/// Up to 40 messages are edited at a time, with the alarm set to run again
+/// immediately if more have expired. Requests that would be rate limited are
+/// delayed until the rate limit resets. Messages that couldn't be edited,
+/// such as because they were deleted, aren't retried.
+///
+/// # Errors
+///
+/// Returns an error if the expiries could not be retrieved or removed, or if
+/// the alarm could not be set.
+pub async fn expire(
+    storage: &mut Storage,
+    client: &BotClient,
+) -> worker::Result<Vec<(Id<MessageMarker>, RestError)>> {
+    let client = client.clone().delay_rate_limited(true);
+    let now = Date::now().as_millis();
+    let end = key_prefix(now.saturating_add(1));
+    let entries = storage
+        .list_with_options(
+            ListOptions::new()
+                .start(KEY_PREFIX)
+                .end(&end)
+                .limit(EXPIRIES_PER_ALARM),
+        )
+        .await?;
+    let mut expired = Vec::new();
+    entries.for_each(&mut |value, key| expired.push((key.as_string(), value.as_string())));
+
+    let mut failures = Vec::new();
+    let mut keys = Vec::with_capacity(expired.len());
+
+    for (key, value) in expired {
+        let Some(key) = key else {
+            continue;
+        };
+
+        if let Some(expiry) = value.and_then(|value| serde_json::from_str::<Expiry>(&value).ok()) {
+            let data = InteractionResponseData {
+                components: Some(expiry.components.unwrap_or_default()),
+                ..InteractionResponseData::default()
+            };
+
+            if let Err(source) = client
+                .update_message(expiry.channel_id, expiry.message_id, &data)
+                .await
+            {
+                failures.push((expiry.message_id, source));
+            }
+        }
+
+        keys.push(key);
+    }
+
+    storage.delete_multiple(keys).await?;
+
+    let mut next = None;
+    storage
+        .list_with_options(ListOptions::new().prefix(KEY_PREFIX).limit(1))
+        .await?
+        .for_each(&mut |_, key| next = key.as_string().and_then(|key| expires_at(&key)));
+
+    if let Some(next) = next {
+        let now = Date::now().as_millis();
+        storage
+            .set_alarm(Duration::from_millis(next.saturating_sub(now)))
+            .await?;
+    }
+
+    Ok(failures)
+}
+
+/// Copy of components with their buttons and select menus disabled.
+fn disabled(components: &[Component]) -> Vec<Component> {
+    components
+        .iter()
+        .cloned()
+        .map(|component| match component {
+            Component::ActionRow(mut row) => {
+                row.components = disabled(&row.components);
+
+                Component::ActionRow(row)
+            }
+            Component::Button(mut button) => {
+                button.disabled = true;
+
+                Component::Button(button)
+            }
+            Component::SelectMenu(mut menu) => {
+                menu.disabled = true;
+
+                Component::SelectMenu(menu)
+            }
+            other => other,
+        })
+        .collect()
+}
+
+/// Time in milliseconds since the Unix epoch that the key of an expiry
+/// expires at.
+fn expires_at(key: &str) -> Option<u64> {
+    key.strip_prefix(KEY_PREFIX)?
+        .split(':')
+        .next()?
+        .parse()
+        .ok()
+}
+
+/// Key an expiry is stored under, ordered by the time it expires at.
+fn key(expires_at: u64, message_id: Id<MessageMarker>) -> String {
+    format!("{}{message_id}", key_prefix(expires_at))
+}
+
+/// Prefix of the keys of expiries that expire at a time, which sorts before
+/// the keys of expiries that expire later.
+fn key_prefix(expires_at: u64) -> String {
+    format!("{KEY_PREFIX}{expires_at:020}:")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{disabled, expires_at, key, key_prefix, Expiry};
+    use twilight_model::{
+        channel::message::component::{
+            ActionRow, Button, ButtonStyle, Component, SelectMenu, TextInput, TextInputStyle,
+        },
+        id::Id,
+    };
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    fn button() -> Component {
+        Component::Button(Button {
+            custom_id: Some("button".to_owned()),
+            disabled: false,
+            emoji: None,
+            label: Some("Click".to_owned()),
+            style: ButtonStyle::Primary,
+            url: None,
+        })
+    }
+
+    fn select_menu() -> Component {
+        Component::SelectMenu(SelectMenu {
+            custom_id: "menu".to_owned(),
+            disabled: false,
+            max_values: None,
+            min_values: None,
+            options: Vec::new(),
+            placeholder: None,
+        })
+    }
+
+    #[test]
+    fn disables_nested_components() {
+        let components = [Component::ActionRow(ActionRow {
+            components: vec![button(), select_menu()],
+        })];
+        let Component::ActionRow(row) = &disabled(&components)[0] else {
+            panic!("action row should be kept");
+        };
+
+        assert!(row.components.iter().all(|component| match component {
+            Component::Button(button) => button.disabled,
+            Component::SelectMenu(menu) => menu.disabled,
+            _ => false,
+        }));
+    }
+
+    #[test]
+    fn keeps_other_components() {
+        let input = Component::TextInput(TextInput {
+            custom_id: "input".to_owned(),
+            label: "Input".to_owned(),
+            max_length: None,
+            min_length: None,
+            placeholder: None,
+            required: None,
+            style: TextInputStyle::Short,
+            value: None,
+        });
+
+        assert_eq!(vec![input.clone()], disabled(&[input]));
+    }
+
+    #[test]
+    fn keys_sort_by_expiry() {
+        let earlier = key(999, Id::new(u64::MAX));
+        let later = key(1000, Id::new(1));
+
+        assert!(earlier < later);
+        assert!(earlier < key_prefix(1000));
+        assert!(later >= key_prefix(1000));
+        assert!(later < key_prefix(1001));
+    }
+
+    #[test]
+    fn keys_parse_expiry() {
+        assert_eq!(Some(1000), expires_at(&key(1000, Id::new(1))));
+        assert_eq!(Some(u64::MAX), expires_at(&key(u64::MAX, Id::new(1))));
+        assert_eq!(None, expires_at("other:1000:1"));
+    }
+
+    #[test]
+    fn expiry_round_trip() {
+        let expiry = Expiry {
+            channel_id: Id::new(1),
+            components: Some(disabled(&[button()])),
+            message_id: Id::new(2),
+        };
+        let json = serde_json::to_string(&expiry).unwrap();
+
+        assert_eq!(expiry, serde_json::from_str(&json).unwrap());
+    }
+}
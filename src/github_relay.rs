@@ -0,0 +1,794 @@
+//! Relay of GitHub webhook deliveries to Discord, rendering selected events
+//! as embeds and posting them to a channel or a Discord webhook.
+//!
+//! Deliveries are verified with the webhook's secret as by
+//! [`hmac_webhooks::verify_github`]. Pushes, opened, closed, and reopened
+//! issues, and published releases are rendered, while other events and
+//! actions, including the `ping` event GitHub sends when the webhook is
+//! created, are acknowledged without posting anything.
+//!
+//! ```ignore
+//! use twilight_cloudflare_workers::github_relay::{Destination, Event, Relay};
+//!
+//! let relay = Relay::new(
+//!     secret.as_bytes(),
+//!     Destination::Channel {
+//!         channel_id,
+//!         client: &client,
+//!     },
+//! )
+//! .events(&[Event::Push, Event::Release]);
+//!
+//! if req.path() == "/github" {
+//!     return Ok(relay.handle(&mut req).await.unwrap_or_else(|source| source.response()));
+//! }
+//! ```
+//!
+//! [`hmac_webhooks::verify_github`]: crate::hmac_webhooks::verify_github
+
+use crate::{
+    build_response, hmac_webhooks,
+    rest::{self, Body, BotClient, RestError},
+    table, ResponseBody,
+};
+use core::fmt::{Display, Formatter, Result as FmtResult};
+use serde::{de::DeserializeOwned, Deserialize};
+use std::error::Error;
+use twilight_model::{
+    channel::message::{
+        embed::{Embed, EmbedAuthor},
+        AllowedMentions,
+    },
+    http::interaction::InteractionResponseData,
+    id::{
+        marker::{ChannelMarker, WebhookMarker},
+        Id,
+    },
+};
+use worker::{Method, Request, Response};
+
+/// Maximum number of commits listed in the embed of a push.
+const COMMITS_MAX: usize = 5;
+
+/// Maximum number of characters of a commit message's first line listed in
+/// the embed of a push.
+const COMMIT_MESSAGE_LENGTH_MAX: usize = 72;
+
+/// Maximum number of characters of an issue's or release's body in the
+/// description of its embed.
+const DESCRIPTION_LENGTH_MAX: usize = 500;
+
+/// Color of the embeds of closed issues.
+const ISSUE_CLOSED_COLOR: u32 = 0x0089_57E5;
+
+/// Color of the embeds of opened and reopened issues.
+const ISSUE_OPENED_COLOR: u32 = 0x003F_B950;
+
+/// Color of the embeds of pushes.
+const PUSH_COLOR: u32 = 0x0009_69DA;
+
+/// Color of the embeds of releases.
+const RELEASE_COLOR: u32 = 0x00BF_8700;
+
+/// Maximum number of characters in the title of an embed.
+const TITLE_LENGTH_MAX: usize = 256;
+
+/// Where rendered events are posted to.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Destination<'a> {
+    /// Channel to create messages in with a bot client.
+    Channel {
+        /// ID of the channel.
+        channel_id: Id<ChannelMarker>,
+        /// Client to create the messages with.
+        client: &'a BotClient,
+    },
+    /// Discord webhook to execute.
+    Webhook {
+        /// ID of the webhook.
+        id: Id<WebhookMarker>,
+        /// Token of the webhook.
+        token: &'a str,
+    },
+}
+
+/// Type of GitHub event that can be relayed.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Event {
+    /// Issue was opened, closed, or reopened.
+    Issues,
+    /// Commits were pushed to a branch or tag.
+    Push,
+    /// Release was published.
+    Release,
+}
+
+impl Event {
+    /// Parse the name of an event, as in the `X-GitHub-Event` header of
+    /// deliveries.
+    #[must_use = "parsing the name of an event is not useful on its own"]
+    pub fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "issues" => Self::Issues,
+            "push" => Self::Push,
+            "release" => Self::Release,
+            _ => return None,
+        })
+    }
+
+    /// Name of the event, as in the `X-GitHub-Event` header of deliveries.
+    #[must_use = "retrieving the name of an event is not useful on its own"]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Issues => "issues",
+            Self::Push => "push",
+            Self::Release => "release",
+        }
+    }
+}
+
+/// Relay of GitHub webhook deliveries to a [`Destination`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[must_use = "relays have no effect if unused"]
+pub struct Relay<'a> {
+    destination: Destination<'a>,
+    events: &'a [Event],
+    secret: &'a [u8],
+}
+
+impl<'a> Relay<'a> {
+    /// Create a new relay of deliveries signed with the webhook's secret,
+    /// relaying all types of events.
+    pub const fn new(secret: &'a [u8], destination: Destination<'a>) -> Self {
+        Self {
+            destination,
+            events: &[Event::Issues, Event::Push, Event::Release],
+            secret,
+        }
+    }
+
+    /// Set the types of events that are relayed, acknowledging deliveries of
+    /// other types without posting them.
+    pub const fn events(mut self, events: &'a [Event]) -> Self {
+        self.events = events;
+
+        self
+    }
+
+    /// Verify a delivery and render it as an embed, from the values of its
+    /// `X-GitHub-Event` and `X-Hub-Signature-256` headers and its body.
+    ///
+    /// Returns `None` if the event isn't relayed, such as when its type isn't
+    /// selected or its action isn't rendered.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of type [`Deserializing`] if the body could not be
+    /// deserialized as the event's payload.
+    ///
+    /// Returns an error of type [`Verifying`] if the delivery is not signed
+    /// with the secret.
+    ///
+    /// [`Deserializing`]: RelayErrorType::Deserializing
+    /// [`Verifying`]: RelayErrorType::Verifying
+    pub fn render(
+        &self,
+        event: &str,
+        signature: &str,
+        body: &[u8],
+    ) -> Result<Option<Embed>, RelayError> {
+        hmac_webhooks::verify_github(self.secret, signature, body).map_err(|source| {
+            RelayError {
+                kind: RelayErrorType::Verifying,
+                source: Some(Box::new(source)),
+            }
+        })?;
+
+        let Some(event) = Event::from_name(event).filter(|event| self.events.contains(event))
+        else {
+            return Ok(None);
+        };
+
+        Ok(match event {
+            Event::Issues => render_issues(deserialize(body)?),
+            Event::Push => render_push(deserialize(body)?),
+            Event::Release => render_release(deserialize(body)?),
+        })
+    }
+
+    /// Handle a delivery, posting its embed to the destination if it is
+    /// relayed.
+    ///
+    /// The returned response has a status code of 204 (No Content) and
+    /// acknowledges the delivery to GitHub.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of type [`Posting`] if the embed could not be posted
+    /// to the destination.
+    ///
+    /// Returns an error of type [`Reading`] if the headers or body of the
+    /// request could not be read.
+    ///
+    /// Refer to the documentation for [`render`] for other errors.
+    ///
+    /// [`Posting`]: RelayErrorType::Posting
+    /// [`Reading`]: RelayErrorType::Reading
+    /// [`render`]: Self::render
+    pub async fn handle(&self, req: &mut Request) -> Result<Response, RelayError> {
+        let header = |name| {
+            req.headers()
+                .get(name)
+                .map(Option::unwrap_or_default)
+                .map_err(RelayError::reading)
+        };
+        let event = header("X-GitHub-Event")?;
+        let signature = header("X-Hub-Signature-256")?;
+        let body = req.bytes().await.map_err(RelayError::reading)?;
+
+        if let Some(embed) = self.render(&event, &signature, &body)? {
+            let data = InteractionResponseData {
+                allowed_mentions: Some(AllowedMentions::default()),
+                embeds: Some(vec![embed]),
+                ..InteractionResponseData::default()
+            };
+
+            self.post(&data).await.map_err(|source| RelayError {
+                kind: RelayErrorType::Posting,
+                source: Some(Box::new(source)),
+            })?;
+        }
+
+        Ok(build_response(ResponseBody::Empty, 204, &[]))
+    }
+
+    /// Post a message to the destination.
+    async fn post(&self, data: &InteractionResponseData) -> Result<(), RestError> {
+        match &self.destination {
+            Destination::Channel { channel_id, client } => {
+                client.create_message(*channel_id, data).await.map(drop)
+            }
+            Destination::Webhook { id, token } => {
+                let path = format!("/webhooks/{id}/{token}");
+
+                rest::send(Method::Post, &path, Some(Body::json(data)?), &[], false)
+                    .await
+                    .map(drop)
+            }
+        }
+    }
+}
+
+/// Commit in the payload of a push.
+#[derive(Deserialize)]
+struct Commit {
+    author: CommitAuthor,
+    id: String,
+    message: String,
+    url: String,
+}
+
+/// Author of a commit.
+#[derive(Deserialize)]
+struct CommitAuthor {
+    name: String,
+}
+
+/// Issue in the payload of an issues event.
+#[derive(Deserialize)]
+struct Issue {
+    #[serde(default)]
+    body: Option<String>,
+    html_url: String,
+    number: u64,
+    title: String,
+}
+
+/// Payload of an issues event.
+#[derive(Deserialize)]
+struct IssuesPayload {
+    action: String,
+    issue: Issue,
+    repository: Repository,
+    sender: Sender,
+}
+
+/// Payload of a push event.
+#[derive(Deserialize)]
+struct PushPayload {
+    commits: Vec<Commit>,
+    compare: String,
+    #[serde(default)]
+    deleted: bool,
+    #[serde(rename = "ref")]
+    git_ref: String,
+    repository: Repository,
+    sender: Sender,
+}
+
+/// Release in the payload of a release event.
+#[derive(Deserialize)]
+struct Release {
+    #[serde(default)]
+    body: Option<String>,
+    html_url: String,
+    #[serde(default)]
+    name: Option<String>,
+    tag_name: String,
+}
+
+/// Payload of a release event.
+#[derive(Deserialize)]
+struct ReleasePayload {
+    action: String,
+    release: Release,
+    repository: Repository,
+    sender: Sender,
+}
+
+/// Repository an event occurred in.
+#[derive(Deserialize)]
+struct Repository {
+    full_name: String,
+}
+
+/// User who triggered an event.
+#[derive(Deserialize)]
+struct Sender {
+    avatar_url: String,
+    html_url: String,
+    login: String,
+}
+
+/// Deserialize the payload of an event.
+fn deserialize<T: DeserializeOwned>(body: &[u8]) -> Result<T, RelayError> {
+    serde_json::from_slice(body).map_err(|source| RelayError {
+        kind: RelayErrorType::Deserializing,
+        source: Some(Box::new(source)),
+    })
+}
+
+/// Create an embed of an event triggered by a user.
+fn embed(
+    sender: Sender,
+    color: u32,
+    title: &str,
+    url: String,
+    description: Option<String>,
+) -> Embed {
+    Embed {
+        author: Some(EmbedAuthor {
+            icon_url: Some(sender.avatar_url),
+            name: sender.login,
+            proxy_icon_url: None,
+            url: Some(sender.html_url),
+        }),
+        color: Some(color),
+        description: description.filter(|description| !description.is_empty()),
+        fields: Vec::new(),
+        footer: None,
+        image: None,
+        kind: "rich".to_owned(),
+        provider: None,
+        thumbnail: None,
+        timestamp: None,
+        title: Some(table::truncate(title, TITLE_LENGTH_MAX)),
+        url: Some(url),
+        video: None,
+    }
+}
+
+/// Render an issues event, if its action is opening, closing, or reopening
+/// the issue.
+fn render_issues(payload: IssuesPayload) -> Option<Embed> {
+    let IssuesPayload {
+        action,
+        issue,
+        repository,
+        sender,
+    } = payload;
+    let color = match action.as_str() {
+        "closed" => ISSUE_CLOSED_COLOR,
+        "opened" | "reopened" => ISSUE_OPENED_COLOR,
+        _ => return None,
+    };
+    let title = format!(
+        "[{}] Issue {action}: #{} {}",
+        repository.full_name, issue.number, issue.title
+    );
+    let description = issue
+        .body
+        .filter(|_| action == "opened")
+        .map(|body| table::truncate(&body, DESCRIPTION_LENGTH_MAX));
+
+    Some(embed(sender, color, &title, issue.html_url, description))
+}
+
+/// Render a push event, if it pushed commits.
+fn render_push(payload: PushPayload) -> Option<Embed> {
+    if payload.deleted || payload.commits.is_empty() {
+        return None;
+    }
+
+    let branch = payload
+        .git_ref
+        .strip_prefix("refs/heads/")
+        .or_else(|| payload.git_ref.strip_prefix("refs/tags/"))
+        .unwrap_or(&payload.git_ref);
+    let count = payload.commits.len();
+    let title = format!(
+        "[{}:{branch}] {count} new commit{}",
+        payload.repository.full_name,
+        if count == 1 { "" } else { "s" },
+    );
+    let mut lines = payload
+        .commits
+        .iter()
+        .take(COMMITS_MAX)
+        .map(|commit| {
+            let summary = commit.message.lines().next().unwrap_or_default();
+
+            format!(
+                "[`{}`]({}) {} - {}",
+                commit.id.get(..7).unwrap_or(&commit.id),
+                commit.url,
+                table::truncate(summary, COMMIT_MESSAGE_LENGTH_MAX),
+                commit.author.name,
+            )
+        })
+        .collect::<Vec<_>>();
+
+    if count > COMMITS_MAX {
+        lines.push(format!("and {} more", count - COMMITS_MAX));
+    }
+
+    Some(embed(
+        payload.sender,
+        PUSH_COLOR,
+        &title,
+        payload.compare,
+        Some(lines.join("\n")),
+    ))
+}
+
+/// Render a release event, if its action is publishing the release.
+fn render_release(payload: ReleasePayload) -> Option<Embed> {
+    if payload.action != "published" {
+        return None;
+    }
+
+    let Release {
+        body,
+        html_url,
+        name,
+        tag_name,
+    } = payload.release;
+    let title = format!(
+        "[{}] New release published: {}",
+        payload.repository.full_name,
+        name.filter(|name| !name.is_empty()).unwrap_or(tag_name),
+    );
+    let description = body.map(|body| table::truncate(&body, DESCRIPTION_LENGTH_MAX));
+
+    Some(embed(
+        payload.sender,
+        RELEASE_COLOR,
+        &title,
+        html_url,
+        description,
+    ))
+}
+
+/// GitHub webhook delivery could not be relayed.
+#[derive(Debug)]
+pub struct RelayError {
+    kind: RelayErrorType,
+    source: Option<Box<dyn Error>>,
+}
+
+impl RelayError {
+    /// Immutable reference to the type of error that occurred.
+    #[must_use = "retrieving the type has no effect if left unused"]
+    pub const fn kind(&self) -> &RelayErrorType {
+        &self.kind
+    }
+
+    /// Consume the error, returning the source error if there is any.
+    #[must_use = "consuming the error and retrieving the source has no effect if left unused"]
+    pub fn into_source(self) -> Option<Box<dyn Error>> {
+        self.source
+    }
+
+    /// Consume the error, returning the owned error type and the source error.
+    #[must_use = "consuming the error into its parts has no effect if left unused"]
+    pub fn into_parts(self) -> (RelayErrorType, Option<Box<dyn Error>>) {
+        (self.kind, self.source)
+    }
+
+    /// Create a response for the error.
+    ///
+    /// If the variant is [`RelayErrorType::Verifying`] then the returned
+    /// response has a status code of 401 (Unauthorized), if the variant is
+    /// [`RelayErrorType::Deserializing`] or [`RelayErrorType::Reading`] then
+    /// the status code is 400 (Bad Request), otherwise the status code is 502
+    /// (Bad Gateway) so that GitHub reports the delivery as failed.
+    #[must_use = "created responses must be used to actually send the response"]
+    pub fn response(&self) -> Response {
+        let status = match self.kind {
+            RelayErrorType::Deserializing | RelayErrorType::Reading => 400,
+            RelayErrorType::Posting => 502,
+            RelayErrorType::Verifying => 401,
+        };
+        let body = self.to_string().into_bytes();
+
+        build_response(
+            ResponseBody::Body(body),
+            status,
+            &[("Content-Type", "text/plain")],
+        )
+    }
+
+    /// Create an error of type [`RelayErrorType::Reading`].
+    fn reading(source: worker::Error) -> Self {
+        Self {
+            kind: RelayErrorType::Reading,
+            source: Some(Box::new(source)),
+        }
+    }
+}
+
+impl Display for RelayError {
+    #[cfg(feature = "minimal-errors")]
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.write_str("error ")?;
+
+        Display::fmt(&self.kind.code(), f)
+    }
+
+    #[cfg(not(feature = "minimal-errors"))]
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self.kind {
+            RelayErrorType::Deserializing => f.write_str("failed to deserialize event payload"),
+            RelayErrorType::Posting => f.write_str("failed to post event to Discord"),
+            RelayErrorType::Reading => f.write_str("failed to read delivery"),
+            RelayErrorType::Verifying => f.write_str("delivery signature is invalid"),
+        }
+    }
+}
+
+impl Error for RelayError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.source
+            .as_ref()
+            .map(|source| &**source as &(dyn Error + 'static))
+    }
+}
+
+/// Type of [`RelayError`] that occurred.
+#[derive(Debug)]
+pub enum RelayErrorType {
+    /// Failed to deserialize the body of the delivery as the event's payload.
+    Deserializing,
+    /// Failed to post the rendered event to the destination.
+    Posting,
+    /// Failed to read the headers or body of the delivery.
+    Reading,
+    /// Delivery is not signed with the webhook's secret.
+    Verifying,
+}
+
+impl RelayErrorType {
+    /// Numeric code of the type of error, which errors are displayed as with
+    /// the `minimal-errors` feature.
+    ///
+    /// Refer to [`ProcessRequestErrorType::code`] for more information.
+    ///
+    /// [`ProcessRequestErrorType::code`]: crate::ProcessRequestErrorType::code
+    #[must_use = "retrieving the code is not useful on its own"]
+    pub const fn code(&self) -> u16 {
+        match self {
+            Self::Deserializing => 29,
+            Self::Posting => 30,
+            Self::Reading => 31,
+            Self::Verifying => 32,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Destination, Event, Relay, RelayErrorType, COMMITS_MAX};
+    use hmac::{Hmac, Mac, NewMac};
+    use serde_json::{json, Value};
+    use sha2::Sha256;
+    use twilight_model::{channel::message::embed::Embed, id::Id};
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    const SECRET: &[u8] = b"secret";
+
+    const fn relay() -> Relay<'static> {
+        Relay::new(
+            SECRET,
+            Destination::Webhook {
+                id: Id::new(1),
+                token: "token",
+            },
+        )
+    }
+
+    /// Sign a payload and render it with a relay.
+    fn render(relay: &Relay<'_>, event: &str, payload: &Value) -> Option<Embed> {
+        let body = serde_json::to_vec(payload).unwrap();
+        let mut mac = Hmac::<Sha256>::new_from_slice(SECRET).unwrap();
+        mac.update(&body);
+        let signature = format!("sha256={}", hex::encode(mac.finalize().into_bytes()));
+
+        relay.render(event, &signature, &body).unwrap()
+    }
+
+    fn sender() -> Value {
+        json!({
+            "avatar_url": "https://avatars.githubusercontent.com/u/1",
+            "html_url": "https://github.com/octocat",
+            "login": "octocat",
+        })
+    }
+
+    fn push(commits: usize) -> Value {
+        json!({
+            "commits": (0..commits).map(|index| json!({
+                "author": { "name": "Octo Cat" },
+                "id": format!("{index:040x}"),
+                "message": format!("Commit {index}\n\nDetails"),
+                "url": format!("https://github.com/octo/repo/commit/{index:040x}"),
+            })).collect::<Vec<_>>(),
+            "compare": "https://github.com/octo/repo/compare/a...b",
+            "deleted": false,
+            "ref": "refs/heads/main",
+            "repository": { "full_name": "octo/repo" },
+            "sender": sender(),
+        })
+    }
+
+    fn issue(action: &str) -> Value {
+        json!({
+            "action": action,
+            "issue": {
+                "body": "It's broken.",
+                "html_url": "https://github.com/octo/repo/issues/7",
+                "number": 7,
+                "title": "Bug",
+            },
+            "repository": { "full_name": "octo/repo" },
+            "sender": sender(),
+        })
+    }
+
+    fn release(action: &str) -> Value {
+        json!({
+            "action": action,
+            "release": {
+                "body": "Changes.",
+                "html_url": "https://github.com/octo/repo/releases/tag/v1.0.0",
+                "name": "",
+                "tag_name": "v1.0.0",
+            },
+            "repository": { "full_name": "octo/repo" },
+            "sender": sender(),
+        })
+    }
+
+    #[test]
+    fn event_names() {
+        for event in [Event::Issues, Event::Push, Event::Release] {
+            assert_eq!(Some(event), Event::from_name(event.name()));
+        }
+
+        assert_eq!(None, Event::from_name("ping"));
+    }
+
+    #[test]
+    fn push_renders_commits() {
+        let embed = render(&relay(), "push", &push(1)).unwrap();
+
+        assert_eq!(
+            Some("[octo/repo:main] 1 new commit"),
+            embed.title.as_deref()
+        );
+        assert_eq!(
+            Some("https://github.com/octo/repo/compare/a...b"),
+            embed.url.as_deref()
+        );
+        assert_eq!(
+            Some(
+                "[`0000000`](https://github.com/octo/repo/commit/0000000000000000000000000000000000000000) \
+                 Commit 0 - Octo Cat"
+            ),
+            embed.description.as_deref()
+        );
+        assert_eq!("octocat", embed.author.unwrap().name);
+    }
+
+    #[test]
+    fn push_lists_limited_commits() {
+        let embed = render(&relay(), "push", &push(COMMITS_MAX + 2)).unwrap();
+        let description = embed.description.unwrap();
+
+        assert_eq!(COMMITS_MAX + 1, description.lines().count());
+        assert!(description.ends_with("and 2 more"));
+        assert_eq!(
+            Some("[octo/repo:main] 7 new commits"),
+            embed.title.as_deref()
+        );
+    }
+
+    #[test]
+    fn push_without_commits() {
+        assert!(render(&relay(), "push", &push(0)).is_none());
+
+        let mut deleted = push(1);
+        deleted["deleted"] = json!(true);
+
+        assert!(render(&relay(), "push", &deleted).is_none());
+    }
+
+    #[test]
+    fn issues_render_actions() {
+        let opened = render(&relay(), "issues", &issue("opened")).unwrap();
+
+        assert_eq!(
+            Some("[octo/repo] Issue opened: #7 Bug"),
+            opened.title.as_deref()
+        );
+        assert_eq!(Some("It's broken."), opened.description.as_deref());
+
+        let closed = render(&relay(), "issues", &issue("closed")).unwrap();
+
+        assert_eq!(
+            Some("[octo/repo] Issue closed: #7 Bug"),
+            closed.title.as_deref()
+        );
+        assert!(closed.description.is_none());
+        assert!(render(&relay(), "issues", &issue("labeled")).is_none());
+    }
+
+    #[test]
+    fn release_renders_published() {
+        let embed = render(&relay(), "release", &release("published")).unwrap();
+
+        assert_eq!(
+            Some("[octo/repo] New release published: v1.0.0"),
+            embed.title.as_deref()
+        );
+        assert_eq!(Some("Changes."), embed.description.as_deref());
+        assert!(render(&relay(), "release", &release("created")).is_none());
+    }
+
+    #[test]
+    fn unselected_events() {
+        let relay = relay().events(&[Event::Push]);
+
+        assert!(render(&relay, "issues", &issue("opened")).is_none());
+        assert!(render(&relay, "ping", &json!({ "zen": "Keep it simple." })).is_none());
+        assert!(render(&relay, "push", &push(1)).is_some());
+    }
+
+    #[test]
+    fn invalid_signature() {
+        let error = relay().render("push", "sha256=00", b"{}").unwrap_err();
+
+        assert!(matches!(error.kind(), RelayErrorType::Verifying));
+    }
+
+    #[test]
+    fn malformed_payload() {
+        let body = b"{}";
+        let mut mac = Hmac::<Sha256>::new_from_slice(SECRET).unwrap();
+        mac.update(body);
+        let signature = format!("sha256={}", hex::encode(mac.finalize().into_bytes()));
+        let error = relay().render("push", &signature, body).unwrap_err();
+
+        assert!(matches!(error.kind(), RelayErrorType::Deserializing));
+    }
+}
@@ -0,0 +1,604 @@
+//! Typed per-guild configuration stored in KV, with caching and migrations
+//! between versions of its schema.
+//!
+//! Configurations are stored as JSON under the name of the guild's ID with a
+//! prefix, along with the version of the schema they were stored with. Values
+//! stored with an older version are migrated when they are retrieved, one
+//! version at a time.
+//!
+//! Only KV is supported, as the `worker` crate this crate depends on has no
+//! bindings for D1.
+//!
+//! ```ignore
+//! use twilight_cloudflare_workers::guild_config::GuildConfig;
+//!
+//! #[derive(Default, Deserialize, Serialize)]
+//! struct Settings {
+//!     log_channel_id: Option<Id<ChannelMarker>>,
+//! }
+//!
+//! let kv = env.kv("CONFIG")?;
+//! let settings = GuildConfig::<Settings>::new(&kv, "CONFIG", 1);
+//!
+//! settings
+//!     .update(guild_id, |current| Settings {
+//!         log_channel_id: Some(channel_id),
+//!         ..current.unwrap_or_default()
+//!     })
+//!     .await?;
+//! ```
+
+use core::{
+    fmt::{Display, Formatter, Result as FmtResult},
+    marker::PhantomData,
+    time::Duration,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+use std::{cell::RefCell, error::Error};
+use worker::{kv::KvStore, Date};
+
+/// Maximum number of configurations cached by an isolate, beyond which the
+/// least recently cached configurations are evicted.
+const CACHE_CAPACITY: usize = 256;
+
+thread_local! {
+    /// Configurations loaded from KV, along with the namespaces and names they
+    /// are stored under and the time in milliseconds since the Unix epoch they
+    /// expire at, in the order they were cached.
+    ///
+    /// Configurations that aren't stored are cached as `None`.
+    static LOADED: RefCell<Vec<Cached>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Configuration cached by an isolate, along with the namespace and name it
+/// is stored under and the time in milliseconds since the Unix epoch it
+/// expires at.
+type Cached = (String, Option<String>, u64);
+
+/// Configuration as it is stored in KV.
+#[derive(Deserialize, Serialize)]
+struct Stored<T> {
+    value: T,
+    version: u32,
+}
+
+/// Typed configuration of guilds stored in KV.
+///
+/// KV is eventually consistent and doesn't support transactions, so updates
+/// made concurrently or in other locations may overwrite each other, and may
+/// take up to a minute to be visible elsewhere.
+#[allow(clippy::module_name_repetitions)]
+#[must_use = "guild configurations have no effect if unused"]
+pub struct GuildConfig<'a, T> {
+    cache_ttl: Duration,
+    kv: &'a KvStore,
+    migrate: Option<fn(u32, Value) -> Option<Value>>,
+    namespace: &'a str,
+    phantom: PhantomData<fn() -> T>,
+    prefix: &'a str,
+    version: u32,
+}
+
+impl<'a, T: DeserializeOwned + Serialize> GuildConfig<'a, T> {
+    /// Create a new configuration stored in a KV namespace with the name of
+    /// its binding and the current version of its schema.
+    ///
+    /// Cached configurations are kept apart by the name of the binding, so
+    /// configurations with the same prefix in different namespaces don't
+    /// serve each other's values.
+    pub const fn new(kv: &'a KvStore, namespace: &'a str, version: u32) -> Self {
+        Self {
+            cache_ttl: Duration::from_secs(60),
+            kv,
+            migrate: None,
+            namespace,
+            phantom: PhantomData,
+            prefix: "guild_config",
+            version,
+        }
+    }
+
+    /// Set how long configurations loaded from KV are reused for by the
+    /// isolate before they are loaded again.
+    ///
+    /// Updates and deletions made by the isolate are visible to it
+    /// immediately. Defaults to 60 seconds; a TTL of zero disables caching.
+    pub const fn cache_ttl(mut self, cache_ttl: Duration) -> Self {
+        self.cache_ttl = cache_ttl;
+
+        self
+    }
+
+    /// Set the function migrating configurations stored with a version of the
+    /// schema to the next version, returning `None` if the version can't be
+    /// migrated.
+    ///
+    /// Defaults to configurations stored with older versions not being
+    /// migrated, failing to be retrieved.
+    pub const fn migrate(mut self, migrate: fn(u32, Value) -> Option<Value>) -> Self {
+        self.migrate = Some(migrate);
+
+        self
+    }
+
+    /// Set the prefix of the names configurations are stored under, followed
+    /// by a colon and the guild's ID.
+    ///
+    /// Defaults to `guild_config`. Using different prefixes allows multiple
+    /// configurations to be stored in the same KV namespace.
+    pub const fn prefix(mut self, prefix: &'a str) -> Self {
+        self.prefix = prefix;
+
+        self
+    }
+
+    /// Delete the configuration of a guild.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of type [`Storing`] if the configuration could not be
+    /// deleted from KV.
+    ///
+    /// [`Storing`]: GuildConfigErrorType::Storing
+    pub async fn delete(&self, guild_id: impl Display) -> Result<(), GuildConfigError> {
+        let name = self.name(guild_id);
+
+        self.kv
+            .delete(&name)
+            .await
+            .map_err(GuildConfigError::storing)?;
+        self.cache(&name, None);
+
+        Ok(())
+    }
+
+    /// Retrieve the configuration of a guild, returning `None` if it isn't
+    /// stored.
+    ///
+    /// Configurations stored with an older version of the schema are migrated,
+    /// but aren't stored again until they are updated.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of type [`Deserializing`] if the configuration could
+    /// not be deserialized.
+    ///
+    /// Returns an error of type [`Migrating`] if the configuration was stored
+    /// with a version that could not be migrated.
+    ///
+    /// Returns an error of type [`Storing`] if the configuration could not be
+    /// retrieved from KV.
+    ///
+    /// [`Deserializing`]: GuildConfigErrorType::Deserializing
+    /// [`Migrating`]: GuildConfigErrorType::Migrating
+    /// [`Storing`]: GuildConfigErrorType::Storing
+    pub async fn get(&self, guild_id: impl Display) -> Result<Option<T>, GuildConfigError> {
+        self.load(&self.name(guild_id)).await
+    }
+
+    /// Update the configuration of a guild with a function returning the new
+    /// configuration from the current one, or `None` if it isn't stored.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of type [`Serializing`] if the new configuration could
+    /// not be serialized.
+    ///
+    /// Refer to [`get`] and [`delete`] for the other errors that may be
+    /// returned.
+    ///
+    /// [`delete`]: Self::delete
+    /// [`get`]: Self::get
+    /// [`Serializing`]: GuildConfigErrorType::Serializing
+    pub async fn update(
+        &self,
+        guild_id: impl Display,
+        update: impl FnOnce(Option<T>) -> T,
+    ) -> Result<T, GuildConfigError> {
+        let name = self.name(guild_id);
+        let value = update(self.load(&name).await?);
+        let stored = serde_json::to_string(&Stored {
+            value: &value,
+            version: self.version,
+        })
+        .map_err(|source| GuildConfigError {
+            kind: GuildConfigErrorType::Serializing,
+            source: Some(Box::new(source)),
+        })?;
+
+        self.kv
+            .put(&name, stored.as_str())
+            .map_err(GuildConfigError::storing)?
+            .execute()
+            .await
+            .map_err(GuildConfigError::storing)?;
+        self.cache(&name, Some(stored));
+
+        Ok(value)
+    }
+
+    /// Cache the configuration stored under a name.
+    fn cache(&self, name: &str, stored: Option<String>) {
+        let now = Date::now().as_millis();
+        let expires_at =
+            now.saturating_add(self.cache_ttl.as_millis().try_into().unwrap_or(u64::MAX));
+
+        LOADED.with(|loaded| {
+            cache_insert(
+                &mut loaded.borrow_mut(),
+                self.cache_key(name),
+                stored,
+                expires_at,
+                now,
+            );
+        });
+    }
+
+    /// Key the configuration stored under a name is cached by.
+    fn cache_key(&self, name: &str) -> String {
+        [self.namespace, "/", name].concat()
+    }
+
+    /// Load, migrate, and deserialize the configuration stored under a name.
+    async fn load(&self, name: &str) -> Result<Option<T>, GuildConfigError> {
+        let key = self.cache_key(name);
+        let now = Date::now().as_millis();
+        let cached = LOADED.with(|loaded| {
+            cache_get(&mut loaded.borrow_mut(), &key, now).map(|(_, stored, _)| stored.clone())
+        });
+
+        let stored = if let Some(stored) = cached {
+            stored
+        } else {
+            let stored = self
+                .kv
+                .get(name)
+                .text()
+                .await
+                .map_err(GuildConfigError::storing)?;
+            self.cache(name, stored.clone());
+
+            stored
+        };
+
+        stored
+            .map(|stored| decode(&stored, self.version, self.migrate))
+            .transpose()
+    }
+
+    /// Name the configuration of a guild is stored under.
+    fn name(&self, guild_id: impl Display) -> String {
+        format!("{}:{guild_id}", self.prefix)
+    }
+}
+
+/// Configuration cached under a key at a time, if it hasn't expired,
+/// removing the configurations that have.
+fn cache_get<'a>(loaded: &'a mut Vec<Cached>, key: &str, now: u64) -> Option<&'a Cached> {
+    loaded.retain(|(_, _, expires_at)| *expires_at > now);
+
+    loaded.iter().find(|(loaded_key, _, _)| loaded_key == key)
+}
+
+/// Cache a configuration under a key until a time, replacing what is cached
+/// under the key and evicting the least recently cached configuration if the
+/// cache is full.
+///
+/// Configurations that have already expired, such as with a TTL of zero,
+/// aren't cached.
+fn cache_insert(
+    loaded: &mut Vec<Cached>,
+    key: String,
+    stored: Option<String>,
+    expires_at: u64,
+    now: u64,
+) {
+    loaded.retain(|(loaded_key, _, expires_at)| *loaded_key != key && *expires_at > now);
+
+    if expires_at > now {
+        if loaded.len() >= CACHE_CAPACITY {
+            loaded.remove(0);
+        }
+
+        loaded.push((key, stored, expires_at));
+    }
+}
+
+/// Deserialize a stored configuration, migrating it from the version of the
+/// schema it was stored with to the current version one version at a time.
+fn decode<T: DeserializeOwned>(
+    stored: &str,
+    current: u32,
+    migrate: Option<fn(u32, Value) -> Option<Value>>,
+) -> Result<T, GuildConfigError> {
+    let Stored { mut value, version } =
+        serde_json::from_str::<Stored<Value>>(stored).map_err(GuildConfigError::deserializing)?;
+
+    if version > current {
+        return Err(GuildConfigError::migrating(version));
+    }
+
+    for version in version..current {
+        value = migrate
+            .and_then(|migrate| migrate(version, value))
+            .ok_or_else(|| GuildConfigError::migrating(version))?;
+    }
+
+    serde_json::from_value(value).map_err(GuildConfigError::deserializing)
+}
+
+/// Guild configuration could not be retrieved, updated, or deleted.
+#[allow(clippy::module_name_repetitions)]
+#[derive(Debug)]
+pub struct GuildConfigError {
+    kind: GuildConfigErrorType,
+    source: Option<Box<dyn Error>>,
+}
+
+impl GuildConfigError {
+    /// Immutable reference to the type of error that occurred.
+    #[must_use = "retrieving the type has no effect if left unused"]
+    pub const fn kind(&self) -> &GuildConfigErrorType {
+        &self.kind
+    }
+
+    /// Consume the error, returning the source error if there is any.
+    #[must_use = "consuming the error and retrieving the source has no effect if left unused"]
+    pub fn into_source(self) -> Option<Box<dyn Error>> {
+        self.source
+    }
+
+    /// Consume the error, returning the owned error type and the source error.
+    #[must_use = "consuming the error into its parts has no effect if left unused"]
+    pub fn into_parts(self) -> (GuildConfigErrorType, Option<Box<dyn Error>>) {
+        (self.kind, self.source)
+    }
+
+    /// Create an error of type [`GuildConfigErrorType::Deserializing`].
+    fn deserializing(source: serde_json::Error) -> Self {
+        Self {
+            kind: GuildConfigErrorType::Deserializing,
+            source: Some(Box::new(source)),
+        }
+    }
+
+    /// Create an error of type [`GuildConfigErrorType::Migrating`].
+    const fn migrating(version: u32) -> Self {
+        Self {
+            kind: GuildConfigErrorType::Migrating { version },
+            source: None,
+        }
+    }
+
+    /// Create an error of type [`GuildConfigErrorType::Storing`].
+    fn storing(source: worker::kv::KvError) -> Self {
+        Self {
+            kind: GuildConfigErrorType::Storing,
+            source: Some(Box::new(worker::Error::from(source))),
+        }
+    }
+}
+
+impl Display for GuildConfigError {
+    #[cfg(feature = "minimal-errors")]
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.write_str("error ")?;
+
+        Display::fmt(&self.kind.code(), f)
+    }
+
+    #[cfg(not(feature = "minimal-errors"))]
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match &self.kind {
+            GuildConfigErrorType::Deserializing => {
+                f.write_str("guild configuration could not be deserialized")
+            }
+            GuildConfigErrorType::Migrating { version } => {
+                f.write_str("guild configuration stored with version ")?;
+                Display::fmt(version, f)?;
+
+                f.write_str(" could not be migrated")
+            }
+            GuildConfigErrorType::Serializing => {
+                f.write_str("guild configuration could not be serialized")
+            }
+            GuildConfigErrorType::Storing => {
+                f.write_str("guild configuration could not be accessed in KV")
+            }
+        }
+    }
+}
+
+impl Error for GuildConfigError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.source
+            .as_ref()
+            .map(|source| &**source as &(dyn Error + 'static))
+    }
+}
+
+/// Type of [`GuildConfigError`] that occurred.
+#[allow(clippy::module_name_repetitions)]
+#[derive(Debug)]
+pub enum GuildConfigErrorType {
+    /// Stored configuration is not valid JSON or doesn't match the schema.
+    Deserializing,
+    /// Configuration was stored with a newer version of the schema, or an
+    /// older version that the migration function returned `None` for.
+    Migrating {
+        /// Version of the schema that could not be migrated from.
+        version: u32,
+    },
+    /// New configuration could not be serialized.
+    Serializing,
+    /// Configuration could not be retrieved from, stored in, or deleted from
+    /// KV.
+    Storing,
+}
+
+impl GuildConfigErrorType {
+    /// Numeric code of the type of error, which errors are displayed as with
+    /// the `minimal-errors` feature.
+    ///
+    /// Refer to [`ProcessRequestErrorType::code`] for more information.
+    ///
+    /// [`ProcessRequestErrorType::code`]: crate::ProcessRequestErrorType::code
+    #[must_use = "retrieving the code is not useful on its own"]
+    pub const fn code(&self) -> u16 {
+        match self {
+            Self::Deserializing => 33,
+            Self::Migrating { .. } => 34,
+            Self::Serializing => 35,
+            Self::Storing => 36,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        cache_get, cache_insert, decode, Cached, GuildConfigError, GuildConfigErrorType,
+        CACHE_CAPACITY,
+    };
+    use serde_json::{json, Value};
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    /// Migration renaming `channel` to `channel_id` in version 1 and adding
+    /// `enabled` in version 2.
+    #[allow(clippy::needless_pass_by_value)]
+    fn migrate(version: u32, mut value: Value) -> Option<Value> {
+        let object = value.as_object_mut()?;
+
+        match version {
+            1 => {
+                let channel = object.remove("channel")?;
+                object.insert("channel_id".to_owned(), channel);
+            }
+            2 => {
+                object.insert("enabled".to_owned(), json!(true));
+            }
+            _ => return None,
+        }
+
+        Some(value)
+    }
+
+    fn migrating_version(result: Result<Value, GuildConfigError>) -> u32 {
+        match result.unwrap_err().kind() {
+            GuildConfigErrorType::Migrating { version } => *version,
+            other => panic!("expected a migrating error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn decode_current() {
+        let stored = json!({ "value": { "a": 1 }, "version": 3 }).to_string();
+
+        assert_eq!(
+            json!({ "a": 1 }),
+            decode::<Value>(&stored, 3, None).unwrap()
+        );
+    }
+
+    #[test]
+    fn decode_migrates_each_version() {
+        let stored = json!({ "value": { "channel": "1" }, "version": 1 }).to_string();
+
+        assert_eq!(
+            json!({ "channel_id": "1", "enabled": true }),
+            decode::<Value>(&stored, 3, Some(migrate)).unwrap()
+        );
+        assert_eq!(
+            json!({ "channel_id": "1" }),
+            decode::<Value>(&stored, 2, Some(migrate)).unwrap()
+        );
+
+        let stored = json!({ "value": { "channel_id": "1" }, "version": 2 }).to_string();
+        assert_eq!(
+            json!({ "channel_id": "1", "enabled": true }),
+            decode::<Value>(&stored, 3, Some(migrate)).unwrap()
+        );
+    }
+
+    #[test]
+    fn decode_unmigratable() {
+        let stored = json!({ "value": {}, "version": 0 }).to_string();
+        assert_eq!(0, migrating_version(decode(&stored, 3, Some(migrate))));
+
+        // Migrating from version 1 fails without a `channel`.
+        let stored = json!({ "value": {}, "version": 1 }).to_string();
+        assert_eq!(1, migrating_version(decode(&stored, 3, Some(migrate))));
+
+        let stored = json!({ "value": {}, "version": 2 }).to_string();
+        assert_eq!(2, migrating_version(decode(&stored, 3, None)));
+    }
+
+    #[test]
+    fn decode_rejects_newer() {
+        let stored = json!({ "value": {}, "version": 4 }).to_string();
+
+        assert_eq!(4, migrating_version(decode(&stored, 3, Some(migrate))));
+    }
+
+    #[test]
+    fn decode_invalid() {
+        let result = decode::<Value>("not json", 1, None);
+
+        assert!(matches!(
+            result.unwrap_err().kind(),
+            GuildConfigErrorType::Deserializing
+        ));
+    }
+
+    /// Configuration cached under a key at a time.
+    fn stored<'a>(loaded: &'a mut Vec<Cached>, key: &str, now: u64) -> Option<&'a Option<String>> {
+        cache_get(loaded, key, now).map(|(_, stored, _)| stored)
+    }
+
+    #[test]
+    fn cache_expiry() {
+        let mut loaded = Vec::new();
+        cache_insert(&mut loaded, "a".to_owned(), Some("1".to_owned()), 1000, 0);
+        cache_insert(&mut loaded, "b".to_owned(), None, 2000, 0);
+
+        assert_eq!(Some(&Some("1".to_owned())), stored(&mut loaded, "a", 999));
+        assert_eq!(Some(&None), stored(&mut loaded, "b", 999));
+        assert_eq!(None, stored(&mut loaded, "c", 999));
+
+        assert_eq!(None, stored(&mut loaded, "a", 1000));
+        assert_eq!(1, loaded.len());
+    }
+
+    #[test]
+    fn cache_replaces_key() {
+        let mut loaded = Vec::new();
+        cache_insert(&mut loaded, "a".to_owned(), Some("1".to_owned()), 1000, 0);
+        cache_insert(&mut loaded, "a".to_owned(), Some("2".to_owned()), 1000, 0);
+
+        assert_eq!(1, loaded.len());
+        assert_eq!(Some(&Some("2".to_owned())), stored(&mut loaded, "a", 0));
+
+        // A TTL of zero removes what was cached without caching anything.
+        cache_insert(&mut loaded, "a".to_owned(), None, 0, 0);
+        assert!(loaded.is_empty());
+    }
+
+    #[test]
+    fn cache_evicts_least_recent() {
+        let mut loaded = Vec::new();
+
+        for index in 0..=CACHE_CAPACITY {
+            cache_insert(&mut loaded, index.to_string(), None, 1000, 0);
+        }
+
+        assert_eq!(CACHE_CAPACITY, loaded.len());
+        assert_eq!(None, stored(&mut loaded, "0", 0));
+        assert_eq!(Some(&None), stored(&mut loaded, "1", 0));
+        assert_eq!(
+            Some(&None),
+            stored(&mut loaded, &CACHE_CAPACITY.to_string(), 0)
+        );
+    }
+}
@@ -0,0 +1,447 @@
+//! Verification of third-party webhooks signed with HMAC-SHA256, such as from
+//! GitHub or Stripe, for accepting them on other routes of the same Worker.
+//!
+//! Signatures are compared in constant time.
+//!
+//! ```ignore
+//! use twilight_cloudflare_workers::hmac_webhooks;
+//!
+//! let signature = req.headers().get("X-Hub-Signature-256")?.unwrap_or_default();
+//! let body = req.bytes().await?;
+//!
+//! if hmac_webhooks::verify_github(secret.as_bytes(), &signature, &body).is_err() {
+//!     return Response::error("invalid signature", 401);
+//! }
+//! ```
+
+use core::fmt::{Display, Formatter, Result as FmtResult};
+use hmac::{Hmac, Mac, NewMac};
+use sha2::Sha256;
+use std::error::Error;
+
+/// Prefix of the signatures in GitHub's `X-Hub-Signature-256` header.
+const GITHUB_PREFIX: &str = "sha256=";
+
+/// Key of the signatures in Stripe's `Stripe-Signature` header.
+const STRIPE_SIGNATURE_KEY: &str = "v1";
+
+/// Key of the timestamp in Stripe's `Stripe-Signature` header.
+const STRIPE_TIMESTAMP_KEY: &str = "t";
+
+/// HMAC-SHA256, used by webhook providers to sign requests.
+type HmacSha256 = Hmac<Sha256>;
+
+/// Verify that a hex encoded HMAC-SHA256 signature is of a message signed
+/// with a secret.
+///
+/// # Errors
+///
+/// Returns an error of type [`InvalidSignature`] if the signature is not of
+/// the message.
+///
+/// Returns an error of type [`Malformed`] if the signature is not in a valid
+/// format.
+///
+/// [`InvalidSignature`]: VerifyErrorType::InvalidSignature
+/// [`Malformed`]: VerifyErrorType::Malformed
+pub fn verify(secret: &[u8], message: &[u8], signature: &str) -> Result<(), VerifyError> {
+    let signature = hex::decode(signature).map_err(|source| VerifyError {
+        kind: VerifyErrorType::Malformed,
+        source: Some(Box::new(source)),
+    })?;
+
+    mac(secret, &[message])?
+        .verify(&signature)
+        .map_err(|_| VerifyError::invalid_signature())
+}
+
+/// Verify a GitHub webhook delivery with the webhook's secret and the value of
+/// its `X-Hub-Signature-256` header.
+///
+/// # Errors
+///
+/// Returns an error of type [`InvalidSignature`] if the signature is not of
+/// the body.
+///
+/// Returns an error of type [`Malformed`] if the header is not in a valid
+/// format.
+///
+/// [`InvalidSignature`]: VerifyErrorType::InvalidSignature
+/// [`Malformed`]: VerifyErrorType::Malformed
+pub fn verify_github(secret: &[u8], signature: &str, body: &[u8]) -> Result<(), VerifyError> {
+    let signature = signature
+        .strip_prefix(GITHUB_PREFIX)
+        .ok_or(VerifyError::malformed())?;
+
+    verify(secret, body, signature)
+}
+
+/// Verify a Stripe webhook event with the endpoint's signing secret and the
+/// value of its `Stripe-Signature` header.
+///
+/// The event must have been signed within the tolerance, in seconds, of the
+/// current time in seconds since the Unix epoch, such as 300 seconds as
+/// Stripe's libraries default to. Any of the header's signatures may match,
+/// as during the rotation of a secret.
+///
+/// # Errors
+///
+/// Returns an error of type [`InvalidSignature`] if none of the signatures are
+/// of the timestamp and body.
+///
+/// Returns an error of type [`Malformed`] if the header is not in a valid
+/// format or has no signatures.
+///
+/// Returns an error of type [`TimestampOutsideTolerance`] if the event was
+/// signed outside of the tolerance of the current time.
+///
+/// [`InvalidSignature`]: VerifyErrorType::InvalidSignature
+/// [`Malformed`]: VerifyErrorType::Malformed
+/// [`TimestampOutsideTolerance`]: VerifyErrorType::TimestampOutsideTolerance
+pub fn verify_stripe(
+    secret: &[u8],
+    signature: &str,
+    body: &[u8],
+    now: u64,
+    tolerance: u64,
+) -> Result<(), VerifyError> {
+    let mut timestamp = None;
+    let mut signatures = Vec::new();
+
+    for pair in signature.split(',') {
+        let (key, value) = pair
+            .trim()
+            .split_once('=')
+            .ok_or(VerifyError::malformed())?;
+
+        match key {
+            STRIPE_SIGNATURE_KEY => signatures.push(value),
+            STRIPE_TIMESTAMP_KEY => timestamp = Some(value),
+            // Ignore signatures of other schemes, such as test mode's `v0`.
+            _ => {}
+        }
+    }
+
+    let timestamp = timestamp.ok_or(VerifyError::malformed())?;
+    let seconds = timestamp.parse::<u64>().map_err(|source| VerifyError {
+        kind: VerifyErrorType::Malformed,
+        source: Some(Box::new(source)),
+    })?;
+
+    if signatures.is_empty() {
+        return Err(VerifyError::malformed());
+    }
+
+    if now.abs_diff(seconds) > tolerance {
+        return Err(VerifyError {
+            kind: VerifyErrorType::TimestampOutsideTolerance { timestamp: seconds },
+            source: None,
+        });
+    }
+
+    let expected = mac(secret, &[timestamp.as_bytes(), b".", body])?;
+
+    for signature in signatures {
+        let Ok(signature) = hex::decode(signature) else {
+            continue;
+        };
+
+        if expected.clone().verify(&signature).is_ok() {
+            return Ok(());
+        }
+    }
+
+    Err(VerifyError::invalid_signature())
+}
+
+/// HMAC-SHA256 of the concatenation of parts with a secret.
+///
+/// HMAC accepts keys of any length, but a rejected secret is treated as no
+/// signature being of the message rather than panicking.
+fn mac(secret: &[u8], parts: &[&[u8]]) -> Result<HmacSha256, VerifyError> {
+    let mut mac =
+        HmacSha256::new_from_slice(secret).map_err(|_| VerifyError::invalid_signature())?;
+
+    for part in parts {
+        mac.update(part);
+    }
+
+    Ok(mac)
+}
+
+/// Webhook could not be verified.
+#[derive(Debug)]
+pub struct VerifyError {
+    kind: VerifyErrorType,
+    source: Option<Box<dyn Error>>,
+}
+
+impl VerifyError {
+    /// Immutable reference to the type of error that occurred.
+    #[must_use = "retrieving the type has no effect if left unused"]
+    pub const fn kind(&self) -> &VerifyErrorType {
+        &self.kind
+    }
+
+    /// Consume the error, returning the source error if there is any.
+    #[must_use = "consuming the error and retrieving the source has no effect if left unused"]
+    pub fn into_source(self) -> Option<Box<dyn Error>> {
+        self.source
+    }
+
+    /// Consume the error, returning the owned error type and the source error.
+    #[must_use = "consuming the error into its parts has no effect if left unused"]
+    pub fn into_parts(self) -> (VerifyErrorType, Option<Box<dyn Error>>) {
+        (self.kind, self.source)
+    }
+
+    /// Create an error of type [`VerifyErrorType::InvalidSignature`].
+    const fn invalid_signature() -> Self {
+        Self {
+            kind: VerifyErrorType::InvalidSignature,
+            source: None,
+        }
+    }
+
+    /// Create an error of type [`VerifyErrorType::Malformed`] without a
+    /// source.
+    const fn malformed() -> Self {
+        Self {
+            kind: VerifyErrorType::Malformed,
+            source: None,
+        }
+    }
+}
+
+impl Display for VerifyError {
+    #[cfg(feature = "minimal-errors")]
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.write_str("error ")?;
+
+        Display::fmt(&self.kind.code(), f)
+    }
+
+    #[cfg(not(feature = "minimal-errors"))]
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match &self.kind {
+            VerifyErrorType::InvalidSignature => f.write_str("webhook signature is invalid"),
+            VerifyErrorType::Malformed => f.write_str("webhook signature is malformed"),
+            VerifyErrorType::TimestampOutsideTolerance { timestamp } => {
+                f.write_str("webhook timestamp ")?;
+                Display::fmt(timestamp, f)?;
+
+                f.write_str(" is outside of the tolerance")
+            }
+        }
+    }
+}
+
+impl Error for VerifyError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.source
+            .as_ref()
+            .map(|source| &**source as &(dyn Error + 'static))
+    }
+}
+
+/// Type of [`VerifyError`] that occurred.
+#[derive(Debug)]
+pub enum VerifyErrorType {
+    /// Signature is not of the webhook's body signed with the secret.
+    InvalidSignature,
+    /// Signature or header is not in a valid format.
+    Malformed,
+    /// Webhook was signed outside of the tolerance of the current time.
+    TimestampOutsideTolerance {
+        /// Timestamp the webhook was signed at, in seconds since the Unix
+        /// epoch.
+        timestamp: u64,
+    },
+}
+
+impl VerifyErrorType {
+    /// Numeric code of the type of error, which errors are displayed as with
+    /// the `minimal-errors` feature.
+    ///
+    /// Refer to [`ProcessRequestErrorType::code`] for more information.
+    ///
+    /// [`ProcessRequestErrorType::code`]: crate::ProcessRequestErrorType::code
+    #[must_use = "retrieving the code is not useful on its own"]
+    pub const fn code(&self) -> u16 {
+        match self {
+            Self::InvalidSignature => 44,
+            Self::Malformed => 45,
+            Self::TimestampOutsideTolerance { .. } => 46,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{verify, verify_github, verify_stripe, VerifyErrorType};
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    /// Body of the Stripe event the signatures are of.
+    const STRIPE_BODY: &[u8] = br#"{"id":"evt_1","object":"event"}"#;
+
+    /// Signature of the Stripe event signed at 1700000000 with
+    /// `whsec_test_secret`.
+    const STRIPE_SIGNATURE: &str =
+        "0c8670ed117751cc551a20e35839447075c42800ea3cf3e8a2fbda99cd1e6edd";
+
+    /// Signature of the Stripe event signed at 1700000000 with
+    /// `whsec_old_secret`, as during the rotation of a secret.
+    const STRIPE_SIGNATURE_OLD: &str =
+        "921476aa6febf6d11353fcee45246000c9edf7b3938ae2d09a2d6ea5cb98d9db";
+
+    /// Time the Stripe event was signed at.
+    const STRIPE_TIMESTAMP: u64 = 1_700_000_000;
+
+    fn stripe(header: &str, secret: &[u8], now: u64) -> Option<VerifyErrorType> {
+        verify_stripe(secret, header, STRIPE_BODY, now, 300)
+            .err()
+            .map(|error| error.into_parts().0)
+    }
+
+    #[test]
+    fn rfc_4231_vectors() {
+        for (secret, message, signature) in [
+            (
+                [0x0B; 20].as_slice(),
+                b"Hi There".as_slice(),
+                "b0344c61d8db38535ca8afceaf0bf12b881dc200c9833da726e9376c2e32cff7",
+            ),
+            (
+                b"Jefe",
+                b"what do ya want for nothing?",
+                "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843",
+            ),
+            // Keys longer than the block size are hashed first.
+            (
+                &[0xAA; 131],
+                b"Test Using Larger Than Block-Size Key - Hash Key First",
+                "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54",
+            ),
+        ] {
+            assert!(verify(secret, message, signature).is_ok());
+            assert!(matches!(
+                verify(secret, b"other", signature).unwrap_err().kind(),
+                VerifyErrorType::InvalidSignature
+            ));
+        }
+
+        assert!(matches!(
+            verify(b"Jefe", b"", "not hex").unwrap_err().kind(),
+            VerifyErrorType::Malformed
+        ));
+    }
+
+    #[test]
+    fn github_example() {
+        // Example from GitHub's documentation on validating deliveries.
+        let secret = b"It's a Secret to Everybody";
+        let signature = "sha256=757107ea0eb2509fc211221cce984b8a37570b6d7586c22c46f4379c8b043e17";
+
+        assert!(verify_github(secret, signature, b"Hello, World!").is_ok());
+        assert!(matches!(
+            verify_github(secret, signature, b"Hello, World")
+                .unwrap_err()
+                .kind(),
+            VerifyErrorType::InvalidSignature
+        ));
+        assert!(matches!(
+            verify_github(b"other secret", signature, b"Hello, World!")
+                .unwrap_err()
+                .kind(),
+            VerifyErrorType::InvalidSignature
+        ));
+        assert!(matches!(
+            verify_github(secret, &signature[7..], b"Hello, World!")
+                .unwrap_err()
+                .kind(),
+            VerifyErrorType::Malformed
+        ));
+    }
+
+    #[test]
+    fn stripe_signatures() {
+        let header = format!("t={STRIPE_TIMESTAMP},v1={STRIPE_SIGNATURE}");
+
+        assert!(stripe(&header, b"whsec_test_secret", STRIPE_TIMESTAMP).is_none());
+        assert!(matches!(
+            stripe(&header, b"whsec_other_secret", STRIPE_TIMESTAMP),
+            Some(VerifyErrorType::InvalidSignature)
+        ));
+
+        // Signatures of other schemes are ignored, and the order of the pairs
+        // doesn't matter.
+        let header = format!("v0=abcd, v1={STRIPE_SIGNATURE}, t={STRIPE_TIMESTAMP}");
+        assert!(stripe(&header, b"whsec_test_secret", STRIPE_TIMESTAMP).is_none());
+
+        let header = format!("t={STRIPE_TIMESTAMP},v0={STRIPE_SIGNATURE}");
+        assert!(matches!(
+            stripe(&header, b"whsec_test_secret", STRIPE_TIMESTAMP),
+            Some(VerifyErrorType::Malformed)
+        ));
+    }
+
+    #[test]
+    fn stripe_rotated_secret() {
+        let header =
+            format!("t={STRIPE_TIMESTAMP},v1={STRIPE_SIGNATURE_OLD},v1={STRIPE_SIGNATURE}");
+
+        assert!(stripe(&header, b"whsec_test_secret", STRIPE_TIMESTAMP).is_none());
+        assert!(stripe(&header, b"whsec_old_secret", STRIPE_TIMESTAMP).is_none());
+        assert!(matches!(
+            stripe(&header, b"whsec_other_secret", STRIPE_TIMESTAMP),
+            Some(VerifyErrorType::InvalidSignature)
+        ));
+
+        // Malformed signatures are skipped rather than failing the others.
+        let header = format!("t={STRIPE_TIMESTAMP},v1=zz,v1={STRIPE_SIGNATURE}");
+        assert!(stripe(&header, b"whsec_test_secret", STRIPE_TIMESTAMP).is_none());
+    }
+
+    #[test]
+    fn stripe_tolerance_edges() {
+        let header = format!("t={STRIPE_TIMESTAMP},v1={STRIPE_SIGNATURE}");
+        let secret = b"whsec_test_secret";
+
+        for now in [STRIPE_TIMESTAMP - 300, STRIPE_TIMESTAMP + 300] {
+            assert!(stripe(&header, secret, now).is_none(), "{now}");
+        }
+
+        for now in [STRIPE_TIMESTAMP - 301, STRIPE_TIMESTAMP + 301, 0, u64::MAX] {
+            assert!(
+                matches!(
+                    stripe(&header, secret, now),
+                    Some(VerifyErrorType::TimestampOutsideTolerance {
+                        timestamp: STRIPE_TIMESTAMP
+                    })
+                ),
+                "{now}"
+            );
+        }
+    }
+
+    #[test]
+    fn stripe_malformed() {
+        for header in [
+            "",
+            "t=1700000000",
+            "v1=abcd",
+            "t=soon,v1=abcd",
+            "t=-1,v1=abcd",
+            "t=1700000000,v1",
+        ] {
+            assert!(
+                matches!(
+                    stripe(header, b"whsec_test_secret", STRIPE_TIMESTAMP),
+                    Some(VerifyErrorType::Malformed)
+                ),
+                "{header:?}"
+            );
+        }
+    }
+}
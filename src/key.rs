@@ -0,0 +1,332 @@
+//! Parsing and caching of application public keys.
+
+use crate::{ProcessRequestError, ProcessRequestErrorType};
+#[cfg(feature = "worker")]
+use core::time::Duration;
+use ed25519_dalek::{PublicKey, PUBLIC_KEY_LENGTH};
+use hex::FromHex;
+use std::cell::RefCell;
+#[cfg(feature = "worker")]
+use worker::{kv::KvStore, Date};
+
+/// Maximum number of parsed public keys cached by an isolate, beyond which
+/// the least recently parsed keys are evicted.
+const CACHE_CAPACITY: usize = 16;
+
+thread_local! {
+    /// Parsed public keys and the hex strings they were parsed from.
+    ///
+    /// Workers isolates are reused across requests, so caching the keys lets
+    /// warm isolates skip decoding and validating them on every request, even
+    /// when the Worker serves the interactions of multiple applications.
+    static CACHE: RefCell<Vec<(String, PublicKey)>> = const { RefCell::new(Vec::new()) };
+
+    /// Public keys loaded from KV, along with the names they are stored under
+    /// and the time in milliseconds since the Unix epoch they expire at.
+    #[cfg(feature = "worker")]
+    static LOADED: RefCell<Vec<(String, String, u64)>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Load a hex encoded public key stored in KV under a name, reusing the
+/// previously loaded key until the TTL has passed.
+///
+/// # Errors
+///
+/// Returns an error of type [`LoadingPublicKey`] if the public key could not
+/// be retrieved from KV or is not stored.
+///
+/// [`LoadingPublicKey`]: ProcessRequestErrorType::LoadingPublicKey
+#[cfg(feature = "worker")]
+pub(crate) async fn load(
+    kv: &KvStore,
+    name: &str,
+    ttl: Duration,
+) -> Result<String, ProcessRequestError> {
+    let now = Date::now().as_millis();
+    let cached = LOADED.with(|loaded| loaded_get(&loaded.borrow(), name, now));
+
+    if let Some(public_key) = cached {
+        return Ok(public_key);
+    }
+
+    let public_key = kv
+        .get(name)
+        .text()
+        .await
+        .map_err(|source| ProcessRequestError {
+            kind: ProcessRequestErrorType::LoadingPublicKey {
+                name: name.to_owned(),
+            },
+            source: Some(Box::new(worker::Error::from(source))),
+        })?
+        .ok_or_else(|| ProcessRequestError {
+            kind: ProcessRequestErrorType::LoadingPublicKey {
+                name: name.to_owned(),
+            },
+            source: None,
+        })?;
+
+    LOADED.with(|loaded| {
+        loaded_insert(&mut loaded.borrow_mut(), name, public_key.clone(), ttl, now);
+    });
+
+    Ok(public_key)
+}
+
+/// Public key loaded under a name, if it hasn't expired at a time in
+/// milliseconds since the Unix epoch.
+#[cfg(feature = "worker")]
+fn loaded_get(loaded: &[(String, String, u64)], name: &str, now: u64) -> Option<String> {
+    loaded
+        .iter()
+        .find(|(loaded_name, _, expires_at)| loaded_name == name && *expires_at > now)
+        .map(|(_, public_key, _)| public_key.clone())
+}
+
+/// Record a public key loaded under a name at a time, to be reused until the
+/// TTL has passed, replacing the key previously loaded under the name and
+/// removing the keys that have expired.
+#[cfg(feature = "worker")]
+fn loaded_insert(
+    loaded: &mut Vec<(String, String, u64)>,
+    name: &str,
+    public_key: String,
+    ttl: Duration,
+    now: u64,
+) {
+    let expires_at = now.saturating_add(ttl.as_millis().try_into().unwrap_or(u64::MAX));
+
+    loaded.retain(|(loaded_name, _, expires_at)| loaded_name != name && *expires_at > now);
+    loaded.push((name.to_owned(), public_key, expires_at));
+}
+
+/// Parse a hex encoded public key, reusing the previously parsed key if the
+/// hex string was parsed before.
+///
+/// # Errors
+///
+/// Returns an error of type [`FromHex`] if the public key is not in a valid
+/// format.
+///
+/// Returns an error of type [`InvalidPublicKey`] if the public key is invalid.
+///
+/// [`FromHex`]: ProcessRequestErrorType::FromHex
+/// [`InvalidPublicKey`]: ProcessRequestErrorType::InvalidPublicKey
+pub(crate) fn parse(public_key: &str) -> Result<PublicKey, ProcessRequestError> {
+    let cached = CACHE.with(|cache| {
+        cache
+            .borrow()
+            .iter()
+            .find(|(hex, _)| hex == public_key)
+            .map(|(_, key)| *key)
+    });
+
+    if let Some(key) = cached {
+        return Ok(key);
+    }
+
+    let hex = <[u8; PUBLIC_KEY_LENGTH] as FromHex>::from_hex(public_key).map_err(|source| {
+        ProcessRequestError {
+            kind: ProcessRequestErrorType::FromHex,
+            source: Some(Box::new(source)),
+        }
+    })?;
+    let key = PublicKey::from_bytes(&hex).map_err(|source| ProcessRequestError {
+        kind: ProcessRequestErrorType::InvalidPublicKey,
+        source: Some(Box::new(source)),
+    })?;
+
+    CACHE.with(|cache| {
+        let mut cache = cache.borrow_mut();
+
+        if cache.len() >= CACHE_CAPACITY {
+            cache.remove(0);
+        }
+
+        cache.push((public_key.to_owned(), key));
+    });
+
+    Ok(key)
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "worker")]
+    use super::{loaded_get, loaded_insert};
+    use super::{parse, CACHE, CACHE_CAPACITY};
+    use crate::ProcessRequestErrorType;
+    #[cfg(feature = "worker")]
+    use core::time::Duration;
+    use ed25519_dalek::{PublicKey, SecretKey};
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    /// Hex encoded public key of a secret key made of a byte.
+    fn public_key(byte: u8) -> String {
+        let secret = SecretKey::from_bytes(&[byte; 32]).unwrap();
+
+        hex::encode(PublicKey::from(&secret).as_bytes())
+    }
+
+    fn cached(public_key: &str) -> bool {
+        CACHE.with(|cache| cache.borrow().iter().any(|(hex, _)| hex == public_key))
+    }
+
+    #[test]
+    fn caches_multiple_keys() {
+        let first = public_key(1);
+        let second = public_key(2);
+
+        let first_key = parse(&first).unwrap();
+        let second_key = parse(&second).unwrap();
+
+        assert_ne!(first_key, second_key);
+        assert!(cached(&first) && cached(&second));
+        assert_eq!(parse(&first).unwrap(), first_key);
+        assert_eq!(parse(&second).unwrap(), second_key);
+    }
+
+    #[test]
+    fn evicts_oldest_key() {
+        let keys = (0..=u8::try_from(CACHE_CAPACITY).unwrap())
+            .map(|byte| public_key(byte.wrapping_add(100)))
+            .collect::<Vec<_>>();
+
+        for key in &keys {
+            parse(key).unwrap();
+        }
+
+        assert!(!cached(&keys[0]));
+        assert!(keys[1..].iter().all(|key| cached(key)));
+        assert_eq!(CACHE.with(|cache| cache.borrow().len()), CACHE_CAPACITY);
+    }
+
+    #[test]
+    fn invalid_keys() {
+        assert!(matches!(
+            parse("not hex").unwrap_err().kind(),
+            ProcessRequestErrorType::FromHex
+        ));
+        assert!(matches!(
+            parse(&"ab".repeat(16)).unwrap_err().kind(),
+            ProcessRequestErrorType::FromHex
+        ));
+        assert!(!cached("not hex"));
+    }
+
+    #[cfg(feature = "worker")]
+    #[test]
+    fn loaded_until_expiry() {
+        let mut loaded = Vec::new();
+        loaded_insert(
+            &mut loaded,
+            "PUBLIC_KEY",
+            public_key(1),
+            Duration::from_secs(60),
+            1000,
+        );
+
+        assert_eq!(Some(public_key(1)), loaded_get(&loaded, "PUBLIC_KEY", 1000));
+        assert_eq!(
+            Some(public_key(1)),
+            loaded_get(&loaded, "PUBLIC_KEY", 60_999)
+        );
+        assert_eq!(None, loaded_get(&loaded, "PUBLIC_KEY", 61_000));
+        assert_eq!(None, loaded_get(&loaded, "OTHER_KEY", 1000));
+    }
+
+    #[cfg(feature = "worker")]
+    #[test]
+    fn loaded_refreshed() {
+        let mut loaded = Vec::new();
+        loaded_insert(
+            &mut loaded,
+            "PUBLIC_KEY",
+            public_key(1),
+            Duration::from_secs(60),
+            1000,
+        );
+        loaded_insert(
+            &mut loaded,
+            "PUBLIC_KEY",
+            public_key(2),
+            Duration::from_secs(60),
+            61_000,
+        );
+
+        assert_eq!(1, loaded.len());
+        assert_eq!(
+            Some(public_key(2)),
+            loaded_get(&loaded, "PUBLIC_KEY", 120_999)
+        );
+        assert_eq!(None, loaded_get(&loaded, "PUBLIC_KEY", 121_000));
+    }
+
+    #[cfg(feature = "worker")]
+    #[test]
+    fn loaded_removes_expired() {
+        let mut loaded = Vec::new();
+        loaded_insert(
+            &mut loaded,
+            "FIRST",
+            public_key(1),
+            Duration::from_secs(1),
+            0,
+        );
+        loaded_insert(
+            &mut loaded,
+            "SECOND",
+            public_key(2),
+            Duration::from_secs(60),
+            0,
+        );
+        loaded_insert(
+            &mut loaded,
+            "THIRD",
+            public_key(3),
+            Duration::from_secs(60),
+            1000,
+        );
+
+        assert_eq!(
+            vec!["SECOND", "THIRD"],
+            loaded
+                .iter()
+                .map(|(name, _, _)| name.as_str())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[cfg(feature = "worker")]
+    #[test]
+    fn loaded_ttl_saturates() {
+        let mut loaded = Vec::new();
+        loaded_insert(
+            &mut loaded,
+            "PUBLIC_KEY",
+            public_key(1),
+            Duration::MAX,
+            1000,
+        );
+
+        assert_eq!(
+            Some(public_key(1)),
+            loaded_get(&loaded, "PUBLIC_KEY", u64::MAX - 1)
+        );
+    }
+
+    #[cfg(feature = "worker")]
+    #[test]
+    fn loaded_zero_ttl() {
+        let mut loaded = Vec::new();
+        loaded_insert(
+            &mut loaded,
+            "PUBLIC_KEY",
+            public_key(1),
+            Duration::ZERO,
+            1000,
+        );
+
+        assert_eq!(None, loaded_get(&loaded, "PUBLIC_KEY", 1000));
+    }
+}
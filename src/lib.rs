@@ -23,6 +23,10 @@
 //! // work with the interaction..
 //! ```
 //!
+//! Errors can also be converted into Worker errors with `?`, or handled with
+//! `respond`, which responds to them with their response while still allowing
+//! `?` on Worker errors.
+//!
 //! The other function in the API is `response`, which takes an interaction response
 //! and produces a Worker response:
 //!
@@ -30,6 +34,69 @@
 //! return twilight_cloudflare_workers::response(interaction_response);
 //! ```
 //!
+//! If the interaction response can't be serialized, `response` produces a 500
+//! response. `try_response` instead returns the error, for handling it yourself.
+//!
+//! `response_bytes` returns the serialized body and its content type without
+//! creating a response, for composing it with other headers or forwarding it.
+//!
+//! ### Features
+//!
+//! The `twilight-model` feature is enabled by default and provides the
+//! functions working with `twilight-model`'s interaction models, along with
+//! clients for following up on interactions and making requests with a bot
+//! token in the `rest` module, pushing linked roles metadata in the
+//! `linked_roles` module, disabling the components of messages after a
+//! timeout with Durable Object alarms in the `expiring_components` module,
+//! reminding users at a time in the `reminders` module, gating commands by
+//! premium tiers in the `premium` module, downloading their attachments in the `attachments` module, and creating
+//! responses such as deferred responses in the `responses` module.
+//!
+//! The `worker` feature is enabled by default and provides the functions and
+//! configuration working with Worker requests and responses. Disabling it
+//! leaves request verification with [`verify`] and [`verify_parts`], the
+//! interaction models, and [`LazyInteraction`], which don't depend on the
+//! `worker` crate and so can be used on native targets and other runtimes.
+//! It also provides typed per-guild configuration stored in KV in the
+//! `guild_config` module, and payloads scheduled to be delivered with Durable
+//! Object alarms in the `scheduler` module. The `oauth2` and `js-json`
+//! features enable it.
+//!
+//! The `minimal-model` feature provides a small set of interaction models in
+//! the `model` module, covering the commonly used fields. Disabling the default
+//! features and enabling it in place of `twilight-model` greatly reduces the
+//! size of the compiled binary.
+//!
+//! The `minimal-errors` feature displays the crate's errors as the numeric
+//! code of their type, such as from [`ProcessRequestErrorType::code`] for
+//! errors processing requests, instead of a description, leaving the
+//! formatting code and descriptions out of size-constrained builds.
+//!
+//! The `hmac` feature provides verification of third-party webhooks signed
+//! with HMAC-SHA256, such as from GitHub or Stripe, in the `hmac_webhooks`
+//! module, and a relay of GitHub webhook deliveries to Discord in the
+//! `github_relay` module.
+//!
+//! The `oauth2` feature provides helpers for Discord's `OAuth2` authorization
+//! code flow in the `oauth2` module, such as for linked roles or dashboards,
+//! and the redirect of the linked roles verification URL.
+//!
+//! The `queue` feature provides broadcasts of a message to many channels,
+//! sharded across the messages of a Cloudflare Queue, in the `broadcast`
+//! module.
+//!
+//! The `testing` feature provides utilities for testing interaction request
+//! handling in the `testing` module, such as signing requests with a known
+//! key and creating requests from them to process under `wasm-bindgen-test`,
+//! and the `proptest` feature additionally provides `proptest` strategies for
+//! signed requests and malformed inputs.
+//!
+//! The `js-json` feature deserializes interactions, including the fields read to
+//! route them, with the runtime's JSON parser via `serde-wasm-bindgen` instead
+//! of with `serde_json`, which can be faster for large interactions. It doesn't
+//! reduce the size of the binary, as `serde_json` is still used to serialize
+//! responses.
+//!
 //! ### License
 //!
 //! ISC.
@@ -48,14 +115,109 @@
     warnings
 )]
 
+#[cfg(feature = "twilight-model")]
+pub mod accessors;
+#[cfg(all(feature = "twilight-model", feature = "worker"))]
+pub mod attachments;
+#[cfg(feature = "twilight-model")]
+pub mod autocomplete;
+#[cfg(feature = "queue")]
+pub mod broadcast;
+#[cfg(feature = "twilight-model")]
+pub mod confirmation;
+#[cfg(feature = "twilight-model")]
+pub mod emoji;
+#[cfg(all(feature = "twilight-model", feature = "worker"))]
+pub mod expiring_components;
+#[cfg(all(feature = "hmac", feature = "twilight-model", feature = "worker"))]
+pub mod github_relay;
+#[cfg(feature = "worker")]
+pub mod guild_config;
+#[cfg(feature = "hmac")]
+pub mod hmac_webhooks;
+#[cfg(all(feature = "twilight-model", feature = "worker"))]
+pub mod linked_roles;
+pub mod mentions;
+#[cfg(feature = "minimal-model")]
+pub mod model;
+#[cfg(feature = "oauth2")]
+pub mod oauth2;
+#[cfg(feature = "twilight-model")]
+pub mod permissions;
+#[cfg(all(feature = "twilight-model", feature = "worker"))]
+pub mod premium;
+#[cfg(all(feature = "twilight-model", feature = "worker"))]
+pub mod reminders;
+#[cfg(all(feature = "twilight-model", feature = "worker"))]
+pub mod responses;
+#[cfg(all(feature = "twilight-model", feature = "worker"))]
+pub mod rest;
+#[cfg(all(feature = "twilight-model", feature = "worker"))]
+pub mod role_menu;
+#[cfg(feature = "worker")]
+pub mod scheduler;
+pub mod table;
+#[cfg(feature = "testing")]
+pub mod testing;
+#[cfg(feature = "twilight-model")]
+pub mod webhook_events;
+
+#[cfg(feature = "worker")]
+mod config;
+mod key;
+mod parse;
+#[cfg(feature = "worker")]
+mod tenants;
+mod verification;
+
+#[cfg(feature = "twilight-model")]
+pub use self::parse::RawInteraction;
+#[cfg(feature = "worker")]
+pub use self::{
+    config::{Config, ConfigBuilder, EnvError, EnvErrorType, LandingPage, Route},
+    tenants::{Tenant, Tenants},
+};
+pub use self::{
+    parse::{LazyInteraction, RequestTiming},
+    verification::{verify, RequestSignature, VerificationHeaders},
+};
+
+#[cfg(all(feature = "twilight-model", feature = "worker"))]
+use self::webhook_events::WebhookEvent;
 use core::fmt::{Debug, Display, Error as FmtError, Formatter};
-use ed25519_dalek::{PublicKey, Verifier, PUBLIC_KEY_LENGTH};
-use hex::FromHex;
-use std::{error::Error, str};
+#[cfg(feature = "worker")]
+use core::future::Future;
+#[cfg(feature = "worker")]
+use core::time::Duration;
+#[cfg(feature = "worker")]
+use futures_util::StreamExt;
+#[cfg(all(
+    any(feature = "minimal-model", feature = "twilight-model"),
+    feature = "worker"
+))]
+use serde::Serialize;
+use std::{borrow::Cow, error::Error, rc::Rc, str};
+#[cfg(feature = "worker")]
+use std::{panic, sync::Once};
+#[cfg(all(feature = "twilight-model", feature = "worker"))]
 use twilight_model::{
     application::interaction::Interaction, http::interaction::InteractionResponse,
 };
-use worker::{Method, Request, Response};
+#[cfg(feature = "worker")]
+use worker::{kv::KvStore, Date, Method, Request, Response, ResponseBody};
+
+/// Type of autocomplete interactions.
+#[cfg(all(feature = "twilight-model", feature = "worker"))]
+const AUTOCOMPLETE_INTERACTION_TYPE: u8 = 4;
+
+/// Response to interactions whose type is not accepted.
+#[cfg(all(feature = "twilight-model", feature = "worker"))]
+const NOT_ACCEPTED_RESPONSE: &str =
+    r#"{"type":4,"data":{"content":"This interaction isn't supported.","flags":64}}"#;
+
+/// Response to autocomplete interactions when they are not accepted.
+#[cfg(all(feature = "twilight-model", feature = "worker"))]
+const NOT_ACCEPTED_AUTOCOMPLETE_RESPONSE: &str = r#"{"type":8,"data":{"choices":[]}}"#;
 
 /// Name of a required request header.
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
@@ -77,6 +239,102 @@ impl InteractionRequestHeaderName {
     }
 }
 
+/// Method of a request, as carried by errors rejecting it.
+///
+/// Methods are parsed case-sensitively, as HTTP methods are.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum RequestMethod {
+    /// `CONNECT` method.
+    Connect,
+    /// `DELETE` method.
+    Delete,
+    /// `GET` method.
+    Get,
+    /// `HEAD` method.
+    Head,
+    /// `OPTIONS` method.
+    Options,
+    /// `PATCH` method.
+    Patch,
+    /// `POST` method.
+    Post,
+    /// `PUT` method.
+    Put,
+    /// `TRACE` method.
+    Trace,
+    /// Other method, such as an extension method.
+    ///
+    /// The name of the method isn't carried, so that rejecting requests with
+    /// it doesn't allocate.
+    Other,
+}
+
+impl RequestMethod {
+    /// Parse the method of a request.
+    #[must_use = "parsing the method is not useful on its own"]
+    pub fn parse(method: &str) -> Self {
+        match method {
+            "CONNECT" => Self::Connect,
+            "DELETE" => Self::Delete,
+            "GET" => Self::Get,
+            "HEAD" => Self::Head,
+            "OPTIONS" => Self::Options,
+            "PATCH" => Self::Patch,
+            "POST" => Self::Post,
+            "PUT" => Self::Put,
+            "TRACE" => Self::Trace,
+            _ => Self::Other,
+        }
+    }
+
+    /// String name of the method.
+    #[must_use = "retrieving the name of the method is not useful on its own"]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Connect => "CONNECT",
+            Self::Delete => "DELETE",
+            Self::Get => "GET",
+            Self::Head => "HEAD",
+            Self::Options => "OPTIONS",
+            Self::Patch => "PATCH",
+            Self::Post => "POST",
+            Self::Put => "PUT",
+            Self::Trace => "TRACE",
+            Self::Other => "OTHER",
+        }
+    }
+}
+
+#[cfg(feature = "worker")]
+impl From<Method> for RequestMethod {
+    fn from(method: Method) -> Self {
+        match method {
+            Method::Connect => Self::Connect,
+            Method::Delete => Self::Delete,
+            Method::Get => Self::Get,
+            Method::Head => Self::Head,
+            Method::Options => Self::Options,
+            Method::Patch => Self::Patch,
+            Method::Post => Self::Post,
+            Method::Put => Self::Put,
+            Method::Trace => Self::Trace,
+        }
+    }
+}
+
+/// Body of the responses created for errors.
+#[cfg(feature = "worker")]
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub enum ErrorBody {
+    /// Description of the error.
+    #[default]
+    Description,
+    /// No body, as Discord recommends for requests whose signature is invalid.
+    Empty,
+    /// Provided text.
+    Text(String),
+}
+
 /// Interaction request could not be verified or otherwise processed.
 #[derive(Debug)]
 pub struct ProcessRequestError {
@@ -105,24 +363,89 @@ impl ProcessRequestError {
 
     /// Create a response for the error.
     ///
-    /// If the variant is [`ProcessRequestErrorType::InvalidSignature`] then the
-    /// returned response has a status code of 401 (Unauthorized), otherwise the
-    /// status code is 500 (Internal Service Error).
+    /// If the variant is [`ProcessRequestErrorType::InvalidSignature`],
+    /// [`ProcessRequestErrorType::InvalidTimestamp`],
+    /// [`ProcessRequestErrorType::SignatureFromHex`], or
+    /// [`ProcessRequestErrorType::SignatureLength`] then the returned response
+    /// has a status code of 401 (Unauthorized), if the variant is
+    /// [`ProcessRequestErrorType::RouteIncorrect`] then the status code is 404
+    /// (Not Found), if the variant is
+    /// [`ProcessRequestErrorType::MethodNotAllowed`] then the status code is
+    /// 405 (Method Not Allowed) with an `Allow: POST` header, if the variant is
+    /// [`ProcessRequestErrorType::BodyTooLarge`] then the status code is 413
+    /// (Payload Too Large), otherwise the status code is 500 (Internal Service
+    /// Error).
+    #[cfg(feature = "worker")]
     #[must_use = "created responses must be used to actually send the response"]
     pub fn response(&self) -> Response {
-        let status = if matches!(self.kind(), ProcessRequestErrorType::InvalidSignature) {
-            401
+        self.response_with_body(&ErrorBody::Description)
+    }
+
+    /// Create a response for the error with a body other than the error's
+    /// description, such as an empty body.
+    ///
+    /// The status code of the response is the same as for [`response`].
+    ///
+    /// [`response`]: Self::response
+    #[cfg(feature = "worker")]
+    #[must_use = "created responses must be used to actually send the response"]
+    pub fn response_with_body(&self, body: &ErrorBody) -> Response {
+        #[cfg(feature = "twilight-model")]
+        if let ProcessRequestErrorType::InteractionTypeNotAccepted { kind } = self.kind() {
+            let json = if *kind == AUTOCOMPLETE_INTERACTION_TYPE {
+                NOT_ACCEPTED_AUTOCOMPLETE_RESPONSE
+            } else {
+                NOT_ACCEPTED_RESPONSE
+            };
+            return build_response(
+                ResponseBody::Body(json.as_bytes().to_vec()),
+                200,
+                &[("Content-Type", "application/json")],
+            );
+        }
+
+        let status = match self.kind() {
+            ProcessRequestErrorType::BodyTooLarge { .. } => 413,
+            ProcessRequestErrorType::InvalidSignature
+            | ProcessRequestErrorType::InvalidTimestamp
+            | ProcessRequestErrorType::SignatureFromHex
+            | ProcessRequestErrorType::SignatureLength { .. } => 401,
+            ProcessRequestErrorType::MethodNotAllowed { .. } => 405,
+            ProcessRequestErrorType::RouteIncorrect { .. } => 404,
+            _ => 500,
+        };
+
+        let body = match body {
+            ErrorBody::Description => ResponseBody::Body(self.to_string().into_bytes()),
+            ErrorBody::Empty => ResponseBody::Empty,
+            ErrorBody::Text(text) => ResponseBody::Body(text.as_bytes().to_vec()),
+        };
+        let headers: &[(&str, &str)] = if status == 405 {
+            &[("Allow", "POST")]
         } else {
-            500
+            &[]
         };
 
-        Response::error(self.to_string(), status).expect("status code is valid")
+        build_response(body, status, headers)
     }
 }
 
 impl Display for ProcessRequestError {
+    #[cfg(feature = "minimal-errors")]
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
+        f.write_str("error ")?;
+
+        Display::fmt(&self.kind.code(), f)
+    }
+
+    #[cfg(not(feature = "minimal-errors"))]
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
         match self.kind() {
+            ProcessRequestErrorType::BodyTooLarge { max_body_size } => {
+                f.write_str("request body is larger than the maximum size of ")?;
+                Display::fmt(max_body_size, f)?;
+                f.write_str(" bytes")?;
+            }
             ProcessRequestErrorType::ChunkingBody => {
                 f.write_str("failed to chunk request body")?;
             }
@@ -135,15 +458,42 @@ impl Display for ProcessRequestError {
                     Debug::fmt(body, f)?;
                 }
             }
+            ProcessRequestErrorType::DeserializingWebhookEvent { body } => {
+                f.write_str("failed to deserialize request body as webhook event: ")?;
+
+                if let Ok(text) = str::from_utf8(body) {
+                    Display::fmt(text, f)?;
+                } else {
+                    Debug::fmt(body, f)?;
+                }
+            }
             ProcessRequestErrorType::FromHex => {
                 f.write_str("failed to register public key")?;
             }
+            ProcessRequestErrorType::InteractionTypeNotAccepted { kind } => {
+                f.write_str("interaction type ")?;
+                Display::fmt(kind, f)?;
+                f.write_str(" is not accepted")?;
+            }
             ProcessRequestErrorType::InvalidPublicKey => {
                 f.write_str("public key is invalid")?;
             }
             ProcessRequestErrorType::InvalidSignature => {
                 f.write_str("signature is invalid")?;
             }
+            ProcessRequestErrorType::InvalidTimestamp => {
+                f.write_str("timestamp is invalid")?;
+            }
+            ProcessRequestErrorType::LoadingPublicKey { name } => {
+                f.write_str("failed to load public key '")?;
+                f.write_str(name)?;
+                f.write_str("' from kv")?;
+            }
+            ProcessRequestErrorType::MethodNotAllowed { method } => {
+                f.write_str("method of the request ('")?;
+                write_lowercase(f, method.name())?;
+                f.write_str("') is not 'post'")?;
+            }
             ProcessRequestErrorType::MissingHeader { header } => {
                 f.write_str("header '")?;
                 f.write_str(header.name())?;
@@ -151,10 +501,18 @@ impl Display for ProcessRequestError {
             }
             ProcessRequestErrorType::RouteIncorrect { method, path } => {
                 f.write_str("route of the request ('")?;
-                f.write_str(&method.to_ascii_lowercase())?;
+                write_lowercase(f, method.name())?;
                 f.write_str(" ")?;
                 f.write_str(path)?;
-                f.write_str("') is not 'post /'")?;
+                f.write_str("') is not the interactions route")?;
+            }
+            ProcessRequestErrorType::SignatureFromHex => {
+                f.write_str("signature is not hex encoded")?;
+            }
+            ProcessRequestErrorType::SignatureLength { length } => {
+                f.write_str("signature has a length of ")?;
+                Display::fmt(length, f)?;
+                f.write_str(" instead of 128")?;
             }
         }
 
@@ -170,36 +528,289 @@ impl Error for ProcessRequestError {
     }
 }
 
+/// Convert the error into a Worker error, so that `?` can be used in handlers
+/// returning a [`worker::Result`].
+///
+/// The runtime responds to Worker errors with a status code of 500 (Internal
+/// Server Error). Use [`respond`] to instead respond with the error's own
+/// [`response`].
+///
+/// [`response`]: ProcessRequestError::response
+#[cfg(feature = "worker")]
+impl From<ProcessRequestError> for worker::Error {
+    fn from(error: ProcessRequestError) -> Self {
+        Self::RustError(error.to_string())
+    }
+}
+
+/// Error of a handler run with [`respond`].
+#[cfg(feature = "worker")]
+#[derive(Debug)]
+pub enum HandlerError {
+    /// Request could not be processed, which is responded to with the
+    /// error's [`response`].
+    ///
+    /// [`response`]: ProcessRequestError::response
+    Request(ProcessRequestError),
+    /// Worker error, which is returned from [`respond`].
+    Worker(worker::Error),
+}
+
+#[cfg(feature = "worker")]
+impl From<ProcessRequestError> for HandlerError {
+    fn from(error: ProcessRequestError) -> Self {
+        Self::Request(error)
+    }
+}
+
+#[cfg(feature = "worker")]
+impl From<worker::Error> for HandlerError {
+    fn from(error: worker::Error) -> Self {
+        Self::Worker(error)
+    }
+}
+
+/// Serialized body of a response and its content type, for composing worker
+/// responses with other headers or forwarding them to other Workers.
+#[cfg(all(
+    any(feature = "minimal-model", feature = "twilight-model"),
+    feature = "worker"
+))]
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct ResponseBytes {
+    /// Serialized body of the response.
+    pub body: Vec<u8>,
+    /// Content type of the body, such as `application/json` or
+    /// `multipart/form-data` with its boundary.
+    pub content_type: String,
+}
+
+/// Interaction response could not be serialized or created into a worker
+/// response.
+#[cfg(all(
+    any(feature = "minimal-model", feature = "twilight-model"),
+    feature = "worker"
+))]
+#[derive(Debug)]
+pub struct ResponseError {
+    kind: ResponseErrorType,
+    source: Option<Box<dyn Error>>,
+}
+
+#[cfg(all(
+    any(feature = "minimal-model", feature = "twilight-model"),
+    feature = "worker"
+))]
+impl ResponseError {
+    /// Immutable reference to the type of error that occurred.
+    #[must_use = "retrieving the type has no effect if left unused"]
+    pub const fn kind(&self) -> &ResponseErrorType {
+        &self.kind
+    }
+
+    /// Consume the error, returning the source error if there is any.
+    #[must_use = "consuming the error and retrieving the source has no effect if left unused"]
+    pub fn into_source(self) -> Option<Box<dyn Error>> {
+        self.source
+    }
+
+    /// Consume the error, returning the owned error type and the source error.
+    #[must_use = "consuming the error into its parts has no effect if left unused"]
+    pub fn into_parts(self) -> (ResponseErrorType, Option<Box<dyn Error>>) {
+        (self.kind, self.source)
+    }
+
+    /// Create an error of type [`ResponseErrorType::Creating`].
+    fn creating(source: worker::Error) -> Self {
+        Self {
+            kind: ResponseErrorType::Creating,
+            source: Some(Box::new(source)),
+        }
+    }
+}
+
+#[cfg(all(
+    any(feature = "minimal-model", feature = "twilight-model"),
+    feature = "worker"
+))]
+impl Display for ResponseError {
+    #[cfg(feature = "minimal-errors")]
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
+        f.write_str("error ")?;
+
+        Display::fmt(&self.kind.code(), f)
+    }
+
+    #[cfg(not(feature = "minimal-errors"))]
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError> {
+        match self.kind {
+            ResponseErrorType::Creating => f.write_str("failed to create worker response"),
+            ResponseErrorType::Serializing => {
+                f.write_str("failed to serialize interaction response")
+            }
+        }
+    }
+}
+
+#[cfg(all(
+    any(feature = "minimal-model", feature = "twilight-model"),
+    feature = "worker"
+))]
+impl Error for ResponseError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.source
+            .as_ref()
+            .map(|source| &**source as &(dyn Error + 'static))
+    }
+}
+
+/// Convert the error into a Worker error, so that `?` can be used in handlers
+/// returning a [`worker::Result`].
+#[cfg(all(
+    any(feature = "minimal-model", feature = "twilight-model"),
+    feature = "worker"
+))]
+impl From<ResponseError> for worker::Error {
+    fn from(error: ResponseError) -> Self {
+        Self::RustError(error.to_string())
+    }
+}
+
+/// Type of [`ResponseError`] that occurred.
+#[cfg(all(
+    any(feature = "minimal-model", feature = "twilight-model"),
+    feature = "worker"
+))]
+#[derive(Debug)]
+pub enum ResponseErrorType {
+    /// Worker response could not be created or its header could not be set.
+    Creating,
+    /// Interaction response could not be serialized.
+    Serializing,
+}
+
+#[cfg(all(
+    any(feature = "minimal-model", feature = "twilight-model"),
+    feature = "worker"
+))]
+impl ResponseErrorType {
+    /// Numeric code of the type of error, which errors are displayed as with
+    /// the `minimal-errors` feature.
+    ///
+    /// Refer to [`ProcessRequestErrorType::code`] for more information.
+    #[must_use = "retrieving the code is not useful on its own"]
+    pub const fn code(&self) -> u16 {
+        match self {
+            Self::Creating => 16,
+            Self::Serializing => 17,
+        }
+    }
+}
+
 /// Type of [`ProcessRequestError`] that occurred.
 #[derive(Debug)]
 pub enum ProcessRequestErrorType {
+    /// Request body is larger than the configured maximum size.
+    ///
+    /// Refer to `ConfigBuilder::max_body_size` for more information.
+    BodyTooLarge {
+        /// Maximum size of the body in bytes.
+        max_body_size: usize,
+    },
     /// Failed to chunk the request body.
     ChunkingBody,
     /// Failed to deserialize the request's interaction body.
     DeserializingInteraction {
-        /// Body of the request.
-        body: Vec<u8>,
+        /// Body of the request, which is cheap to clone.
+        body: Rc<[u8]>,
+    },
+    /// Failed to deserialize the request's webhook event body.
+    DeserializingWebhookEvent {
+        /// Body of the request, which is cheap to clone.
+        body: Rc<[u8]>,
     },
     /// Public key is not in a valid format.
     FromHex,
+    /// Type of the interaction is not accepted by the configuration.
+    ///
+    /// Refer to `ConfigBuilder::interaction_types` for more information.
+    /// Unlike other errors, responses created for the error have a status code
+    /// of 200 (OK) and answer the interaction, with empty choices for
+    /// autocomplete interactions and with an ephemeral message otherwise.
+    InteractionTypeNotAccepted {
+        /// Type of the interaction.
+        kind: u8,
+    },
     /// Public key is invalid.
     InvalidPublicKey,
     /// Request signature could not be verified.
     InvalidSignature,
+    /// Request timestamp is not made of digits.
+    InvalidTimestamp,
+    /// Public key could not be loaded from KV.
+    LoadingPublicKey {
+        /// Name the public key is stored under.
+        name: String,
+    },
+    /// Method of the request is not `POST`.
+    ///
+    /// Responses created for the error have an `Allow: POST` header.
+    MethodNotAllowed {
+        /// Method of the request.
+        method: RequestMethod,
+    },
     /// Required verification header is not present.
     MissingHeader {
         /// Name of the missing header.
         header: InteractionRequestHeaderName,
     },
-    /// Route (method + path) of the request is incorrect.
+    /// Path of the request is not the interactions route.
     RouteIncorrect {
         /// Method of the request.
-        method: String,
+        method: RequestMethod,
         /// Path of the request.
-        path: String,
+        ///
+        /// The root path is borrowed rather than allocated.
+        path: Cow<'static, str>,
+    },
+    /// Request signature is not hex encoded.
+    SignatureFromHex,
+    /// Request signature is not of the length of a hex encoded Ed25519
+    /// signature.
+    SignatureLength {
+        /// Length of the request signature.
+        length: usize,
     },
 }
 
+impl ProcessRequestErrorType {
+    /// Numeric code of the type of error, which errors are displayed as with
+    /// the `minimal-errors` feature.
+    ///
+    /// Codes are stable across versions and features, unique across the types
+    /// of errors of the crate, and new types of errors are given new codes.
+    #[must_use = "retrieving the code is not useful on its own"]
+    pub const fn code(&self) -> u16 {
+        match self {
+            Self::BodyTooLarge { .. } => 1,
+            Self::ChunkingBody => 2,
+            Self::DeserializingInteraction { .. } => 3,
+            Self::DeserializingWebhookEvent { .. } => 4,
+            Self::FromHex => 5,
+            Self::InteractionTypeNotAccepted { .. } => 6,
+            Self::InvalidPublicKey => 7,
+            Self::InvalidSignature => 8,
+            Self::InvalidTimestamp => 9,
+            Self::LoadingPublicKey { .. } => 10,
+            Self::MethodNotAllowed { .. } => 11,
+            Self::MissingHeader { .. } => 12,
+            Self::RouteIncorrect { .. } => 13,
+            Self::SignatureFromHex => 14,
+            Self::SignatureLength { .. } => 15,
+        }
+    }
+}
+
 /// Process a request, returning the request's interaction body if the request
 /// is valid.
 ///
@@ -208,6 +819,7 @@ pub enum ProcessRequestErrorType {
 /// # Errors
 ///
 /// Refer to the documentation for [`request`].
+#[cfg(all(feature = "twilight-model", feature = "worker"))]
 #[deprecated(note = "use `request` instead")]
 pub async fn process(
     request: &mut Request,
@@ -224,6 +836,10 @@ pub async fn process(
 /// Returns an error of type [`ChunkingBody`] if the request body could not be
 /// chunked.
 ///
+/// Use [`Config::request`] to process requests with a maximum body size, which
+/// additionally returns an error of type [`BodyTooLarge`] if the request body
+/// exceeds it.
+///
 /// Returns an error of type [`DeserializingInteraction`] if the request body
 /// could not be deserialized as an interaction.
 ///
@@ -236,97 +852,593 @@ pub async fn process(
 /// Returns an error of type [`InvalidSignature`] if the request signature could
 /// not be verified.
 ///
+/// Returns an error of type [`InvalidTimestamp`] if the request timestamp is
+/// not made of digits.
+///
 /// Returns an error of type [`MissingHeader`] if a required verification header
 /// is not present.
 ///
-/// Returns an error of type [`RouteIncorrect`] if the route is not `POST /`.
+/// Returns an error of type [`MethodNotAllowed`] if the path is `/` but the
+/// method is not `POST`.
+///
+/// Returns an error of type [`RouteIncorrect`] if the path is not `/`.
+///
+/// Returns an error of type [`SignatureFromHex`] if the request signature is
+/// not hex encoded.
+///
+/// Returns an error of type [`SignatureLength`] if the request signature is
+/// not of the length of an Ed25519 signature.
 ///
+/// [`BodyTooLarge`]: ProcessRequestErrorType::BodyTooLarge
 /// [`ChunkingBody`]: ProcessRequestErrorType::ChunkingBody
 /// [`DeserializingInteraction`]: ProcessRequestErrorType::DeserializingInteraction
 /// [`FromHex`]: ProcessRequestErrorType::FromHex
 /// [`InvalidPublicKey`]: ProcessRequestErrorType::InvalidPublicKey
 /// [`InvalidSignature`]: ProcessRequestErrorType::InvalidSignature
+/// [`InvalidTimestamp`]: ProcessRequestErrorType::InvalidTimestamp
+/// [`MethodNotAllowed`]: ProcessRequestErrorType::MethodNotAllowed
 /// [`MissingHeader`]: ProcessRequestErrorType::MissingHeader
 /// [`RouteIncorrect`]: ProcessRequestErrorType::RouteIncorrect
+/// [`SignatureFromHex`]: ProcessRequestErrorType::SignatureFromHex
+/// [`SignatureLength`]: ProcessRequestErrorType::SignatureLength
+#[cfg(all(feature = "twilight-model", feature = "worker"))]
 pub async fn request(
     req: &mut Request,
     public_key: &str,
 ) -> Result<Interaction, ProcessRequestError> {
-    let (method, path) = (req.method(), req.path());
+    let body = verify_request(req, Endpoint::Interactions { path: "/" }, public_key, None).await?;
+
+    // Deserialize the body into a interaction.
+    parse::deserialize(body)
+}
+
+/// Process a request if it is for the interactions route, returning the
+/// request's interaction body if the request is valid.
+///
+/// Unlike [`request`], requests whose route is not `POST /` return `None`
+/// with their body left untouched, so that they can be handled by the
+/// Worker's own routing.
+///
+/// ```ignore
+/// if let Some(interaction) = twilight_cloudflare_workers::request_if_matched(&mut req, key).await? {
+///     return handle(interaction).await;
+/// }
+///
+/// router.run(req, env).await
+/// ```
+///
+/// # Errors
+///
+/// Refer to the documentation for [`request`].
+#[cfg(all(feature = "twilight-model", feature = "worker"))]
+pub async fn request_if_matched(
+    req: &mut Request,
+    public_key: &str,
+) -> Result<Option<Interaction>, ProcessRequestError> {
+    // Match the route the same way as when verifying the request, so that
+    // matched requests aren't then rejected as being for another route.
+    if req.method() != Method::Post || match_path("/", &req.path()).is_none() {
+        return Ok(None);
+    }
+
+    request(req, public_key).await.map(Some)
+}
 
-    if method != Method::Post || path != "/" {
+/// Process a request, returning the request's interaction body if the request
+/// is valid, deserializing only what is required to route the interaction.
+///
+/// This is useful for large interactions when only some of them need to be
+/// fully deserialized, or to choose a handler before paying for the full
+/// deserialization of the interaction.
+///
+/// # Errors
+///
+/// Returns an error of type [`ChunkingBody`] if the request body could not be
+/// chunked.
+///
+/// Returns an error of type [`DeserializingInteraction`] if the type or data of
+/// the request body is invalid.
+///
+/// Returns an error of type [`FromHex`] if the provided public key is not in a
+/// valid format.
+///
+/// Returns an error of type [`InvalidPublicKey`] if the provided public key is
+/// invalid.
+///
+/// Returns an error of type [`InvalidSignature`] if the request signature could
+/// not be verified.
+///
+/// Returns an error of type [`InvalidTimestamp`] if the request timestamp is
+/// not made of digits.
+///
+/// Returns an error of type [`MissingHeader`] if a required verification header
+/// is not present.
+///
+/// Returns an error of type [`MethodNotAllowed`] if the path is `/` but the
+/// method is not `POST`.
+///
+/// Returns an error of type [`RouteIncorrect`] if the path is not `/`.
+///
+/// Returns an error of type [`SignatureFromHex`] if the request signature is
+/// not hex encoded.
+///
+/// Returns an error of type [`SignatureLength`] if the request signature is
+/// not of the length of an Ed25519 signature.
+///
+/// [`ChunkingBody`]: ProcessRequestErrorType::ChunkingBody
+/// [`DeserializingInteraction`]: ProcessRequestErrorType::DeserializingInteraction
+/// [`FromHex`]: ProcessRequestErrorType::FromHex
+/// [`InvalidPublicKey`]: ProcessRequestErrorType::InvalidPublicKey
+/// [`InvalidSignature`]: ProcessRequestErrorType::InvalidSignature
+/// [`InvalidTimestamp`]: ProcessRequestErrorType::InvalidTimestamp
+/// [`MethodNotAllowed`]: ProcessRequestErrorType::MethodNotAllowed
+/// [`MissingHeader`]: ProcessRequestErrorType::MissingHeader
+/// [`RouteIncorrect`]: ProcessRequestErrorType::RouteIncorrect
+/// [`SignatureFromHex`]: ProcessRequestErrorType::SignatureFromHex
+/// [`SignatureLength`]: ProcessRequestErrorType::SignatureLength
+#[cfg(feature = "worker")]
+pub async fn request_lazy(
+    req: &mut Request,
+    public_key: &str,
+) -> Result<LazyInteraction, ProcessRequestError> {
+    let (body, timing) =
+        verify_request_timed(req, Endpoint::Interactions { path: "/" }, public_key, None).await?;
+
+    Ok(LazyInteraction::new(body)?.with_timing(timing))
+}
+
+/// Verify the parts of a request, such as a request received outside of a
+/// Worker's fetch handler or by another framework.
+///
+/// This performs the same checks as `request_lazy` without a worker
+/// `Request`: the request must be a `POST` on the path `/` with valid
+/// verification headers, whose names are matched case-insensitively, and a
+/// body signed by the public key.
+///
+/// ```ignore
+/// twilight_cloudflare_workers::verify_parts(
+///     "POST",
+///     "/",
+///     [
+///         ("X-Signature-Ed25519", signature),
+///         ("X-Signature-Timestamp", timestamp),
+///     ],
+///     &body,
+///     public_key,
+/// )?;
+///
+/// let interaction = LazyInteraction::new(body)?;
+/// ```
+///
+/// # Errors
+///
+/// Returns an error of type [`FromHex`] if the provided public key is not in a
+/// valid format.
+///
+/// Returns an error of type [`InvalidPublicKey`] if the provided public key is
+/// invalid.
+///
+/// Returns an error of type [`InvalidSignature`] if the request signature could
+/// not be verified.
+///
+/// Returns an error of type [`InvalidTimestamp`] if the request timestamp is
+/// not made of digits.
+///
+/// Returns an error of type [`MissingHeader`] if a required verification header
+/// is not present.
+///
+/// Returns an error of type [`MethodNotAllowed`] if the path is `/` but the
+/// method is not `POST`.
+///
+/// Returns an error of type [`RouteIncorrect`] if the path is not `/`.
+///
+/// Returns an error of type [`SignatureFromHex`] if the request signature is
+/// not hex encoded.
+///
+/// Returns an error of type [`SignatureLength`] if the request signature is
+/// not of the length of an Ed25519 signature.
+///
+/// [`FromHex`]: ProcessRequestErrorType::FromHex
+/// [`InvalidPublicKey`]: ProcessRequestErrorType::InvalidPublicKey
+/// [`InvalidSignature`]: ProcessRequestErrorType::InvalidSignature
+/// [`InvalidTimestamp`]: ProcessRequestErrorType::InvalidTimestamp
+/// [`MethodNotAllowed`]: ProcessRequestErrorType::MethodNotAllowed
+/// [`MissingHeader`]: ProcessRequestErrorType::MissingHeader
+/// [`RouteIncorrect`]: ProcessRequestErrorType::RouteIncorrect
+/// [`SignatureFromHex`]: ProcessRequestErrorType::SignatureFromHex
+/// [`SignatureLength`]: ProcessRequestErrorType::SignatureLength
+pub fn verify_parts<K: AsRef<str>, V: AsRef<str>>(
+    method: &str,
+    path: &str,
+    headers: impl IntoIterator<Item = (K, V)>,
+    body: &[u8],
+    public_key: &str,
+) -> Result<(), ProcessRequestError> {
+    verify_head(Endpoint::Interactions { path: "/" }, method, path.into())?;
+
+    VerificationHeaders::from_headers(headers)?.verify(public_key, body)
+}
+
+/// Run a handler in which `?` can be used on both the errors of processing
+/// requests and Worker errors, responding to the former with their
+/// [`response`] and returning the latter.
+///
+/// ```ignore
+/// #[event(fetch)]
+/// async fn fetch(mut req: Request, env: Env, _: Context) -> Result<Response> {
+///     twilight_cloudflare_workers::respond(async {
+///         let public_key = env.var("PUBLIC_KEY")?.to_string();
+///         let interaction = twilight_cloudflare_workers::request(&mut req, &public_key).await?;
+///
+///         Ok(handle(interaction).await?)
+///     })
+///     .await
+/// }
+/// ```
+///
+/// # Errors
+///
+/// Returns the Worker error of the handler, if any.
+///
+/// [`response`]: ProcessRequestError::response
+#[cfg(feature = "worker")]
+pub async fn respond(
+    handler: impl Future<Output = Result<Response, HandlerError>>,
+) -> worker::Result<Response> {
+    match handler.await {
+        Ok(response) => Ok(response),
+        Err(HandlerError::Request(source)) => Ok(source.response()),
+        Err(HandlerError::Worker(source)) => Err(source),
+    }
+}
+
+/// Set up the isolate, such as from `#[event(start)]` or on every request,
+/// installing a panic hook and warming the public key cache.
+///
+/// The panic hook logs panics with `console.error` before calling the
+/// previously installed hook, so that their messages appear in the Worker's
+/// logs rather than only an opaque `unreachable` trap. It is installed once
+/// per isolate, however often this is called. Parsing the public key caches it
+/// for the requests processed by the isolate.
+///
+/// ```ignore
+/// #[event(fetch)]
+/// async fn fetch(mut req: Request, env: Env, _: Context) -> Result<Response> {
+///     let public_key = env.var("PUBLIC_KEY")?.to_string();
+///     twilight_cloudflare_workers::init(&public_key)?;
+///
+///     // ...
+/// }
+/// ```
+///
+/// # Errors
+///
+/// Returns an error of type [`FromHex`] if the public key is not in a valid
+/// format.
+///
+/// Returns an error of type [`InvalidPublicKey`] if the public key is invalid.
+///
+/// [`FromHex`]: ProcessRequestErrorType::FromHex
+/// [`InvalidPublicKey`]: ProcessRequestErrorType::InvalidPublicKey
+#[cfg(feature = "worker")]
+pub fn init(public_key: &str) -> Result<(), ProcessRequestError> {
+    static PANIC_HOOK: Once = Once::new();
+
+    PANIC_HOOK.call_once(|| {
+        let previous = panic::take_hook();
+
+        panic::set_hook(Box::new(move |info| {
+            worker::console_error!("{info}");
+            previous(info);
+        }));
+    });
+
+    key::parse(public_key).map(|_| ())
+}
+
+/// Load an application's hex encoded public key stored in KV under a name,
+/// such as its application ID, so that keys can be added or rotated without
+/// redeploying the Worker.
+///
+/// Loaded keys are cached in the isolate until the TTL has passed.
+///
+/// ```ignore
+/// let kv = env.kv("PUBLIC_KEYS")?;
+/// let public_key = twilight_cloudflare_workers::public_key_from_kv(
+///     &kv,
+///     application_id,
+///     Duration::from_secs(60),
+/// )
+/// .await?;
+///
+/// let interaction = twilight_cloudflare_workers::request_lazy(&mut req, &public_key).await?;
+/// ```
+///
+/// # Errors
+///
+/// Returns an error of type [`LoadingPublicKey`] if the public key could not be
+/// retrieved from KV or is not stored.
+///
+/// [`LoadingPublicKey`]: ProcessRequestErrorType::LoadingPublicKey
+#[cfg(feature = "worker")]
+pub async fn public_key_from_kv(
+    kv: &KvStore,
+    name: &str,
+    ttl: Duration,
+) -> Result<String, ProcessRequestError> {
+    key::load(kv, name, ttl).await
+}
+
+/// Process a webhook events request, returning the request's webhook event
+/// body if the request is valid.
+///
+/// Unlike interactions, webhook events may be received on any path, so that
+/// the same Worker can serve both. Discord expects every webhook event,
+/// including [pings], to be acknowledged with a [`webhook_event_response`].
+///
+/// # Errors
+///
+/// Returns an error of type [`DeserializingWebhookEvent`] if the request body
+/// could not be deserialized as a webhook event.
+///
+/// Returns an error of type [`MethodNotAllowed`] if the method is not `POST`.
+///
+/// Refer to the documentation for [`request_lazy`] for the errors returned when
+/// the request could not be verified.
+///
+/// [`DeserializingWebhookEvent`]: ProcessRequestErrorType::DeserializingWebhookEvent
+/// [`MethodNotAllowed`]: ProcessRequestErrorType::MethodNotAllowed
+/// [pings]: webhook_events::WebhookEventType::Ping
+#[cfg(all(feature = "twilight-model", feature = "worker"))]
+pub async fn webhook_event(
+    req: &mut Request,
+    public_key: &str,
+) -> Result<WebhookEvent, ProcessRequestError> {
+    let body = verify_request(req, Endpoint::WebhookEvents, public_key, None).await?;
+
+    webhook_event_from_body(body)
+}
+
+/// Deserialize a verified body as a webhook event.
+#[cfg(all(feature = "twilight-model", feature = "worker"))]
+fn webhook_event_from_body(body: Vec<u8>) -> Result<WebhookEvent, ProcessRequestError> {
+    serde_json::from_slice(&body).map_err(|source| ProcessRequestError {
+        kind: ProcessRequestErrorType::DeserializingWebhookEvent { body: body.into() },
+        source: Some(Box::new(source)),
+    })
+}
+
+/// Create a new worker response acknowledging a webhook event.
+///
+/// The response has a status code of 204 (No Content) and no body.
+#[cfg(feature = "worker")]
+#[must_use = "created responses must be used to actually send the response"]
+pub fn webhook_event_response() -> Response {
+    build_response(ResponseBody::Empty, 204, &[])
+}
+
+/// Endpoint a request is being verified for.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Endpoint<'a> {
+    /// Interactions endpoint, which must be requested via `POST` on the path.
+    Interactions {
+        /// Path pattern of the interactions endpoint.
+        path: &'a str,
+    },
+    /// Webhook events endpoint, which must be requested via `POST` on any path.
+    #[cfg(all(feature = "twilight-model", feature = "worker"))]
+    WebhookEvents,
+}
+
+/// Verify a request, returning the body of the request if it is valid.
+#[cfg(all(
+    any(feature = "minimal-model", feature = "twilight-model"),
+    feature = "worker"
+))]
+async fn verify_request(
+    req: &mut Request,
+    endpoint: Endpoint<'_>,
+    public_key: &str,
+    max_body_size: Option<usize>,
+) -> Result<Vec<u8>, ProcessRequestError> {
+    verify_request_timed(req, endpoint, public_key, max_body_size)
+        .await
+        .map(|(body, _)| body)
+}
+
+/// Verify a request, returning the body of the request and when it was
+/// signed and received if it is valid.
+#[cfg(feature = "worker")]
+async fn verify_request_timed(
+    req: &mut Request,
+    endpoint: Endpoint<'_>,
+    public_key: &str,
+    max_body_size: Option<usize>,
+) -> Result<(Vec<u8>, RequestTiming), ProcessRequestError> {
+    let received_at = Date::now().as_millis();
+
+    verify_head(endpoint, req.method().as_ref(), req.path().into())?;
+
+    // Retrieve only the timestamp and signature headers, rather than every
+    // header of the request, for use later to check the signature.
+    let headers = req.headers();
+    let header = |header: InteractionRequestHeaderName| {
+        headers
+            .get(header.name())
+            .ok()
+            .flatten()
+            .ok_or(ProcessRequestError {
+                kind: ProcessRequestErrorType::MissingHeader { header },
+                source: None,
+            })
+    };
+    let timestamp = header(InteractionRequestHeaderName::Timestamp)?;
+    let signature = RequestSignature::new(
+        &timestamp,
+        &header(InteractionRequestHeaderName::Signature)?,
+    )?;
+    let timing = RequestTiming {
+        received_at,
+        // The timestamp is made of digits once the signature has been created,
+        // so it only fails to parse if it overflows.
+        signed_at: timestamp
+            .parse::<u64>()
+            .map_or(u64::MAX, |secs| secs.saturating_mul(1000)),
+    };
+
+    // Fetch the whole body of the request as that is needed to check the
+    // signature against.
+    let Some(max_body_size) = max_body_size else {
+        let body = req.bytes().await.map_err(|source| ProcessRequestError {
+            kind: ProcessRequestErrorType::ChunkingBody,
+            source: Some(Box::new(source)),
+        })?;
+
+        signature.verify(public_key, &body)?;
+
+        return Ok((body, timing));
+    };
+
+    // Stream the body directly after the timestamp to form the signed message
+    // without copying the body again.
+    let mut message = Vec::from(timestamp.as_bytes());
+    read_body(req, &mut message, max_body_size).await?;
+    signature.verify_message(public_key, &message)?;
+
+    // Remove the timestamp, leaving only the body.
+    message.drain(..timestamp.len());
+
+    Ok((message, timing))
+}
+
+/// Check that the method and path of a request are those of the endpoint.
+fn verify_head(
+    endpoint: Endpoint<'_>,
+    method: &str,
+    path: Cow<'_, str>,
+) -> Result<(), ProcessRequestError> {
+    let route_matches = match endpoint {
+        Endpoint::Interactions { path: expected } => match_path(expected, &path).is_some(),
+        // Webhook events may be received on any path.
+        #[cfg(all(feature = "twilight-model", feature = "worker"))]
+        Endpoint::WebhookEvents => true,
+    };
+
+    if !route_matches {
         return Err(ProcessRequestError {
             kind: ProcessRequestErrorType::RouteIncorrect {
-                method: method.to_string(),
-                path,
+                method: RequestMethod::parse(method),
+                path: static_path(path),
             },
             source: None,
         });
     }
 
-    // Extract the timestamp header for use later to check the signature.
-    let timestamp = req
-        .headers()
-        .get(InteractionRequestHeaderName::Timestamp.name())
-        .expect("header name is valid")
-        .ok_or(ProcessRequestError {
-            kind: ProcessRequestErrorType::MissingHeader {
-                header: InteractionRequestHeaderName::Timestamp,
+    if method != "POST" {
+        return Err(ProcessRequestError {
+            kind: ProcessRequestErrorType::MethodNotAllowed {
+                method: RequestMethod::parse(method),
             },
             source: None,
-        })?;
+        });
+    }
 
-    let signature_header = req
-        .headers()
-        .get(InteractionRequestHeaderName::Signature.name())
-        .expect("header name is valid")
-        .ok_or(ProcessRequestError {
-            kind: ProcessRequestErrorType::MissingHeader {
-                header: InteractionRequestHeaderName::Signature,
-            },
-            source: None,
-        })?;
+    Ok(())
+}
 
-    let signature = signature_header
-        .parse()
-        .map_err(|source| ProcessRequestError {
-            kind: ProcessRequestErrorType::InvalidSignature,
-            source: Some(Box::new(source)),
-        })?;
+/// Path of a request to be carried by an error, reusing owned paths and
+/// borrowing the root path rather than allocating either.
+fn static_path(path: Cow<'_, str>) -> Cow<'static, str> {
+    if path == "/" {
+        Cow::Borrowed("/")
+    } else {
+        Cow::Owned(path.into_owned())
+    }
+}
 
-    let hex = <[u8; PUBLIC_KEY_LENGTH] as FromHex>::from_hex(public_key).map_err(|source| {
-        ProcessRequestError {
-            kind: ProcessRequestErrorType::FromHex,
-            source: Some(Box::new(source)),
+/// Write a string in lowercase, without allocating.
+#[cfg(not(feature = "minimal-errors"))]
+fn write_lowercase(f: &mut Formatter<'_>, value: &str) -> Result<(), FmtError> {
+    use core::fmt::Write;
+
+    value
+        .chars()
+        .try_for_each(|character| f.write_char(character.to_ascii_lowercase()))
+}
+
+/// Match a path against a pattern in which `*` segments match any single
+/// non-empty segment, returning the segments they matched.
+pub(crate) fn match_path<'a>(pattern: &str, path: &'a str) -> Option<Vec<&'a str>> {
+    let mut captures = Vec::new();
+    let mut segments = path.split('/');
+
+    for expected in pattern.split('/') {
+        let segment = segments.next()?;
+
+        if expected == "*" {
+            if segment.is_empty() {
+                return None;
+            }
+
+            captures.push(segment);
+        } else if expected != segment {
+            return None;
         }
-    })?;
-    let key = PublicKey::from_bytes(&hex).map_err(|source| ProcessRequestError {
-        kind: ProcessRequestErrorType::InvalidPublicKey,
-        source: Some(Box::new(source)),
-    })?;
+    }
 
-    // Fetch the whole body of the request as that is needed to check the
-    // signature against.
-    let body = req.bytes().await.map_err(|source| ProcessRequestError {
+    segments.next().is_none().then_some(captures)
+}
+
+/// Read the body of a request as a stream into a buffer, stopping as soon as
+/// the body exceeds the maximum size.
+#[cfg(feature = "worker")]
+async fn read_body(
+    req: &mut Request,
+    buf: &mut Vec<u8>,
+    max_body_size: usize,
+) -> Result<(), ProcessRequestError> {
+    let too_large = || ProcessRequestError {
+        kind: ProcessRequestErrorType::BodyTooLarge { max_body_size },
+        source: None,
+    };
+
+    // Reject the request before reading any of it if the declared length is
+    // already too large.
+    let content_length = req
+        .headers()
+        .get("Content-Length")
+        .ok()
+        .flatten()
+        .and_then(|value| value.parse::<usize>().ok());
+
+    if let Some(content_length) = content_length {
+        if content_length > max_body_size {
+            return Err(too_large());
+        }
+
+        buf.reserve(content_length);
+    }
+
+    let mut stream = req.stream().map_err(|source| ProcessRequestError {
         kind: ProcessRequestErrorType::ChunkingBody,
         source: Some(Box::new(source)),
     })?;
+    let start = buf.len();
 
-    // Check if the signature matches and else return a error response.
-    let message = Vec::from([timestamp.as_bytes(), &body]).concat();
-
-    if let Err(source) = key.verify(&message, &signature) {
-        return Err(ProcessRequestError {
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.map_err(|source| ProcessRequestError {
+            kind: ProcessRequestErrorType::ChunkingBody,
             source: Some(Box::new(source)),
-            kind: ProcessRequestErrorType::InvalidSignature,
-        });
+        })?;
+
+        if buf.len() - start + chunk.len() > max_body_size {
+            return Err(too_large());
+        }
+
+        buf.extend_from_slice(&chunk);
     }
 
-    // Deserialize the body into a interaction.
-    serde_json::from_slice(&body).map_err(|source| ProcessRequestError {
-        kind: ProcessRequestErrorType::DeserializingInteraction { body },
-        source: Some(Box::new(source)),
-    })
+    Ok(())
 }
 
 /// Create a new worker response from an interaction response.
@@ -335,18 +1447,337 @@ pub async fn request(
 ///
 /// If the interaction response could not be serialized then a 500 response is
 /// created noting that the response could not be serialized.
+#[cfg(all(feature = "twilight-model", feature = "worker"))]
 #[must_use = "created responses must be used to actually send the response"]
 pub fn response(response: &InteractionResponse) -> Response {
-    let Ok(json) = serde_json::to_string(response) else {
-        return Response::error("failed to serialize interaction response", 500)
-            .expect("status code is within acceptable range");
-    };
+    json_response(response)
+}
+
+/// Create a new worker response from an interaction response, returning an
+/// error instead of a 500 response if it could not be created.
+///
+/// Sets the `Content-Type` header to a value of `application/json`.
+///
+/// # Errors
+///
+/// Returns an error of type [`Creating`] if the worker response could not be
+/// created or its header could not be set.
+///
+/// Returns an error of type [`Serializing`] if the interaction response could
+/// not be serialized.
+///
+/// [`Creating`]: ResponseErrorType::Creating
+/// [`Serializing`]: ResponseErrorType::Serializing
+#[cfg(all(feature = "twilight-model", feature = "worker"))]
+pub fn try_response(response: &InteractionResponse) -> Result<Response, ResponseError> {
+    try_json_response(response)
+}
+
+/// Serialize an interaction response into the body and content type of a
+/// response, such as for composing a worker response with other headers or
+/// forwarding it to another Worker.
+///
+/// Use [`rest::response_bytes_with_attachments`] to serialize a response with
+/// files attached.
+///
+/// # Errors
+///
+/// Returns an error of type [`Serializing`] if the interaction response could
+/// not be serialized.
+///
+/// [`rest::response_bytes_with_attachments`]: crate::rest::response_bytes_with_attachments
+/// [`Serializing`]: ResponseErrorType::Serializing
+#[cfg(all(feature = "twilight-model", feature = "worker"))]
+pub fn response_bytes(response: &InteractionResponse) -> Result<ResponseBytes, ResponseError> {
+    json_bytes(response)
+}
+
+/// Serialize a serializable interaction response into the body and content
+/// type of a response.
+#[cfg(all(
+    any(feature = "minimal-model", feature = "twilight-model"),
+    feature = "worker"
+))]
+pub(crate) fn json_bytes<T: Serialize>(response: &T) -> Result<ResponseBytes, ResponseError> {
+    let body = serde_json::to_vec(response).map_err(|source| ResponseError {
+        kind: ResponseErrorType::Serializing,
+        source: Some(Box::new(source)),
+    })?;
+
+    Ok(ResponseBytes {
+        body,
+        content_type: "application/json".to_owned(),
+    })
+}
 
-    let mut response = Response::ok(json).expect("creating a response shouldn't fail");
+/// Create a new worker response from a serializable interaction response.
+#[cfg(all(
+    any(feature = "minimal-model", feature = "twilight-model"),
+    feature = "worker"
+))]
+pub(crate) fn try_json_response<T: Serialize>(response: &T) -> Result<Response, ResponseError> {
+    let ResponseBytes { body, content_type } = json_bytes(response)?;
+    let mut response =
+        Response::from_body(ResponseBody::Body(body)).map_err(ResponseError::creating)?;
     response
         .headers_mut()
-        .set("Content-Type", "application/json")
-        .expect("Content-Type header is valid");
+        .set("Content-Type", &content_type)
+        .map_err(ResponseError::creating)?;
+
+    Ok(response)
+}
+
+/// Create a new worker response from a serializable interaction response.
+#[cfg(all(
+    any(feature = "minimal-model", feature = "twilight-model"),
+    feature = "worker"
+))]
+fn json_response<T: Serialize>(response: &T) -> Response {
+    let Ok(json) = serde_json::to_vec(response) else {
+        return build_response(
+            ResponseBody::Body(b"failed to serialize interaction response".to_vec()),
+            500,
+            &[],
+        );
+    };
+
+    build_response(
+        ResponseBody::Body(json),
+        200,
+        &[("Content-Type", "application/json")],
+    )
+}
+
+/// Create a new worker response with a body, status code, and headers.
+///
+/// Unlike worker's other constructors, this doesn't validate the status code
+/// or fail when a header can't be set, so that creating responses can't panic
+/// and trap the isolate. Headers whose values are invalid are left out.
+#[cfg(feature = "worker")]
+pub(crate) fn build_response(
+    body: ResponseBody,
+    status: u16,
+    headers: &[(&str, &str)],
+) -> Response {
+    // Creating a response from a body only wraps the body, so it doesn't fail.
+    let mut response = Response::from_body(body)
+        .expect("creating a response from a body doesn't fail")
+        .with_status(status);
+
+    for (name, value) in headers {
+        let _ = response.headers_mut().set(name, value);
+    }
 
     response
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{match_path, InteractionRequestHeaderName, ProcessRequestErrorType, RequestMethod};
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    #[test]
+    #[allow(clippy::too_many_lines)]
+    fn error_codes() {
+        assert_eq!(
+            [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+            [
+                ProcessRequestErrorType::BodyTooLarge { max_body_size: 0 },
+                ProcessRequestErrorType::ChunkingBody,
+                ProcessRequestErrorType::DeserializingInteraction { body: [].into() },
+                ProcessRequestErrorType::DeserializingWebhookEvent { body: [].into() },
+                ProcessRequestErrorType::FromHex,
+                ProcessRequestErrorType::InteractionTypeNotAccepted { kind: 0 },
+                ProcessRequestErrorType::InvalidPublicKey,
+                ProcessRequestErrorType::InvalidSignature,
+                ProcessRequestErrorType::InvalidTimestamp,
+                ProcessRequestErrorType::LoadingPublicKey {
+                    name: String::new(),
+                },
+                ProcessRequestErrorType::MethodNotAllowed {
+                    method: RequestMethod::Get,
+                },
+                ProcessRequestErrorType::MissingHeader {
+                    header: InteractionRequestHeaderName::Signature,
+                },
+                ProcessRequestErrorType::RouteIncorrect {
+                    method: RequestMethod::Get,
+                    path: "/".into(),
+                },
+                ProcessRequestErrorType::SignatureFromHex,
+                ProcessRequestErrorType::SignatureLength { length: 0 },
+            ]
+            .map(|kind| kind.code())
+        );
+
+        #[cfg(all(
+            any(feature = "minimal-model", feature = "twilight-model"),
+            feature = "worker"
+        ))]
+        assert_eq!(
+            [16, 17],
+            [
+                super::ResponseErrorType::Creating,
+                super::ResponseErrorType::Serializing
+            ]
+            .map(|kind| kind.code())
+        );
+
+        #[cfg(all(feature = "twilight-model", feature = "worker"))]
+        {
+            use crate::{
+                attachments::DownloadErrorType, premium::PremiumErrorType, rest::RestErrorType,
+            };
+
+            assert_eq!(
+                [18, 19, 20, 21, 22, 23],
+                [
+                    RestErrorType::Deserializing { body: Vec::new() },
+                    RestErrorType::RateLimited {
+                        retry_after: core::time::Duration::ZERO,
+                    },
+                    RestErrorType::Requesting,
+                    RestErrorType::Serializing,
+                    RestErrorType::Status {
+                        body: Vec::new(),
+                        status: 500,
+                    },
+                    RestErrorType::TokenExpired,
+                ]
+                .map(|kind| kind.code())
+            );
+            assert_eq!(
+                [37, 38, 39, 40],
+                [
+                    DownloadErrorType::ContentTypeNotAllowed { content_type: None },
+                    DownloadErrorType::Requesting,
+                    DownloadErrorType::Status { status: 500 },
+                    DownloadErrorType::TooLarge { max_size: 0 },
+                ]
+                .map(|kind| kind.code())
+            );
+            assert_eq!(
+                [42, 43],
+                [PremiumErrorType::Fetching, PremiumErrorType::Storing].map(|kind| kind.code())
+            );
+        }
+
+        #[cfg(feature = "oauth2")]
+        {
+            use crate::oauth2::OAuth2ErrorType;
+
+            assert_eq!(
+                [24, 25, 26, 27, 28],
+                [
+                    OAuth2ErrorType::Deserializing { body: Vec::new() },
+                    OAuth2ErrorType::Random,
+                    OAuth2ErrorType::Requesting,
+                    OAuth2ErrorType::Status {
+                        body: Vec::new(),
+                        status: 500,
+                    },
+                    OAuth2ErrorType::Store,
+                ]
+                .map(|kind| kind.code())
+            );
+        }
+
+        #[cfg(all(feature = "hmac", feature = "twilight-model", feature = "worker"))]
+        {
+            use crate::github_relay::RelayErrorType;
+
+            assert_eq!(
+                [29, 30, 31, 32],
+                [
+                    RelayErrorType::Deserializing,
+                    RelayErrorType::Posting,
+                    RelayErrorType::Reading,
+                    RelayErrorType::Verifying,
+                ]
+                .map(|kind| kind.code())
+            );
+        }
+
+        #[cfg(feature = "worker")]
+        {
+            use crate::{guild_config::GuildConfigErrorType, EnvErrorType};
+
+            assert_eq!(
+                [33, 34, 35, 36],
+                [
+                    GuildConfigErrorType::Deserializing,
+                    GuildConfigErrorType::Migrating { version: 0 },
+                    GuildConfigErrorType::Serializing,
+                    GuildConfigErrorType::Storing,
+                ]
+                .map(|kind| kind.code())
+            );
+            assert_eq!(
+                41,
+                EnvErrorType::Bindings {
+                    invalid: Vec::new(),
+                    missing: Vec::new(),
+                }
+                .code()
+            );
+        }
+
+        #[cfg(feature = "hmac")]
+        {
+            use crate::hmac_webhooks::VerifyErrorType;
+
+            assert_eq!(
+                [44, 45, 46],
+                [
+                    VerifyErrorType::InvalidSignature,
+                    VerifyErrorType::Malformed,
+                    VerifyErrorType::TimestampOutsideTolerance { timestamp: 0 },
+                ]
+                .map(|kind| kind.code())
+            );
+        }
+    }
+
+    #[test]
+    fn match_path_root() {
+        assert_eq!(match_path("/", "/"), Some(Vec::new()));
+        assert_eq!(match_path("/", ""), None);
+        assert_eq!(match_path("/", "/a"), None);
+        assert_eq!(match_path("/", "//"), None);
+    }
+
+    #[test]
+    fn match_path_literal() {
+        assert_eq!(match_path("/a", "/a"), Some(Vec::new()));
+        assert_eq!(match_path("/a", "/ab"), None);
+        assert_eq!(match_path("/ab", "/a"), None);
+        assert_eq!(match_path("/a", "/A"), None);
+        assert_eq!(match_path("/a/b", "/a"), None);
+        assert_eq!(match_path("/a", "/a/b"), None);
+    }
+
+    #[test]
+    fn match_path_trailing_slash() {
+        assert_eq!(match_path("/a", "/a/"), None);
+        assert_eq!(match_path("/a/", "/a"), None);
+        assert_eq!(match_path("/a/", "/a/"), Some(Vec::new()));
+        assert_eq!(match_path("/a/*", "/a/b/"), None);
+        assert_eq!(match_path("/a/*", "/a/"), None);
+    }
+
+    #[test]
+    fn match_path_params() {
+        assert_eq!(match_path("/*", "/a"), Some(vec!["a"]));
+        assert_eq!(
+            match_path("/tenants/*/interactions", "/tenants/123/interactions"),
+            Some(vec!["123"])
+        );
+        assert_eq!(match_path("/*/x/*", "/a/x/b"), Some(vec!["a", "b"]));
+        assert_eq!(match_path("/*/x/*", "/a/y/b"), None);
+        // Parameters only match a single non-empty segment.
+        assert_eq!(match_path("/*", "/"), None);
+        assert_eq!(match_path("/*", "/a/b"), None);
+        assert_eq!(match_path("/a/*/b", "/a//b"), None);
+    }
+}
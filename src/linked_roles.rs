@@ -0,0 +1,281 @@
+//! Linked roles, roles that members are granted based on metadata the
+//! application pushes about their connection with it.
+//!
+//! The metadata records the application's roles can require are registered
+//! once with [`BotClient::update_role_connection_metadata`]. Discord sends
+//! users to the application's linked roles verification URL, which redirects
+//! them to authorize the application with `verification_redirect` when the
+//! `oauth2` feature is enabled, and once the code Discord redirects them back
+//! with is exchanged for a token, the user's metadata is pushed with
+//! [`update_role_connection`].
+//!
+//! ```ignore
+//! use twilight_cloudflare_workers::{linked_roles::{self, RoleConnection}, oauth2};
+//!
+//! // When Discord sends the user to the verification URL.
+//! if req.path() == "/linked-role" {
+//!     return linked_roles::verification_redirect(&client);
+//! }
+//!
+//! // When Discord redirects the user back with a code and the state.
+//! if !oauth2::verify_cookie_state(req.headers().get("Cookie")?.as_deref(), &state) {
+//!     return Response::error("invalid state", 400);
+//! }
+//!
+//! let token = client.exchange_code(&code).await?;
+//! let connection = RoleConnection {
+//!     metadata: [("level".to_owned(), "12".to_owned())].into(),
+//!     platform_name: Some("Example".to_owned()),
+//!     platform_username: None,
+//! };
+//!
+//! linked_roles::update_role_connection(application_id, &token.access_token, &connection)
+//!     .await?;
+//! ```
+//!
+//! [`BotClient::update_role_connection_metadata`]: crate::rest::BotClient::update_role_connection_metadata
+
+use crate::rest::{self, Body, RestError};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use twilight_model::id::{marker::ApplicationMarker, Id};
+use worker::Method;
+#[cfg(feature = "oauth2")]
+use worker::{Response, Url};
+
+/// Scopes the application is authorized with by the verification flow, which
+/// are required to push the user's metadata.
+pub const SCOPES: &[&str] = &["identify", "role_connections.write"];
+
+/// Metadata record that the application's linked roles can require a value
+/// of the user's metadata to compare with.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct RoleConnectionMetadata {
+    /// Description of the record, of up to 200 characters.
+    pub description: String,
+    /// Key of the record in users' metadata, of up to 50 lowercase
+    /// alphanumeric characters and underscores.
+    pub key: String,
+    /// How users' values of the record are compared with roles' values.
+    #[serde(rename = "type")]
+    pub kind: RoleConnectionMetadataType,
+    /// Name of the record, of up to 100 characters.
+    pub name: String,
+}
+
+/// How users' values of a [`RoleConnectionMetadata`] record are compared with
+/// the value a linked role requires.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[non_exhaustive]
+#[serde(from = "u8", into = "u8")]
+pub enum RoleConnectionMetadataType {
+    /// User's boolean value is equal to the role's.
+    BooleanEqual,
+    /// User's boolean value is not equal to the role's.
+    BooleanNotEqual,
+    /// User's ISO 8601 date is at least the role's number of days ago.
+    DatetimeGreaterThanOrEqual,
+    /// User's ISO 8601 date is at most the role's number of days ago.
+    DatetimeLessThanOrEqual,
+    /// User's integer value is equal to the role's.
+    IntegerEqual,
+    /// User's integer value is greater than or equal to the role's.
+    IntegerGreaterThanOrEqual,
+    /// User's integer value is less than or equal to the role's.
+    IntegerLessThanOrEqual,
+    /// User's integer value is not equal to the role's.
+    IntegerNotEqual,
+    /// Type of comparison unknown to this crate.
+    Unknown(u8),
+}
+
+impl From<u8> for RoleConnectionMetadataType {
+    fn from(value: u8) -> Self {
+        match value {
+            1 => Self::IntegerLessThanOrEqual,
+            2 => Self::IntegerGreaterThanOrEqual,
+            3 => Self::IntegerEqual,
+            4 => Self::IntegerNotEqual,
+            5 => Self::DatetimeLessThanOrEqual,
+            6 => Self::DatetimeGreaterThanOrEqual,
+            7 => Self::BooleanEqual,
+            8 => Self::BooleanNotEqual,
+            unknown => Self::Unknown(unknown),
+        }
+    }
+}
+
+impl From<RoleConnectionMetadataType> for u8 {
+    fn from(value: RoleConnectionMetadataType) -> Self {
+        match value {
+            RoleConnectionMetadataType::IntegerLessThanOrEqual => 1,
+            RoleConnectionMetadataType::IntegerGreaterThanOrEqual => 2,
+            RoleConnectionMetadataType::IntegerEqual => 3,
+            RoleConnectionMetadataType::IntegerNotEqual => 4,
+            RoleConnectionMetadataType::DatetimeLessThanOrEqual => 5,
+            RoleConnectionMetadataType::DatetimeGreaterThanOrEqual => 6,
+            RoleConnectionMetadataType::BooleanEqual => 7,
+            RoleConnectionMetadataType::BooleanNotEqual => 8,
+            RoleConnectionMetadataType::Unknown(unknown) => unknown,
+        }
+    }
+}
+
+/// User's connection with the application, whose metadata is compared with
+/// the values the application's linked roles require.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct RoleConnection {
+    /// Values of the user's metadata by the keys of the application's
+    /// [`RoleConnectionMetadata`] records, stringified.
+    #[serde(default)]
+    pub metadata: BTreeMap<String, String>,
+    /// Name of the platform the user is connected through, displayed on
+    /// their profile.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub platform_name: Option<String>,
+    /// Username of the user on the platform, displayed on their profile.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub platform_username: Option<String>,
+}
+
+/// Update the connection of the user authorizing an access token with the
+/// application, such as once they complete the verification flow or their
+/// metadata changes.
+///
+/// The access token must be authorized with the `role_connections.write`
+/// scope, as it is by the [`SCOPES`] of the verification flow.
+///
+/// # Errors
+///
+/// Returns an error of type [`Deserializing`] if the response body could
+/// not be deserialized as a connection.
+///
+/// Returns an error of type [`RateLimited`] if the request was rate limited.
+///
+/// Returns an error of type [`Requesting`] if the request could not be
+/// sent.
+///
+/// Returns an error of type [`Serializing`] if the connection could not be
+/// serialized.
+///
+/// Returns an error of type [`Status`] if Discord responded with an
+/// unsuccessful status code, such as when the access token isn't authorized
+/// with the `role_connections.write` scope.
+///
+/// [`Deserializing`]: crate::rest::RestErrorType::Deserializing
+/// [`RateLimited`]: crate::rest::RestErrorType::RateLimited
+/// [`Requesting`]: crate::rest::RestErrorType::Requesting
+/// [`Serializing`]: crate::rest::RestErrorType::Serializing
+/// [`Status`]: crate::rest::RestErrorType::Status
+pub async fn update_role_connection(
+    application_id: Id<ApplicationMarker>,
+    access_token: &str,
+    connection: &RoleConnection,
+) -> Result<RoleConnection, RestError> {
+    let path = format!("/users/@me/applications/{application_id}/role-connection");
+    let authorization = ["Bearer ", access_token].concat();
+    let headers = [("Authorization", authorization.as_str())];
+
+    rest::deserialize(
+        rest::send(
+            Method::Put,
+            &path,
+            Some(Body::json(connection)?),
+            &headers,
+            false,
+        )
+        .await?,
+    )
+}
+
+/// Respond to a request to the application's linked roles verification URL
+/// by redirecting the user to authorize the application with the [`SCOPES`]
+/// required to push their metadata.
+///
+/// The authorization flow is protected against cross-site request forgery
+/// with a state bound to a cookie by [`oauth2::create_cookie_state`], which
+/// is to be checked with [`oauth2::verify_cookie_state`] when Discord
+/// redirects the user back.
+///
+/// # Errors
+///
+/// Returns an error if random bytes for the state could not be generated, or
+/// if the client's authorization URL is invalid.
+///
+/// [`oauth2::create_cookie_state`]: crate::oauth2::create_cookie_state
+/// [`oauth2::verify_cookie_state`]: crate::oauth2::verify_cookie_state
+#[cfg(feature = "oauth2")]
+pub fn verification_redirect(client: &crate::oauth2::Client) -> worker::Result<Response> {
+    let crate::oauth2::CookieState { cookie, state } = crate::oauth2::create_cookie_state()?;
+    let url = Url::parse(&client.authorization_url(SCOPES, &state))?;
+    let mut response = Response::redirect(url)?;
+    response.headers_mut().set("Set-Cookie", &cookie)?;
+
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RoleConnection, RoleConnectionMetadata, RoleConnectionMetadataType};
+    use serde_json::json;
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    #[test]
+    fn metadata_type_round_trip() {
+        for value in 0..=9 {
+            let kind = RoleConnectionMetadataType::from(value);
+
+            assert_eq!(value, u8::from(kind));
+        }
+
+        assert_eq!(
+            RoleConnectionMetadataType::Unknown(9),
+            RoleConnectionMetadataType::from(9)
+        );
+    }
+
+    #[test]
+    fn metadata_serializes_type() {
+        let metadata = RoleConnectionMetadata {
+            description: "Days since connecting".to_owned(),
+            key: "connected_at".to_owned(),
+            kind: RoleConnectionMetadataType::DatetimeGreaterThanOrEqual,
+            name: "Connected".to_owned(),
+        };
+        let value = json!({
+            "description": "Days since connecting",
+            "key": "connected_at",
+            "name": "Connected",
+            "type": 6,
+        });
+
+        assert_eq!(value, serde_json::to_value(&metadata).unwrap());
+        assert_eq!(metadata, serde_json::from_value(value).unwrap());
+    }
+
+    #[test]
+    fn connection_serializes_metadata() {
+        let connection = RoleConnection {
+            metadata: [("level".to_owned(), "12".to_owned())].into(),
+            platform_name: Some("Example".to_owned()),
+            platform_username: None,
+        };
+
+        assert_eq!(
+            json!({
+                "metadata": { "level": "12" },
+                "platform_name": "Example",
+            }),
+            serde_json::to_value(&connection).unwrap()
+        );
+    }
+
+    #[test]
+    fn connection_defaults() {
+        assert_eq!(
+            RoleConnection::default(),
+            serde_json::from_value(json!({})).unwrap()
+        );
+    }
+}
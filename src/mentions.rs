@@ -0,0 +1,349 @@
+//! Neutralizing mentions in user input echoed in message content, such as a
+//! command option repeated in a response.
+//!
+//! Mentions are neutralized by inserting a zero width space after their `@`,
+//! so that they are displayed as written but don't mention anyone. This is in
+//! addition to a message's allowed mentions, which may intentionally allow
+//! some mentions in the rest of the message. Mentions inside code blocks are
+//! neutralized too, since the text may be echoed outside of one.
+//!
+//! ```ignore
+//! use twilight_cloudflare_workers::mentions;
+//!
+//! let content = format!("<@{}> said: {}", author_id, mentions::safe_content(&text));
+//! ```
+//!
+//! With the `twilight-model` feature, responses and followups that should
+//! mention specific users or roles can allow them with `AllowedMentionsExt`,
+//! such as on the data built with Twilight's `InteractionResponseDataBuilder`,
+//! or by building the allowed mentions with `AllowedMentionsBuilder`.
+
+#[cfg(feature = "twilight-model")]
+use twilight_model::{
+    channel::message::{AllowedMentions, MentionType},
+    http::interaction::InteractionResponseData,
+    id::{
+        marker::{RoleMarker, UserMarker},
+        Id,
+    },
+};
+
+/// Zero width space, inserted into mentions to neutralize them.
+pub const ZERO_WIDTH_SPACE: char = '\u{200B}';
+
+/// Neutralize the `@everyone` and `@here` mentions and role mentions in text,
+/// leaving mentions of users and channels.
+#[must_use = "neutralizing mentions is not useful on its own"]
+pub fn safe_content(text: &str) -> String {
+    neutralize(text, false)
+}
+
+/// Neutralize all mentions in text, including mentions of users.
+#[must_use = "neutralizing mentions is not useful on its own"]
+pub fn escape(text: &str) -> String {
+    neutralize(text, true)
+}
+
+/// Builder of the mentions allowed in a message, for responses that mention
+/// specific users or roles.
+///
+/// Messages whose allowed mentions are built without any mentions don't
+/// mention anyone.
+///
+/// ```ignore
+/// use twilight_cloudflare_workers::mentions::AllowedMentionsBuilder;
+///
+/// let data = InteractionResponseData {
+///     allowed_mentions: Some(AllowedMentionsBuilder::new().mention_users([target_id]).build()),
+///     content: Some(format!("<@{target_id}>, <@&{role_id}> has been given to you.")),
+///     ..InteractionResponseData::default()
+/// };
+/// ```
+#[cfg(feature = "twilight-model")]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[must_use = "builders have no effect if unused"]
+pub struct AllowedMentionsBuilder(AllowedMentions);
+
+#[cfg(feature = "twilight-model")]
+impl AllowedMentionsBuilder {
+    /// Create a new builder not allowing any mentions.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Build the allowed mentions.
+    #[must_use = "building allowed mentions has no effect if left unused"]
+    pub fn build(self) -> AllowedMentions {
+        self.0
+    }
+
+    /// Allow mentions of roles.
+    pub fn mention_roles(mut self, roles: impl IntoIterator<Item = Id<RoleMarker>>) -> Self {
+        allow_roles(&mut self.0, roles);
+
+        self
+    }
+
+    /// Allow mentions of users.
+    pub fn mention_users(mut self, users: impl IntoIterator<Item = Id<UserMarker>>) -> Self {
+        allow_users(&mut self.0, users);
+
+        self
+    }
+
+    /// Disallow all mentions, including those previously allowed.
+    pub fn suppress_mentions(mut self) -> Self {
+        self.0 = AllowedMentions::default();
+
+        self
+    }
+}
+
+/// Extension trait overriding the mentions allowed in a response or followup,
+/// so that specific messages can mention users or roles without constructing
+/// their allowed mentions by hand.
+///
+/// Data without allowed mentions starts from not allowing any mentions, rather
+/// than Discord's default of allowing all of them.
+///
+/// ```ignore
+/// use twilight_cloudflare_workers::mentions::AllowedMentionsExt;
+///
+/// let data = InteractionResponseDataBuilder::new()
+///     .content(format!("<@{target_id}>, <@&{role_id}> has been given to you."))
+///     .build()
+///     .mention_users([target_id]);
+/// ```
+#[cfg(feature = "twilight-model")]
+pub trait AllowedMentionsExt {
+    /// Allow mentions of roles, in addition to those already allowed.
+    #[must_use = "allowing mentions has no effect if left unused"]
+    fn mention_roles(self, roles: impl IntoIterator<Item = Id<RoleMarker>>) -> Self;
+
+    /// Allow mentions of users, in addition to those already allowed.
+    #[must_use = "allowing mentions has no effect if left unused"]
+    fn mention_users(self, users: impl IntoIterator<Item = Id<UserMarker>>) -> Self;
+
+    /// Disallow all mentions, including those previously allowed.
+    #[must_use = "suppressing mentions has no effect if left unused"]
+    fn suppress_mentions(self) -> Self;
+}
+
+#[cfg(feature = "twilight-model")]
+impl AllowedMentionsExt for InteractionResponseData {
+    fn mention_roles(mut self, roles: impl IntoIterator<Item = Id<RoleMarker>>) -> Self {
+        allow_roles(
+            self.allowed_mentions.get_or_insert_with(Default::default),
+            roles,
+        );
+
+        self
+    }
+
+    fn mention_users(mut self, users: impl IntoIterator<Item = Id<UserMarker>>) -> Self {
+        allow_users(
+            self.allowed_mentions.get_or_insert_with(Default::default),
+            users,
+        );
+
+        self
+    }
+
+    fn suppress_mentions(mut self) -> Self {
+        self.allowed_mentions = Some(AllowedMentions::default());
+
+        self
+    }
+}
+
+/// Allow mentions of specific roles.
+///
+/// Discord rejects allowed mentions that list roles while also parsing all
+/// role mentions, so parsing them is disabled.
+#[cfg(feature = "twilight-model")]
+fn allow_roles(mentions: &mut AllowedMentions, roles: impl IntoIterator<Item = Id<RoleMarker>>) {
+    mentions.parse.retain(|kind| *kind != MentionType::Roles);
+    mentions.roles.extend(roles);
+}
+
+/// Allow mentions of specific users.
+///
+/// Discord rejects allowed mentions that list users while also parsing all
+/// user mentions, so parsing them is disabled.
+#[cfg(feature = "twilight-model")]
+fn allow_users(mentions: &mut AllowedMentions, users: impl IntoIterator<Item = Id<UserMarker>>) {
+    mentions.parse.retain(|kind| *kind != MentionType::Users);
+    mentions.users.extend(users);
+}
+
+/// Insert a zero width space after the `@` of mentions, including mentions
+/// of users if enabled.
+fn neutralize(text: &str, users: bool) -> String {
+    let mut neutralized = String::with_capacity(text.len());
+
+    for (index, character) in text.char_indices() {
+        neutralized.push(character);
+
+        if character != '@' {
+            continue;
+        }
+
+        let before = &text[..index];
+        let after = &text[index + 1..];
+        let mention = if before.ends_with('<') {
+            after.starts_with('&')
+                || (users
+                    && after
+                        .trim_start_matches('!')
+                        .starts_with(|c: char| c.is_ascii_digit()))
+        } else {
+            after.starts_with("everyone") || after.starts_with("here")
+        };
+
+        if mention {
+            neutralized.push(ZERO_WIDTH_SPACE);
+        }
+    }
+
+    neutralized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{escape, safe_content, ZERO_WIDTH_SPACE};
+    #[cfg(feature = "twilight-model")]
+    use super::{AllowedMentionsBuilder, AllowedMentionsExt};
+    #[cfg(feature = "twilight-model")]
+    use twilight_model::{
+        channel::message::{AllowedMentions, MentionType},
+        http::interaction::InteractionResponseData,
+        id::Id,
+    };
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    /// Text with a zero width space inserted after each `@` of a mention,
+    /// written as `@|`.
+    fn neutralized(text: &str) -> String {
+        text.replace("@|", &format!("@{ZERO_WIDTH_SPACE}"))
+    }
+
+    #[test]
+    fn everyone_and_here() {
+        for (text, expected) in [
+            ("@everyone", "@|everyone"),
+            ("@here", "@|here"),
+            ("hi @everyone and @here!", "hi @|everyone and @|here!"),
+            ("@@everyone", "@@|everyone"),
+            ("@ everyone", "@ everyone"),
+            ("@Everyone", "@Everyone"),
+            ("@someone", "@someone"),
+        ] {
+            assert_eq!(safe_content(text), neutralized(expected), "{text:?}");
+            assert_eq!(escape(text), neutralized(expected), "{text:?}");
+        }
+    }
+
+    #[test]
+    fn users() {
+        for text in ["<@123>", "<@!123>", "hi <@!1> and <@2>"] {
+            assert_eq!(safe_content(text), text);
+        }
+
+        assert_eq!(escape("<@123>"), neutralized("<@|123>"));
+        assert_eq!(escape("<@!123>"), neutralized("<@|!123>"));
+        assert_eq!(
+            escape("hi <@!1> and <@2>"),
+            neutralized("hi <@|!1> and <@|2>")
+        );
+        // Only IDs are mentions.
+        assert_eq!(escape("<@name>"), "<@name>");
+        assert_eq!(escape("<@!>"), "<@!>");
+    }
+
+    #[test]
+    fn roles() {
+        assert_eq!(safe_content("<@&123>"), neutralized("<@|&123>"));
+        assert_eq!(escape("<@&123>"), neutralized("<@|&123>"));
+        assert_eq!(safe_content("@&123"), "@&123");
+    }
+
+    #[test]
+    fn channels_untouched() {
+        assert_eq!(escape("<#123>"), "<#123>");
+        assert_eq!(escape("user@example.com"), "user@example.com");
+    }
+
+    #[test]
+    fn code_blocks() {
+        assert_eq!(
+            safe_content("`@everyone` and ```\n<@&1> <@2>\n```"),
+            neutralized("`@|everyone` and ```\n<@|&1> <@2>\n```")
+        );
+        assert_eq!(
+            escape("```\n<@!2> @here\n```"),
+            neutralized("```\n<@|!2> @|here\n```")
+        );
+    }
+
+    #[cfg(feature = "twilight-model")]
+    #[test]
+    fn builder() {
+        assert_eq!(
+            AllowedMentionsBuilder::new().build(),
+            AllowedMentions::default()
+        );
+
+        let mentions = AllowedMentionsBuilder::new()
+            .mention_users([Id::new(1), Id::new(2)])
+            .mention_roles([Id::new(3)])
+            .mention_users([Id::new(4)])
+            .build();
+        assert_eq!(mentions.users, [Id::new(1), Id::new(2), Id::new(4)]);
+        assert_eq!(mentions.roles, [Id::new(3)]);
+        assert!(mentions.parse.is_empty());
+
+        assert_eq!(
+            AllowedMentionsBuilder::new()
+                .mention_users([Id::new(1)])
+                .suppress_mentions()
+                .build(),
+            AllowedMentions::default()
+        );
+    }
+
+    #[cfg(feature = "twilight-model")]
+    #[test]
+    fn response_data() {
+        let data = InteractionResponseData::default().mention_users([Id::new(1)]);
+        assert_eq!(
+            data.allowed_mentions,
+            Some(
+                AllowedMentionsBuilder::new()
+                    .mention_users([Id::new(1)])
+                    .build()
+            )
+        );
+
+        let data = InteractionResponseData {
+            allowed_mentions: Some(AllowedMentions {
+                parse: vec![
+                    MentionType::Everyone,
+                    MentionType::Roles,
+                    MentionType::Users,
+                ],
+                ..AllowedMentions::default()
+            }),
+            ..InteractionResponseData::default()
+        }
+        .mention_roles([Id::new(2)])
+        .mention_users([Id::new(3)]);
+        let mentions = data.allowed_mentions.unwrap();
+        assert_eq!(mentions.parse, [MentionType::Everyone]);
+        assert_eq!(mentions.roles, [Id::new(2)]);
+        assert_eq!(mentions.users, [Id::new(3)]);
+
+        let data = InteractionResponseData::default().suppress_mentions();
+        assert_eq!(data.allowed_mentions, Some(AllowedMentions::default()));
+    }
+}
@@ -0,0 +1,665 @@
+//! Minimal interaction models covering the commonly used fields.
+//!
+//! These are a small alternative to the models provided by `twilight-model`
+//! for builds where binary size matters more than model completeness, such as
+//! Workers with size limits. Disable the default `twilight-model` feature and
+//! enable the `minimal-model` feature to use these in its place.
+//!
+//! IDs are kept as their string representation, and less commonly used or
+//! deeply nested structures such as resolved data, embeds, and components are
+//! kept as [`Value`]s.
+
+#[cfg(feature = "worker")]
+use crate::{
+    json_bytes, json_response, try_json_response, verify_request, Endpoint, ResponseBytes,
+    ResponseError,
+};
+use crate::{parse, LazyInteraction, ProcessRequestError};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+#[cfg(feature = "worker")]
+use worker::{Request, Response};
+
+/// Interaction received from Discord.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+pub struct Interaction {
+    /// ID of the application the interaction is for.
+    pub application_id: String,
+    /// ID of the channel the interaction was invoked in.
+    #[serde(default)]
+    pub channel_id: Option<String>,
+    /// Data of the interaction, present for all types other than pings.
+    #[serde(default)]
+    pub data: Option<InteractionData>,
+    /// ID of the guild the interaction was invoked in.
+    #[serde(default)]
+    pub guild_id: Option<String>,
+    /// Preferred locale of the guild the interaction was invoked in.
+    #[serde(default)]
+    pub guild_locale: Option<String>,
+    /// ID of the interaction.
+    pub id: String,
+    /// Type of interaction.
+    #[serde(rename = "type")]
+    pub kind: InteractionType,
+    /// Selected locale of the user who invoked the interaction.
+    #[serde(default)]
+    pub locale: Option<String>,
+    /// Member who invoked the interaction, present when invoked in a guild.
+    #[serde(default)]
+    pub member: Option<Member>,
+    /// Token used to respond to the interaction.
+    pub token: String,
+    /// User who invoked the interaction, present when invoked in a DM.
+    #[serde(default)]
+    pub user: Option<User>,
+}
+
+impl Interaction {
+    /// User who invoked the interaction, whether in a guild or in a DM.
+    #[must_use = "retrieving the author is not useful on its own"]
+    pub fn author(&self) -> Option<&User> {
+        self.member
+            .as_ref()
+            .and_then(|member| member.user.as_ref())
+            .or(self.user.as_ref())
+    }
+}
+
+/// Data of an interaction.
+///
+/// Which fields are present depends on the type of the interaction.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+pub struct InteractionData {
+    /// Type of component, present for message component interactions.
+    #[serde(default)]
+    pub component_type: Option<u8>,
+    /// Submitted components, present for modal submit interactions.
+    #[serde(default)]
+    pub components: Vec<Value>,
+    /// Custom ID of the component or modal.
+    #[serde(default)]
+    pub custom_id: Option<String>,
+    /// ID of the command.
+    #[serde(default)]
+    pub id: Option<String>,
+    /// Name of the command.
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Options of the command.
+    #[serde(default)]
+    pub options: Vec<CommandDataOption>,
+    /// Resolved users, members, roles, channels, messages, and attachments.
+    #[serde(default)]
+    pub resolved: Option<Value>,
+    /// Selected values of a select menu component.
+    #[serde(default)]
+    pub values: Vec<String>,
+}
+
+/// Option of a command provided by the user.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+pub struct CommandDataOption {
+    /// Whether the option is focused, for autocomplete interactions.
+    #[serde(default)]
+    pub focused: bool,
+    /// Type of option.
+    #[serde(rename = "type")]
+    pub kind: u8,
+    /// Name of the option.
+    pub name: String,
+    /// Nested options of a subcommand or subcommand group.
+    #[serde(default)]
+    pub options: Vec<CommandDataOption>,
+    /// Value of the option.
+    #[serde(default)]
+    pub value: Option<Value>,
+}
+
+/// Guild member who invoked an interaction.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+pub struct Member {
+    /// Nickname of the member.
+    #[serde(default)]
+    pub nick: Option<String>,
+    /// Permissions of the member in the channel, as a stringified bitfield.
+    #[serde(default)]
+    pub permissions: Option<String>,
+    /// IDs of the roles of the member.
+    #[serde(default)]
+    pub roles: Vec<String>,
+    /// User of the member.
+    #[serde(default)]
+    pub user: Option<User>,
+}
+
+/// User who invoked an interaction.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+pub struct User {
+    /// Hash of the user's avatar.
+    #[serde(default)]
+    pub avatar: Option<String>,
+    /// Whether the user is a bot.
+    #[serde(default)]
+    pub bot: bool,
+    /// Discriminator of the user.
+    #[serde(default)]
+    pub discriminator: Option<String>,
+    /// Display name of the user.
+    #[serde(default)]
+    pub global_name: Option<String>,
+    /// ID of the user.
+    pub id: String,
+    /// Username of the user.
+    pub username: String,
+}
+
+/// Type of interaction.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(from = "u8", into = "u8")]
+pub enum InteractionType {
+    /// Ping sent by Discord to check the endpoint.
+    Ping,
+    /// Application command.
+    ApplicationCommand,
+    /// Message component.
+    MessageComponent,
+    /// Application command autocomplete.
+    ApplicationCommandAutocomplete,
+    /// Modal submit.
+    ModalSubmit,
+    /// Type not known to this crate.
+    Unknown(u8),
+}
+
+impl From<u8> for InteractionType {
+    fn from(value: u8) -> Self {
+        match value {
+            1 => Self::Ping,
+            2 => Self::ApplicationCommand,
+            3 => Self::MessageComponent,
+            4 => Self::ApplicationCommandAutocomplete,
+            5 => Self::ModalSubmit,
+            other => Self::Unknown(other),
+        }
+    }
+}
+
+impl From<InteractionType> for u8 {
+    fn from(value: InteractionType) -> Self {
+        match value {
+            InteractionType::Ping => 1,
+            InteractionType::ApplicationCommand => 2,
+            InteractionType::MessageComponent => 3,
+            InteractionType::ApplicationCommandAutocomplete => 4,
+            InteractionType::ModalSubmit => 5,
+            InteractionType::Unknown(other) => other,
+        }
+    }
+}
+
+/// Response to an interaction.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct InteractionResponse {
+    /// Type of response.
+    #[serde(rename = "type")]
+    pub kind: InteractionResponseType,
+    /// Data of the response, if the type of response has any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<InteractionResponseData>,
+}
+
+/// Data of a response to an interaction.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize)]
+pub struct InteractionResponseData {
+    /// Choices of an autocomplete response.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub choices: Option<Vec<Value>>,
+    /// Components of the message or modal.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub components: Option<Vec<Value>>,
+    /// Content of the message.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+    /// Custom ID of the modal.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub custom_id: Option<String>,
+    /// Embeds of the message.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub embeds: Option<Vec<Value>>,
+    /// Flags of the message, such as whether it is ephemeral.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub flags: Option<u64>,
+    /// Title of the modal.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Whether the message is text-to-speech.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tts: Option<bool>,
+}
+
+/// Type of response to an interaction.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(from = "u8", into = "u8")]
+pub enum InteractionResponseType {
+    /// Acknowledge a ping.
+    Pong,
+    /// Respond with a message.
+    ChannelMessageWithSource,
+    /// Acknowledge the interaction, showing a loading state.
+    DeferredChannelMessageWithSource,
+    /// Acknowledge a component interaction, editing the message later.
+    DeferredUpdateMessage,
+    /// Edit the message the component is attached to.
+    UpdateMessage,
+    /// Respond to an autocomplete interaction with choices.
+    ApplicationCommandAutocompleteResult,
+    /// Respond with a modal.
+    Modal,
+    /// Type not known to this crate.
+    Unknown(u8),
+}
+
+impl From<u8> for InteractionResponseType {
+    fn from(value: u8) -> Self {
+        match value {
+            1 => Self::Pong,
+            4 => Self::ChannelMessageWithSource,
+            5 => Self::DeferredChannelMessageWithSource,
+            6 => Self::DeferredUpdateMessage,
+            7 => Self::UpdateMessage,
+            8 => Self::ApplicationCommandAutocompleteResult,
+            9 => Self::Modal,
+            other => Self::Unknown(other),
+        }
+    }
+}
+
+impl From<InteractionResponseType> for u8 {
+    fn from(value: InteractionResponseType) -> Self {
+        match value {
+            InteractionResponseType::Pong => 1,
+            InteractionResponseType::ChannelMessageWithSource => 4,
+            InteractionResponseType::DeferredChannelMessageWithSource => 5,
+            InteractionResponseType::DeferredUpdateMessage => 6,
+            InteractionResponseType::UpdateMessage => 7,
+            InteractionResponseType::ApplicationCommandAutocompleteResult => 8,
+            InteractionResponseType::Modal => 9,
+            InteractionResponseType::Unknown(other) => other,
+        }
+    }
+}
+
+impl LazyInteraction {
+    /// Consume the lazy interaction, deserializing it as a minimal
+    /// interaction.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of type [`DeserializingInteraction`] if the body could
+    /// not be deserialized as an interaction.
+    ///
+    /// [`DeserializingInteraction`]: crate::ProcessRequestErrorType::DeserializingInteraction
+    pub fn into_minimal(self) -> Result<Interaction, ProcessRequestError> {
+        parse::deserialize(self.into_body())
+    }
+}
+
+/// Process a request, returning the request's interaction body as a minimal
+/// interaction if the request is valid.
+///
+/// ```ignore
+/// let interaction = twilight_cloudflare_workers::model::request(&mut req, public_key).await?;
+/// ```
+///
+/// # Errors
+///
+/// Refer to the documentation for [`request_lazy`] for the errors returned when
+/// the request is invalid.
+///
+/// Returns an error of type [`DeserializingInteraction`] if the request body
+/// could not be deserialized as a minimal interaction.
+///
+/// [`DeserializingInteraction`]: crate::ProcessRequestErrorType::DeserializingInteraction
+/// [`request_lazy`]: crate::request_lazy
+#[cfg(feature = "worker")]
+pub async fn request(
+    req: &mut Request,
+    public_key: &str,
+) -> Result<Interaction, ProcessRequestError> {
+    let body = verify_request(req, Endpoint::Interactions { path: "/" }, public_key, None).await?;
+
+    parse::deserialize(body)
+}
+
+/// Create a new worker response from a minimal interaction response.
+///
+/// Sets the `Content-Type` header to a value of `application/json`.
+///
+/// If the interaction response could not be serialized then a 500 response is
+/// created noting that the response could not be serialized.
+#[cfg(feature = "worker")]
+#[must_use = "created responses must be used to actually send the response"]
+pub fn response(response: &InteractionResponse) -> Response {
+    json_response(response)
+}
+
+/// Serialize a minimal interaction response into the body and content type
+/// of a response, such as for composing a worker response with other headers
+/// or forwarding it to another Worker.
+///
+/// # Errors
+///
+/// Returns an error of type [`Serializing`] if the interaction response could
+/// not be serialized.
+///
+/// [`Serializing`]: crate::ResponseErrorType::Serializing
+#[cfg(feature = "worker")]
+pub fn response_bytes(response: &InteractionResponse) -> Result<ResponseBytes, ResponseError> {
+    json_bytes(response)
+}
+
+/// Create a new worker response from a minimal interaction response,
+/// returning an error instead of a 500 response if it could not be created.
+///
+/// Sets the `Content-Type` header to a value of `application/json`.
+///
+/// # Errors
+///
+/// Returns an error of type [`Creating`] if the worker response could not be
+/// created or its header could not be set.
+///
+/// Returns an error of type [`Serializing`] if the interaction response could
+/// not be serialized.
+///
+/// [`Creating`]: crate::ResponseErrorType::Creating
+/// [`Serializing`]: crate::ResponseErrorType::Serializing
+#[cfg(feature = "worker")]
+pub fn try_response(response: &InteractionResponse) -> Result<Response, ResponseError> {
+    try_json_response(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        Interaction, InteractionData, InteractionResponse, InteractionResponseData,
+        InteractionResponseType, InteractionType,
+    };
+    use serde_json::{json, Value};
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    fn user() -> Value {
+        json!({
+            "avatar": "a_d5efa99b3eeaa7dd43acca82f5692432",
+            "discriminator": "0",
+            "global_name": "Mason",
+            "id": "53908232506183680",
+            "public_flags": 131_141,
+            "username": "mason",
+        })
+    }
+
+    #[test]
+    fn ping() {
+        let interaction = serde_json::from_value::<Interaction>(json!({
+            "application_id": "846414918223733855",
+            "id": "1047110570120855592",
+            "token": "aW50ZXJhY3Rpb246MTA0NzExMDU3MDEyMDg1NTU5Mg",
+            "type": 1,
+            "user": user(),
+            "version": 1,
+        }))
+        .unwrap();
+
+        assert_eq!(InteractionType::Ping, interaction.kind);
+        assert_eq!("846414918223733855", interaction.application_id);
+        assert_eq!(None, interaction.data);
+        assert_eq!(None, interaction.member);
+        assert_eq!(
+            Some("53908232506183680"),
+            interaction.author().map(|user| user.id.as_str())
+        );
+    }
+
+    #[test]
+    fn guild_command() {
+        let interaction = serde_json::from_value::<Interaction>(json!({
+            "app_permissions": "442368",
+            "application_id": "846414918223733855",
+            "channel": { "id": "645027906669510667", "type": 0 },
+            "channel_id": "645027906669510667",
+            "data": {
+                "guild_id": "613425648685547541",
+                "id": "771825006014889984",
+                "name": "blep",
+                "options": [
+                    { "name": "animal", "type": 3, "value": "animal_dog" },
+                    { "name": "only_smol", "type": 5, "value": false },
+                ],
+                "type": 1,
+            },
+            "entitlements": [],
+            "guild_id": "613425648685547541",
+            "guild_locale": "en-US",
+            "id": "786008729715212338",
+            "locale": "en-GB",
+            "member": {
+                "avatar": null,
+                "deaf": false,
+                "joined_at": "2019-11-14T21:41:31.456000+00:00",
+                "mute": false,
+                "nick": "Mas",
+                "pending": false,
+                "permissions": "2147483647",
+                "premium_since": null,
+                "roles": ["645027906669510668"],
+                "user": user(),
+            },
+            "token": "A_UNIQUE_TOKEN",
+            "type": 2,
+            "version": 1,
+        }))
+        .unwrap();
+
+        assert_eq!(InteractionType::ApplicationCommand, interaction.kind);
+        assert_eq!(
+            Some("645027906669510667"),
+            interaction.channel_id.as_deref()
+        );
+        assert_eq!(Some("613425648685547541"), interaction.guild_id.as_deref());
+        assert_eq!(Some("en-US"), interaction.guild_locale.as_deref());
+        assert_eq!(Some("en-GB"), interaction.locale.as_deref());
+        assert_eq!(None, interaction.user);
+
+        let member = interaction.member.as_ref().unwrap();
+        assert_eq!(Some("Mas"), member.nick.as_deref());
+        assert_eq!(Some("2147483647"), member.permissions.as_deref());
+        assert_eq!(vec!["645027906669510668"], member.roles);
+
+        let author = interaction.author().unwrap();
+        assert_eq!("mason", author.username);
+        assert_eq!(Some("Mason"), author.global_name.as_deref());
+        assert!(!author.bot);
+
+        let data = interaction.data.unwrap();
+        assert_eq!(Some("771825006014889984"), data.id.as_deref());
+        assert_eq!(Some("blep"), data.name.as_deref());
+        assert_eq!(2, data.options.len());
+        assert_eq!("animal", data.options[0].name);
+        assert_eq!(3, data.options[0].kind);
+        assert_eq!(Some(json!("animal_dog")), data.options[0].value);
+        assert!(!data.options[0].focused);
+        assert_eq!(Some(json!(false)), data.options[1].value);
+    }
+
+    #[test]
+    fn autocomplete_subcommand() {
+        let interaction = serde_json::from_value::<Interaction>(json!({
+            "application_id": "846414918223733855",
+            "data": {
+                "id": "816437322781949972",
+                "name": "tag",
+                "options": [{
+                    "name": "get",
+                    "options": [{ "focused": true, "name": "name", "type": 3, "value": "ru" }],
+                    "type": 1,
+                }],
+                "type": 1,
+            },
+            "id": "816500706226913321",
+            "token": "A_UNIQUE_TOKEN",
+            "type": 4,
+            "user": user(),
+            "version": 1,
+        }))
+        .unwrap();
+
+        assert_eq!(
+            InteractionType::ApplicationCommandAutocomplete,
+            interaction.kind
+        );
+
+        let subcommand = &interaction.data.unwrap().options[0];
+        assert_eq!("get", subcommand.name);
+        assert_eq!(None, subcommand.value);
+        assert!(subcommand.options[0].focused);
+        assert_eq!(Some(json!("ru")), subcommand.options[0].value);
+    }
+
+    #[test]
+    fn select_menu() {
+        let interaction = serde_json::from_value::<Interaction>(json!({
+            "application_id": "846414918223733855",
+            "channel_id": "345626669114982999",
+            "data": {
+                "component_type": 3,
+                "custom_id": "class_select_1",
+                "values": ["mage", "rogue"],
+            },
+            "guild_id": "613425648685547541",
+            "id": "846462639134605312",
+            "message": { "id": "846462638584467467" },
+            "member": { "roles": [], "user": user() },
+            "token": "A_UNIQUE_TOKEN",
+            "type": 3,
+            "version": 1,
+        }))
+        .unwrap();
+
+        assert_eq!(InteractionType::MessageComponent, interaction.kind);
+        assert_eq!(
+            InteractionData {
+                component_type: Some(3),
+                components: Vec::new(),
+                custom_id: Some("class_select_1".to_owned()),
+                id: None,
+                name: None,
+                options: Vec::new(),
+                resolved: None,
+                values: vec!["mage".to_owned(), "rogue".to_owned()],
+            },
+            interaction.data.unwrap()
+        );
+    }
+
+    #[test]
+    fn modal_submit() {
+        let interaction = serde_json::from_value::<Interaction>(json!({
+            "application_id": "846414918223733855",
+            "data": {
+                "components": [{
+                    "components": [{ "custom_id": "name", "type": 4, "value": "Mason" }],
+                    "type": 1,
+                }],
+                "custom_id": "profile",
+            },
+            "id": "846462639134605313",
+            "token": "A_UNIQUE_TOKEN",
+            "type": 5,
+            "user": user(),
+            "version": 1,
+        }))
+        .unwrap();
+        let data = interaction.data.unwrap();
+
+        assert_eq!(InteractionType::ModalSubmit, interaction.kind);
+        assert_eq!(Some("profile"), data.custom_id.as_deref());
+        assert_eq!("Mason", data.components[0]["components"][0]["value"]);
+    }
+
+    #[test]
+    fn missing_required_field() {
+        assert!(serde_json::from_value::<Interaction>(json!({
+            "application_id": "846414918223733855",
+            "id": "1047110570120855592",
+            "type": 1,
+        }))
+        .is_err());
+    }
+
+    #[test]
+    fn interaction_type_round_trip() {
+        for (kind, value) in [
+            (InteractionType::Ping, 1),
+            (InteractionType::ApplicationCommand, 2),
+            (InteractionType::MessageComponent, 3),
+            (InteractionType::ApplicationCommandAutocomplete, 4),
+            (InteractionType::ModalSubmit, 5),
+            (InteractionType::Unknown(0), 0),
+            (InteractionType::Unknown(6), 6),
+            (InteractionType::Unknown(u8::MAX), u8::MAX),
+        ] {
+            assert_eq!(kind, InteractionType::from(value));
+            assert_eq!(value, u8::from(kind));
+            assert_eq!(json!(value), serde_json::to_value(kind).unwrap());
+            assert_eq!(kind, serde_json::from_value(json!(value)).unwrap());
+        }
+    }
+
+    #[test]
+    fn interaction_response_type_round_trip() {
+        for (kind, value) in [
+            (InteractionResponseType::Pong, 1),
+            (InteractionResponseType::ChannelMessageWithSource, 4),
+            (InteractionResponseType::DeferredChannelMessageWithSource, 5),
+            (InteractionResponseType::DeferredUpdateMessage, 6),
+            (InteractionResponseType::UpdateMessage, 7),
+            (
+                InteractionResponseType::ApplicationCommandAutocompleteResult,
+                8,
+            ),
+            (InteractionResponseType::Modal, 9),
+            (InteractionResponseType::Unknown(2), 2),
+            (InteractionResponseType::Unknown(3), 3),
+            (InteractionResponseType::Unknown(10), 10),
+        ] {
+            assert_eq!(kind, InteractionResponseType::from(value));
+            assert_eq!(value, u8::from(kind));
+            assert_eq!(json!(value), serde_json::to_value(kind).unwrap());
+            assert_eq!(kind, serde_json::from_value(json!(value)).unwrap());
+        }
+    }
+
+    #[test]
+    fn response_serializes() {
+        let pong = InteractionResponse {
+            kind: InteractionResponseType::Pong,
+            data: None,
+        };
+        let message = InteractionResponse {
+            kind: InteractionResponseType::ChannelMessageWithSource,
+            data: Some(InteractionResponseData {
+                content: Some("Hello".to_owned()),
+                flags: Some(1 << 6),
+                ..InteractionResponseData::default()
+            }),
+        };
+
+        assert_eq!(json!({ "type": 1 }), serde_json::to_value(pong).unwrap());
+        assert_eq!(
+            json!({ "data": { "content": "Hello", "flags": 64 }, "type": 4 }),
+            serde_json::to_value(message).unwrap()
+        );
+    }
+}
@@ -0,0 +1,572 @@
+//! Discord `OAuth2` authorization code flow over Worker fetch.
+//!
+//! Build the URL to send users to with [`Client::authorization_url`], protected
+//! against cross-site request forgery with a state created by [`create_state`],
+//! and exchange the code Discord redirects back with for a token with
+//! [`Client::exchange_code`] after checking the state with [`consume_state`].
+//!
+//! States stored in KV may be reused within KV's consistency window. States
+//! bound to a cookie with [`create_cookie_state`] and [`verify_cookie_state`]
+//! are instead tied to the browser that started the flow, without storage.
+//!
+//! ```ignore
+//! use twilight_cloudflare_workers::oauth2::{self, Client};
+//!
+//! let client = Client::new(client_id, client_secret, "https://example.com/callback");
+//! let kv = env.kv("OAUTH2_STATE")?;
+//!
+//! // When the user starts the flow.
+//! let state = oauth2::create_state(&kv).await?;
+//! let url = client.authorization_url(&["identify", "role_connections.write"], &state);
+//!
+//! // When Discord redirects the user back with a code and the state.
+//! if !oauth2::consume_state(&kv, &state).await? {
+//!     return Response::error("invalid state", 400);
+//! }
+//!
+//! let token = client.exchange_code(&code).await?;
+//! ```
+
+use core::fmt::{Debug, Display, Formatter, Result as FmtResult};
+use serde::Deserialize;
+use std::{error::Error, str};
+use url::form_urlencoded;
+use worker::{kv::KvStore, wasm_bindgen::JsValue, Fetch, Headers, Method, Request, RequestInit};
+
+/// URL of Discord's authorization page.
+const AUTHORIZATION_URL: &str = "https://discord.com/oauth2/authorize";
+
+/// Name of the cookie states are bound to.
+const STATE_COOKIE: &str = "oauth2_state";
+
+/// Prefix of the keys states are stored under.
+const STATE_KEY_PREFIX: &str = "oauth2-state:";
+
+/// Number of seconds a state is valid for before it expires.
+const STATE_TTL: u64 = 10 * 60;
+
+/// URL of Discord's token endpoint.
+const TOKEN_URL: &str = "https://discord.com/api/v10/oauth2/token";
+
+/// Client for an application's `OAuth2` authorization code flow.
+#[derive(Clone, Eq, PartialEq)]
+pub struct Client {
+    client_id: String,
+    client_secret: String,
+    redirect_uri: String,
+}
+
+impl Client {
+    /// Create a new client with the application's credentials and the
+    /// redirect URI registered with Discord.
+    pub fn new(
+        client_id: impl Into<String>,
+        client_secret: impl Into<String>,
+        redirect_uri: impl Into<String>,
+    ) -> Self {
+        Self {
+            client_id: client_id.into(),
+            client_secret: client_secret.into(),
+            redirect_uri: redirect_uri.into(),
+        }
+    }
+
+    /// URL to send users to in order to authorize the application with the
+    /// provided scopes.
+    #[must_use = "building the authorization URL is not useful on its own"]
+    pub fn authorization_url(&self, scopes: &[&str], state: &str) -> String {
+        let query = form_urlencoded::Serializer::new(String::new())
+            .append_pair("client_id", &self.client_id)
+            .append_pair("redirect_uri", &self.redirect_uri)
+            .append_pair("response_type", "code")
+            .append_pair("scope", &scopes.join(" "))
+            .append_pair("state", state)
+            .finish();
+
+        format!("{AUTHORIZATION_URL}?{query}")
+    }
+
+    /// Exchange the code Discord redirected the user back with for a token.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of type [`Deserializing`] if the response body could
+    /// not be deserialized as a token.
+    ///
+    /// Returns an error of type [`Requesting`] if the request could not be
+    /// sent.
+    ///
+    /// Returns an error of type [`Status`] if Discord responded with an
+    /// unsuccessful status code, such as when the code is invalid.
+    ///
+    /// [`Deserializing`]: OAuth2ErrorType::Deserializing
+    /// [`Requesting`]: OAuth2ErrorType::Requesting
+    /// [`Status`]: OAuth2ErrorType::Status
+    pub async fn exchange_code(&self, code: &str) -> Result<Token, OAuth2Error> {
+        self.token(&[
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", &self.redirect_uri),
+        ])
+        .await
+    }
+
+    /// Exchange a refresh token for a new token.
+    ///
+    /// # Errors
+    ///
+    /// Refer to the documentation for [`exchange_code`].
+    ///
+    /// [`exchange_code`]: Self::exchange_code
+    pub async fn refresh_token(&self, refresh_token: &str) -> Result<Token, OAuth2Error> {
+        self.token(&[
+            ("grant_type", "refresh_token"),
+            ("refresh_token", refresh_token),
+        ])
+        .await
+    }
+
+    /// Request a token from Discord's token endpoint.
+    async fn token(&self, params: &[(&str, &str)]) -> Result<Token, OAuth2Error> {
+        let body = form_urlencoded::Serializer::new(String::new())
+            .extend_pairs(params)
+            .append_pair("client_id", &self.client_id)
+            .append_pair("client_secret", &self.client_secret)
+            .finish();
+
+        let mut headers = Headers::new();
+        headers
+            .set("Content-Type", "application/x-www-form-urlencoded")
+            .map_err(OAuth2Error::requesting)?;
+
+        let mut init = RequestInit::new();
+        init.with_method(Method::Post)
+            .with_headers(headers)
+            .with_body(Some(JsValue::from_str(&body)));
+
+        let request = Request::new_with_init(TOKEN_URL, &init).map_err(OAuth2Error::requesting)?;
+        let mut response = Fetch::Request(request)
+            .send()
+            .await
+            .map_err(OAuth2Error::requesting)?;
+        let status = response.status_code();
+        let body = response.bytes().await.map_err(OAuth2Error::requesting)?;
+
+        if !(200..300).contains(&status) {
+            return Err(OAuth2Error {
+                kind: OAuth2ErrorType::Status { body, status },
+                source: None,
+            });
+        }
+
+        serde_json::from_slice(&body).map_err(|source| OAuth2Error {
+            kind: OAuth2ErrorType::Deserializing { body },
+            source: Some(Box::new(source)),
+        })
+    }
+}
+
+impl Debug for Client {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        // The client secret is omitted so that it isn't leaked in logs.
+        f.debug_struct("Client")
+            .field("client_id", &self.client_id)
+            .field("redirect_uri", &self.redirect_uri)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Token granted by Discord.
+#[derive(Clone, Deserialize, Eq, PartialEq)]
+pub struct Token {
+    /// Access token to make requests on behalf of the user with.
+    pub access_token: String,
+    /// Number of seconds until the access token expires.
+    pub expires_in: u64,
+    /// Token to exchange for a new token once the access token expires.
+    pub refresh_token: String,
+    /// Space separated scopes the token is authorized for.
+    pub scope: String,
+    /// Type of the access token, which is always `Bearer`.
+    pub token_type: String,
+}
+
+impl Debug for Token {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        // The access and refresh tokens are omitted so that they aren't leaked
+        // in logs.
+        f.debug_struct("Token")
+            .field("expires_in", &self.expires_in)
+            .field("scope", &self.scope)
+            .field("token_type", &self.token_type)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Create a new random state to protect the authorization flow against
+/// cross-site request forgery, storing it in KV.
+///
+/// The state expires after 10 minutes.
+///
+/// # Errors
+///
+/// Returns an error of type [`Random`] if random bytes could not be generated.
+///
+/// Returns an error of type [`Store`] if the state could not be stored.
+///
+/// [`Random`]: OAuth2ErrorType::Random
+/// [`Store`]: OAuth2ErrorType::Store
+pub async fn create_state(kv: &KvStore) -> Result<String, OAuth2Error> {
+    let state = random_state()?;
+
+    kv.put(&[STATE_KEY_PREFIX, &state].concat(), "")
+        .map_err(OAuth2Error::store)?
+        .expiration_ttl(STATE_TTL)
+        .execute()
+        .await
+        .map_err(OAuth2Error::store)?;
+
+    Ok(state)
+}
+
+/// Check whether a state was created by [`create_state`] and hasn't expired,
+/// removing it so that it can't be used again.
+///
+/// Single use is not guaranteed: KV is eventually consistent and the state is
+/// retrieved and removed in separate operations, so a state may be accepted
+/// more than once within KV's consistency window of up to a minute, such as
+/// when a callback is replayed from another location. Use
+/// [`create_cookie_state`] to bind states to the user's browser instead.
+///
+/// # Errors
+///
+/// Returns an error of type [`Store`] if the state could not be retrieved or
+/// removed.
+///
+/// [`Store`]: OAuth2ErrorType::Store
+pub async fn consume_state(kv: &KvStore, state: &str) -> Result<bool, OAuth2Error> {
+    let key = [STATE_KEY_PREFIX, state].concat();
+
+    if kv
+        .get(&key)
+        .text()
+        .await
+        .map_err(OAuth2Error::store)?
+        .is_none()
+    {
+        return Ok(false);
+    }
+
+    kv.delete(&key).await.map_err(OAuth2Error::store)?;
+
+    Ok(true)
+}
+
+/// State bound to a cookie, created by [`create_cookie_state`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CookieState {
+    /// Value of the `Set-Cookie` header to respond to the user with when
+    /// redirecting them to the authorization URL.
+    pub cookie: String,
+    /// State to build the authorization URL with.
+    pub state: String,
+}
+
+/// Create a new random state to protect the authorization flow against
+/// cross-site request forgery, bound to a cookie rather than stored.
+///
+/// The cookie is `HttpOnly`, `Secure` and `SameSite=Lax`, so that it is sent
+/// along with Discord's redirect back to the Worker, and expires after 10
+/// minutes. Check the state with [`verify_cookie_state`].
+///
+/// ```ignore
+/// let CookieState { cookie, state } = oauth2::create_cookie_state()?;
+/// let url = client.authorization_url(&["identify"], &state);
+///
+/// let mut response = Response::redirect(Url::parse(&url)?)?;
+/// response.headers_mut().set("Set-Cookie", &cookie)?;
+/// ```
+///
+/// # Errors
+///
+/// Returns an error of type [`Random`] if random bytes could not be generated.
+///
+/// [`Random`]: OAuth2ErrorType::Random
+pub fn create_cookie_state() -> Result<CookieState, OAuth2Error> {
+    let state = random_state()?;
+
+    Ok(CookieState {
+        cookie: state_cookie(&state, STATE_TTL),
+        state,
+    })
+}
+
+/// Value of the `Set-Cookie` header removing the cookie of a state created by
+/// [`create_cookie_state`], to respond with once the state is verified so
+/// that it isn't used again by the browser.
+#[must_use = "creating the cookie is not useful on its own"]
+pub fn clear_cookie_state() -> String {
+    state_cookie("", 0)
+}
+
+/// Check whether a state matches the state bound to the cookie created by
+/// [`create_cookie_state`], from the value of the request's `Cookie` header.
+///
+/// The states are compared in constant time.
+#[must_use = "verifying the state is not useful on its own"]
+pub fn verify_cookie_state(cookie_header: Option<&str>, state: &str) -> bool {
+    let Some(cookie) = cookie_header.and_then(|header| {
+        header.split(';').find_map(|pair| {
+            pair.trim()
+                .strip_prefix(STATE_COOKIE)
+                .and_then(|rest| rest.strip_prefix('='))
+        })
+    }) else {
+        return false;
+    };
+
+    !state.is_empty() && constant_time_eq(cookie.as_bytes(), state.as_bytes())
+}
+
+/// Whether two byte strings are equal, compared in constant time for strings
+/// of the same length.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len()
+        && a.iter()
+            .zip(b)
+            .fold(0, |difference, (a, b)| difference | (a ^ b))
+            == 0
+}
+
+/// Value of the `Set-Cookie` header setting the cookie states are bound to.
+fn state_cookie(value: &str, max_age: u64) -> String {
+    format!("{STATE_COOKIE}={value}; HttpOnly; Max-Age={max_age}; Path=/; SameSite=Lax; Secure")
+}
+
+/// Generate a new random hex encoded state.
+fn random_state() -> Result<String, OAuth2Error> {
+    let mut bytes = [0; 16];
+    getrandom::getrandom(&mut bytes).map_err(|source| OAuth2Error {
+        kind: OAuth2ErrorType::Random,
+        source: Some(Box::new(source)),
+    })?;
+
+    Ok(hex::encode(bytes))
+}
+
+/// `OAuth2` request or state could not be completed.
+#[derive(Debug)]
+pub struct OAuth2Error {
+    kind: OAuth2ErrorType,
+    source: Option<Box<dyn Error>>,
+}
+
+impl OAuth2Error {
+    /// Immutable reference to the type of error that occurred.
+    #[must_use = "retrieving the type has no effect if left unused"]
+    pub const fn kind(&self) -> &OAuth2ErrorType {
+        &self.kind
+    }
+
+    /// Consume the error, returning the source error if there is any.
+    #[must_use = "consuming the error and retrieving the source has no effect if left unused"]
+    pub fn into_source(self) -> Option<Box<dyn Error>> {
+        self.source
+    }
+
+    /// Consume the error, returning the owned error type and the source error.
+    #[must_use = "consuming the error into its parts has no effect if left unused"]
+    pub fn into_parts(self) -> (OAuth2ErrorType, Option<Box<dyn Error>>) {
+        (self.kind, self.source)
+    }
+
+    /// Create an error of type [`OAuth2ErrorType::Requesting`].
+    fn requesting(source: worker::Error) -> Self {
+        Self {
+            kind: OAuth2ErrorType::Requesting,
+            source: Some(Box::new(source)),
+        }
+    }
+
+    /// Create an error of type [`OAuth2ErrorType::Store`].
+    fn store(source: worker::kv::KvError) -> Self {
+        Self {
+            kind: OAuth2ErrorType::Store,
+            source: Some(Box::new(worker::Error::from(source))),
+        }
+    }
+}
+
+impl Display for OAuth2Error {
+    #[cfg(feature = "minimal-errors")]
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.write_str("error ")?;
+
+        Display::fmt(&self.kind.code(), f)
+    }
+
+    #[cfg(not(feature = "minimal-errors"))]
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match &self.kind {
+            OAuth2ErrorType::Deserializing { body } => {
+                f.write_str("failed to deserialize response body as token: ")?;
+
+                match str::from_utf8(body) {
+                    Ok(text) => f.write_str(text),
+                    Err(_) => write!(f, "{body:?}"),
+                }
+            }
+            OAuth2ErrorType::Random => f.write_str("failed to generate random state"),
+            OAuth2ErrorType::Requesting => f.write_str("failed to request token"),
+            OAuth2ErrorType::Status { status, .. } => {
+                f.write_str("token request responded with status code ")?;
+
+                Display::fmt(status, f)
+            }
+            OAuth2ErrorType::Store => f.write_str("failed to access state in KV"),
+        }
+    }
+}
+
+impl Error for OAuth2Error {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.source
+            .as_ref()
+            .map(|source| &**source as &(dyn Error + 'static))
+    }
+}
+
+/// Convert the error into a Worker error, so that `?` can be used in handlers
+/// returning a [`worker::Result`].
+impl From<OAuth2Error> for worker::Error {
+    fn from(error: OAuth2Error) -> Self {
+        Self::RustError(error.to_string())
+    }
+}
+
+/// Type of [`OAuth2Error`] that occurred.
+#[derive(Debug)]
+pub enum OAuth2ErrorType {
+    /// Failed to deserialize the response body as a token.
+    Deserializing {
+        /// Body of the response.
+        body: Vec<u8>,
+    },
+    /// Failed to generate random bytes for a state.
+    Random,
+    /// Failed to send a request or receive its response.
+    Requesting,
+    /// Discord responded with an unsuccessful status code.
+    Status {
+        /// Body of the response, containing details about the error.
+        body: Vec<u8>,
+        /// Status code of the response.
+        status: u16,
+    },
+    /// Failed to store, retrieve, or remove a state in KV.
+    Store,
+}
+
+impl OAuth2ErrorType {
+    /// Numeric code of the type of error, which errors are displayed as with
+    /// the `minimal-errors` feature.
+    ///
+    /// Refer to [`ProcessRequestErrorType::code`] for more information.
+    ///
+    /// [`ProcessRequestErrorType::code`]: crate::ProcessRequestErrorType::code
+    #[must_use = "retrieving the code is not useful on its own"]
+    pub const fn code(&self) -> u16 {
+        match self {
+            Self::Deserializing { .. } => 24,
+            Self::Random => 25,
+            Self::Requesting => 26,
+            Self::Status { .. } => 27,
+            Self::Store => 28,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        clear_cookie_state, constant_time_eq, create_cookie_state, verify_cookie_state, Client,
+        CookieState,
+    };
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    #[test]
+    fn authorization_url() {
+        let client = Client::new("1", "secret", "https://example.com/callback?a=b");
+
+        assert_eq!(
+            "https://discord.com/oauth2/authorize?client_id=1\
+             &redirect_uri=https%3A%2F%2Fexample.com%2Fcallback%3Fa%3Db\
+             &response_type=code&scope=identify+role_connections.write&state=a%26b",
+            client.authorization_url(&["identify", "role_connections.write"], "a&b")
+        );
+    }
+
+    #[test]
+    fn cookie_state() {
+        let CookieState { cookie, state } = create_cookie_state().unwrap();
+
+        assert_eq!(32, state.len());
+        assert!(state.bytes().all(|byte| byte.is_ascii_hexdigit()));
+        assert_eq!(
+            format!("oauth2_state={state}; HttpOnly; Max-Age=600; Path=/; SameSite=Lax; Secure"),
+            cookie
+        );
+        assert_ne!(state, create_cookie_state().unwrap().state);
+
+        let header = cookie.split(';').next().unwrap();
+        assert!(verify_cookie_state(Some(header), &state));
+    }
+
+    #[test]
+    fn clear_cookie() {
+        assert_eq!(
+            "oauth2_state=; HttpOnly; Max-Age=0; Path=/; SameSite=Lax; Secure",
+            clear_cookie_state()
+        );
+    }
+
+    #[test]
+    fn verify_cookie() {
+        assert!(verify_cookie_state(Some("oauth2_state=abc"), "abc"));
+        assert!(verify_cookie_state(
+            Some("theme=dark; oauth2_state=abc; lang=en"),
+            "abc"
+        ));
+        assert!(!verify_cookie_state(Some("oauth2_state=abc"), "abd"));
+        assert!(!verify_cookie_state(Some("oauth2_state=abc"), "abcd"));
+        assert!(!verify_cookie_state(Some("theme=dark"), "abc"));
+        assert!(!verify_cookie_state(None, "abc"));
+    }
+
+    #[test]
+    fn verify_cookie_empty() {
+        assert!(!verify_cookie_state(Some("oauth2_state="), ""));
+        assert!(!verify_cookie_state(Some("oauth2_state=abc"), ""));
+    }
+
+    #[test]
+    fn verify_cookie_prefixed_name() {
+        assert!(!verify_cookie_state(Some("oauth2_state2=abc"), "abc"));
+        assert!(!verify_cookie_state(Some("oauth2_statex=abc"), "abc"));
+        assert!(verify_cookie_state(
+            Some("oauth2_state2=other; oauth2_state=abc"),
+            "abc"
+        ));
+    }
+
+    #[test]
+    fn constant_time_compare() {
+        assert!(constant_time_eq(b"", b""));
+        assert!(constant_time_eq(b"abc", b"abc"));
+        assert!(!constant_time_eq(b"abc", b"abd"));
+        assert!(!constant_time_eq(b"abc", b"bbc"));
+        assert!(!constant_time_eq(b"abc", b"ab"));
+        assert!(!constant_time_eq(b"ab", b"abc"));
+    }
+}
@@ -0,0 +1,310 @@
+//! Partial deserialization of verified interactions.
+
+#[cfg(all(feature = "minimal-model", not(feature = "twilight-model")))]
+use crate::model::InteractionType;
+use crate::{ProcessRequestError, ProcessRequestErrorType};
+#[cfg(any(
+    feature = "js-json",
+    feature = "minimal-model",
+    feature = "twilight-model"
+))]
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+#[cfg(feature = "twilight-model")]
+use serde_json::value::RawValue;
+use std::borrow::Cow;
+#[cfg(feature = "twilight-model")]
+use twilight_model::{
+    application::interaction::{Interaction, InteractionType},
+    channel::{Channel, Message},
+    guild::{PartialMember, Permissions},
+    id::{
+        marker::{ApplicationMarker, GuildMarker, InteractionMarker},
+        Id,
+    },
+    user::User,
+};
+
+/// Verified interaction whose body has only been partially deserialized.
+///
+/// Only the type of the interaction and the name of the command or custom ID
+/// of the component or modal are deserialized, which is enough to route the
+/// interaction to a handler. The full interaction can then be deserialized if
+/// the handler needs it via `into_interaction`, or `into_minimal` when using
+/// the `minimal-model` feature.
+///
+/// Routing on the type and name with a `match` compiles to a static dispatch
+/// table, without needing to build a map of handlers when an isolate starts:
+///
+/// ```ignore
+/// use twilight_model::application::interaction::InteractionType;
+///
+/// let interaction = twilight_cloudflare_workers::request_lazy(&mut request, key).await?;
+///
+/// match (interaction.kind(), interaction.name()) {
+///     (InteractionType::ApplicationCommand, Some("ban")) => ban(interaction).await,
+///     (InteractionType::MessageComponent, Some("confirm")) => confirm(interaction).await,
+///     _ => unknown(interaction),
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct LazyInteraction {
+    body: Vec<u8>,
+    #[cfg(any(feature = "minimal-model", feature = "twilight-model"))]
+    kind: InteractionType,
+    name: Option<String>,
+    timing: Option<RequestTiming>,
+}
+
+/// Times at which a request was signed by Discord and received by the crate.
+///
+/// Recorded when a request is verified by functions such as `request_lazy`,
+/// for reporting latency such as in a ping command.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct RequestTiming {
+    /// Time the crate started verifying the request, in milliseconds since
+    /// the Unix epoch.
+    pub received_at: u64,
+    /// Time Discord signed the request according to its timestamp header, in
+    /// milliseconds since the Unix epoch.
+    ///
+    /// Discord's timestamps are in seconds, so this is only accurate to a
+    /// second.
+    pub signed_at: u64,
+}
+
+impl LazyInteraction {
+    /// Partially deserialize a verified body, such as one whose signature was
+    /// verified with [`verify`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of type [`DeserializingInteraction`] if the type or
+    /// data of the body are invalid.
+    ///
+    /// [`DeserializingInteraction`]: ProcessRequestErrorType::DeserializingInteraction
+    /// [`verify`]: crate::verify
+    pub fn new(body: Vec<u8>) -> Result<Self, ProcessRequestError> {
+        // The runtime's JSON parser produces owned strings, whereas
+        // `serde_json` borrows them from the body where possible.
+        #[cfg(feature = "js-json")]
+        let result = from_body::<RouteFields<'static>>(&body);
+
+        #[cfg(not(feature = "js-json"))]
+        let result = serde_json::from_slice::<RouteFields<'_>>(&body)
+            .map_err(|source| Box::new(source) as Box<dyn std::error::Error>);
+
+        let route = match result {
+            Ok(route) => route,
+            Err(source) => {
+                return Err(ProcessRequestError {
+                    kind: ProcessRequestErrorType::DeserializingInteraction { body: body.into() },
+                    source: Some(source),
+                })
+            }
+        };
+
+        let name = route
+            .data
+            .and_then(|data| data.name.or(data.custom_id))
+            .map(Cow::into_owned);
+
+        Ok(Self {
+            #[cfg(any(feature = "minimal-model", feature = "twilight-model"))]
+            kind: route.kind,
+            body,
+            name,
+            timing: None,
+        })
+    }
+
+    /// Attach the timing recorded while verifying the request.
+    #[cfg(feature = "worker")]
+    pub(crate) const fn with_timing(mut self, timing: RequestTiming) -> Self {
+        self.timing = Some(timing);
+
+        self
+    }
+
+    /// Raw body of the request.
+    #[must_use = "retrieving the body is not useful on its own"]
+    pub fn body(&self) -> &[u8] {
+        &self.body
+    }
+
+    /// Type of interaction.
+    ///
+    /// The type is from `twilight-model`, or from the `model` module when only
+    /// the `minimal-model` feature is enabled.
+    #[cfg(any(feature = "minimal-model", feature = "twilight-model"))]
+    #[must_use = "retrieving the type is not useful on its own"]
+    pub const fn kind(&self) -> InteractionType {
+        self.kind
+    }
+
+    /// Name of the command if the interaction is a command or autocomplete,
+    /// or the custom ID if the interaction is a component or modal.
+    ///
+    /// Returns `None` for interactions without data, such as pings.
+    #[must_use = "retrieving the name is not useful on its own"]
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Times the request was signed and received, if the interaction was
+    /// created while verifying a request.
+    ///
+    /// Returns `None` for interactions created with [`new`].
+    ///
+    /// [`new`]: Self::new
+    #[must_use = "retrieving the timing is not useful on its own"]
+    pub const fn timing(&self) -> Option<RequestTiming> {
+        self.timing
+    }
+
+    /// Consume the lazy interaction, returning the raw body of the request.
+    #[must_use = "consuming the interaction and retrieving the body has no effect if left unused"]
+    pub fn into_body(self) -> Vec<u8> {
+        self.body
+    }
+
+    /// Consume the lazy interaction, deserializing the full interaction.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of type [`DeserializingInteraction`] if the body could
+    /// not be deserialized as an interaction.
+    ///
+    /// [`DeserializingInteraction`]: ProcessRequestErrorType::DeserializingInteraction
+    #[cfg(feature = "twilight-model")]
+    pub fn into_interaction(self) -> Result<Interaction, ProcessRequestError> {
+        deserialize(self.body)
+    }
+
+    /// Consume the lazy interaction, deserializing it with its data kept as
+    /// raw JSON.
+    ///
+    /// This always uses `serde_json`, even when the `js-json` feature is
+    /// enabled.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of type [`DeserializingInteraction`] if the body could
+    /// not be deserialized as an interaction.
+    ///
+    /// [`DeserializingInteraction`]: ProcessRequestErrorType::DeserializingInteraction
+    #[cfg(feature = "twilight-model")]
+    pub fn into_raw(self) -> Result<RawInteraction, ProcessRequestError> {
+        serde_json::from_slice(&self.body).map_err(|source| ProcessRequestError {
+            kind: ProcessRequestErrorType::DeserializingInteraction {
+                body: self.body.into(),
+            },
+            source: Some(Box::new(source)),
+        })
+    }
+}
+
+/// Interaction whose data has been kept as raw JSON.
+///
+/// This is useful for handlers that forward the data elsewhere or only need to
+/// deserialize part of it, as they don't need to pay for deserializing the
+/// deeply nested structures of the data. The data can be deserialized from
+/// [`RawValue::get`] as needed.
+///
+/// Refer to [`Interaction`] for documentation on each of the fields.
+///
+/// [`Interaction`]: twilight_model::application::interaction::Interaction
+#[allow(missing_docs)]
+#[cfg(feature = "twilight-model")]
+#[derive(Clone, Debug, Deserialize)]
+pub struct RawInteraction {
+    #[serde(default)]
+    pub app_permissions: Option<Permissions>,
+    pub application_id: Id<ApplicationMarker>,
+    #[serde(default)]
+    pub channel: Option<Channel>,
+    #[serde(default)]
+    pub data: Option<Box<RawValue>>,
+    #[serde(default)]
+    pub guild_id: Option<Id<GuildMarker>>,
+    #[serde(default)]
+    pub guild_locale: Option<String>,
+    pub id: Id<InteractionMarker>,
+    #[serde(rename = "type")]
+    pub kind: InteractionType,
+    #[serde(default)]
+    pub locale: Option<String>,
+    #[serde(default)]
+    pub member: Option<PartialMember>,
+    #[serde(default)]
+    pub message: Option<Message>,
+    pub token: String,
+    #[serde(default)]
+    pub user: Option<User>,
+}
+
+/// Deserialize a verified body as an interaction.
+///
+/// When the `js-json` feature is enabled the body is parsed by the runtime's
+/// JSON parser and converted from the resulting JavaScript value, otherwise it
+/// is parsed by `serde_json`.
+///
+/// # Errors
+///
+/// Returns an error of type [`DeserializingInteraction`] if the body could not
+/// be deserialized.
+///
+/// [`DeserializingInteraction`]: ProcessRequestErrorType::DeserializingInteraction
+#[cfg(any(feature = "minimal-model", feature = "twilight-model"))]
+pub(crate) fn deserialize<T: DeserializeOwned>(body: Vec<u8>) -> Result<T, ProcessRequestError> {
+    from_body(&body).map_err(|source| ProcessRequestError {
+        kind: ProcessRequestErrorType::DeserializingInteraction { body: body.into() },
+        source: Some(source),
+    })
+}
+
+/// Deserialize a body with the runtime's JSON parser when the `js-json`
+/// feature is enabled, and with `serde_json` otherwise.
+#[cfg(any(
+    feature = "js-json",
+    feature = "minimal-model",
+    feature = "twilight-model"
+))]
+fn from_body<T: DeserializeOwned>(body: &[u8]) -> Result<T, Box<dyn std::error::Error>> {
+    #[cfg(feature = "js-json")]
+    let result = match std::str::from_utf8(body) {
+        Ok(text) => js_sys::JSON::parse(text)
+            .map_err(worker::Error::from)
+            .and_then(|value| serde_wasm_bindgen::from_value(value).map_err(Into::into))
+            .map_err(|source| Box::new(source) as Box<dyn std::error::Error>),
+        Err(source) => Err(Box::new(source) as Box<dyn std::error::Error>),
+    };
+
+    #[cfg(not(feature = "js-json"))]
+    let result = serde_json::from_slice(body)
+        .map_err(|source| Box::new(source) as Box<dyn std::error::Error>);
+
+    result
+}
+
+/// Fields of an interaction required to route it.
+#[derive(Deserialize)]
+struct RouteFields<'a> {
+    #[cfg_attr(not(feature = "js-json"), serde(borrow))]
+    #[serde(default)]
+    data: Option<RouteData<'a>>,
+    #[cfg(any(feature = "minimal-model", feature = "twilight-model"))]
+    #[serde(rename = "type")]
+    kind: InteractionType,
+}
+
+/// Fields of an interaction's data required to route it.
+#[derive(Deserialize)]
+struct RouteData<'a> {
+    #[cfg_attr(not(feature = "js-json"), serde(borrow))]
+    #[serde(default)]
+    custom_id: Option<Cow<'a, str>>,
+    #[cfg_attr(not(feature = "js-json"), serde(borrow))]
+    #[serde(default)]
+    name: Option<Cow<'a, str>>,
+}
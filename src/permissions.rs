@@ -0,0 +1,395 @@
+//! Computing the permissions of guild members, including in channels with
+//! permission overwrites.
+//!
+//! The permissions of the member invoking an interaction are provided by
+//! Discord in the interaction, already including the overwrites of the
+//! channel. [`Calculator`] computes the permissions of other members, such as
+//! the target of a moderation command, from the guild's roles and the
+//! channel's overwrites.
+//!
+//! ```ignore
+//! use twilight_cloudflare_workers::permissions::Calculator;
+//!
+//! let permissions = Calculator::new(guild_id, target.id, &member.roles, &guild_roles)
+//!     .owner_id(guild.owner_id)
+//!     .channel(&channel.permission_overwrites);
+//!
+//! if !permissions.contains(Permissions::SEND_MESSAGES) {
+//!     return respond("They can't send messages in this channel.");
+//! }
+//! ```
+
+use twilight_model::{
+    channel::permission_overwrite::{PermissionOverwrite, PermissionOverwriteType},
+    guild::{Permissions, Role},
+    id::{
+        marker::{GuildMarker, RoleMarker, UserMarker},
+        Id,
+    },
+};
+
+/// Permissions timed out members keep in channels they could otherwise view.
+const TIMED_OUT_PERMISSIONS: Permissions =
+    Permissions::VIEW_CHANNEL.union(Permissions::READ_MESSAGE_HISTORY);
+
+/// Calculator of the permissions of a guild member.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[must_use = "calculators have no effect if unused"]
+pub struct Calculator<'a> {
+    guild_id: Id<GuildMarker>,
+    member_roles: &'a [Id<RoleMarker>],
+    owner_id: Option<Id<UserMarker>>,
+    roles: &'a [Role],
+    timed_out: bool,
+    user_id: Id<UserMarker>,
+}
+
+impl<'a> Calculator<'a> {
+    /// Create a new calculator for a member with the IDs of their roles and
+    /// the roles of the guild, which must include the `@everyone` role.
+    pub const fn new(
+        guild_id: Id<GuildMarker>,
+        user_id: Id<UserMarker>,
+        member_roles: &'a [Id<RoleMarker>],
+        roles: &'a [Role],
+    ) -> Self {
+        Self {
+            guild_id,
+            member_roles,
+            owner_id: None,
+            roles,
+            timed_out: false,
+            user_id,
+        }
+    }
+
+    /// Set the ID of the guild's owner, who has all permissions.
+    ///
+    /// Defaults to the member not being the owner.
+    pub const fn owner_id(mut self, owner_id: Id<UserMarker>) -> Self {
+        self.owner_id = Some(owner_id);
+
+        self
+    }
+
+    /// Set whether the member is timed out, such as when their
+    /// `communication_disabled_until` is in the future, in which case they
+    /// can only view channels and read their message history.
+    ///
+    /// Defaults to not being timed out.
+    pub const fn timed_out(mut self, timed_out: bool) -> Self {
+        self.timed_out = timed_out;
+
+        self
+    }
+
+    /// Permissions of the member in a channel with permission overwrites.
+    ///
+    /// Overwrites are applied in Discord's order: the `@everyone` overwrite,
+    /// then the overwrites of the member's roles, then the member's own
+    /// overwrite. Members who can't view the channel have no permissions in
+    /// it.
+    #[must_use = "calculating the permissions is not useful on its own"]
+    pub fn channel(&self, overwrites: &[PermissionOverwrite]) -> Permissions {
+        let guild = self.base();
+
+        if self.is_privileged(guild) {
+            return Permissions::all();
+        }
+
+        let mut permissions = guild;
+        let everyone_id = self.guild_id.cast();
+
+        if let Some(everyone) = overwrites.iter().find(|overwrite| {
+            overwrite.kind == PermissionOverwriteType::Role && overwrite.id == everyone_id
+        }) {
+            permissions = apply(permissions, everyone.allow, everyone.deny);
+        }
+
+        let (allow, deny) = overwrites
+            .iter()
+            .filter(|overwrite| {
+                overwrite.kind == PermissionOverwriteType::Role
+                    && overwrite.id != everyone_id
+                    && self.member_roles.contains(&overwrite.id.cast())
+            })
+            .fold(
+                (Permissions::empty(), Permissions::empty()),
+                |(allow, deny), overwrite| (allow | overwrite.allow, deny | overwrite.deny),
+            );
+        permissions = apply(permissions, allow, deny);
+
+        if let Some(member) = overwrites.iter().find(|overwrite| {
+            overwrite.kind == PermissionOverwriteType::Member && overwrite.id == self.user_id.cast()
+        }) {
+            permissions = apply(permissions, member.allow, member.deny);
+        }
+
+        if !permissions.contains(Permissions::VIEW_CHANNEL) {
+            return Permissions::empty();
+        }
+
+        self.restrict(permissions)
+    }
+
+    /// Permissions of the member in the guild, without channel overwrites.
+    #[must_use = "calculating the permissions is not useful on its own"]
+    pub fn guild(&self) -> Permissions {
+        let permissions = self.base();
+
+        if self.is_privileged(permissions) {
+            return Permissions::all();
+        }
+
+        self.restrict(permissions)
+    }
+
+    /// Permissions of the `@everyone` role and the member's roles.
+    fn base(&self) -> Permissions {
+        let everyone_id = self.guild_id.cast();
+
+        self.roles
+            .iter()
+            .filter(|role| role.id == everyone_id || self.member_roles.contains(&role.id))
+            .fold(Permissions::empty(), |permissions, role| {
+                permissions | role.permissions
+            })
+    }
+
+    /// Whether the member has all permissions regardless of overwrites, as
+    /// the owner or an administrator.
+    fn is_privileged(&self, guild: Permissions) -> bool {
+        self.owner_id == Some(self.user_id) || guild.contains(Permissions::ADMINISTRATOR)
+    }
+
+    /// Restrict permissions to those timed out members keep, if the member is
+    /// timed out.
+    const fn restrict(&self, permissions: Permissions) -> Permissions {
+        if self.timed_out {
+            permissions.intersection(TIMED_OUT_PERMISSIONS)
+        } else {
+            permissions
+        }
+    }
+}
+
+/// Apply the permissions allowed and denied by an overwrite.
+const fn apply(permissions: Permissions, allow: Permissions, deny: Permissions) -> Permissions {
+    permissions.difference(deny).union(allow)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Calculator, TIMED_OUT_PERMISSIONS};
+    use twilight_model::{
+        channel::permission_overwrite::{PermissionOverwrite, PermissionOverwriteType},
+        guild::{Permissions, Role, RoleFlags},
+        id::{
+            marker::{GuildMarker, RoleMarker, UserMarker},
+            Id,
+        },
+    };
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    const GUILD_ID: Id<GuildMarker> = Id::new(1);
+    const MEMBER_ROLES: &[Id<RoleMarker>] = &[Id::new(2), Id::new(3)];
+    const USER_ID: Id<UserMarker> = Id::new(10);
+
+    fn role(id: u64, permissions: Permissions) -> Role {
+        Role {
+            color: 0,
+            hoist: false,
+            icon: None,
+            id: Id::new(id),
+            managed: false,
+            mentionable: false,
+            name: id.to_string(),
+            permissions,
+            position: 0,
+            flags: RoleFlags::empty(),
+            tags: None,
+            unicode_emoji: None,
+        }
+    }
+
+    const fn overwrite(
+        kind: PermissionOverwriteType,
+        id: u64,
+        allow: Permissions,
+        deny: Permissions,
+    ) -> PermissionOverwrite {
+        PermissionOverwrite {
+            allow,
+            deny,
+            id: Id::new(id),
+            kind,
+        }
+    }
+
+    fn roles() -> Vec<Role> {
+        vec![
+            role(1, Permissions::VIEW_CHANNEL | Permissions::SEND_MESSAGES),
+            role(2, Permissions::EMBED_LINKS),
+            role(3, Permissions::ATTACH_FILES),
+            role(4, Permissions::BAN_MEMBERS),
+        ]
+    }
+
+    #[test]
+    fn guild_permissions() {
+        let roles = roles();
+
+        assert_eq!(
+            Calculator::new(GUILD_ID, USER_ID, MEMBER_ROLES, &roles).guild(),
+            Permissions::VIEW_CHANNEL
+                | Permissions::SEND_MESSAGES
+                | Permissions::EMBED_LINKS
+                | Permissions::ATTACH_FILES
+        );
+        assert_eq!(
+            Calculator::new(GUILD_ID, USER_ID, &[], &roles).guild(),
+            Permissions::VIEW_CHANNEL | Permissions::SEND_MESSAGES
+        );
+    }
+
+    #[test]
+    fn overwrite_precedence() {
+        let roles = roles();
+        let calculator = Calculator::new(GUILD_ID, USER_ID, MEMBER_ROLES, &roles);
+        let everyone = overwrite(
+            PermissionOverwriteType::Role,
+            1,
+            Permissions::empty(),
+            Permissions::SEND_MESSAGES | Permissions::EMBED_LINKS,
+        );
+
+        // The `@everyone` overwrite applies first.
+        let permissions = calculator.channel(&[everyone.clone()]);
+        assert!(!permissions.contains(Permissions::SEND_MESSAGES));
+        assert!(!permissions.contains(Permissions::EMBED_LINKS));
+
+        // Role allows take precedence over role denies and `@everyone`.
+        let overwrites = [
+            everyone.clone(),
+            overwrite(
+                PermissionOverwriteType::Role,
+                2,
+                Permissions::SEND_MESSAGES,
+                Permissions::ATTACH_FILES,
+            ),
+            overwrite(
+                PermissionOverwriteType::Role,
+                3,
+                Permissions::ATTACH_FILES,
+                Permissions::empty(),
+            ),
+            // Overwrites of roles the member doesn't have are ignored.
+            overwrite(
+                PermissionOverwriteType::Role,
+                4,
+                Permissions::EMBED_LINKS,
+                Permissions::empty(),
+            ),
+        ];
+        let permissions = calculator.channel(&overwrites);
+        assert!(permissions.contains(Permissions::SEND_MESSAGES | Permissions::ATTACH_FILES));
+        assert!(!permissions.contains(Permissions::EMBED_LINKS));
+
+        // The member's overwrite applies last, ignoring those of other users.
+        let mut overwrites = overwrites.to_vec();
+        overwrites.push(overwrite(
+            PermissionOverwriteType::Member,
+            11,
+            Permissions::empty(),
+            Permissions::ATTACH_FILES,
+        ));
+        overwrites.push(overwrite(
+            PermissionOverwriteType::Member,
+            10,
+            Permissions::EMBED_LINKS,
+            Permissions::SEND_MESSAGES,
+        ));
+        assert_eq!(
+            calculator.channel(&overwrites),
+            Permissions::VIEW_CHANNEL | Permissions::EMBED_LINKS | Permissions::ATTACH_FILES
+        );
+    }
+
+    #[test]
+    fn channel_not_viewable() {
+        let roles = roles();
+        let calculator = Calculator::new(GUILD_ID, USER_ID, MEMBER_ROLES, &roles);
+        let hidden = overwrite(
+            PermissionOverwriteType::Role,
+            1,
+            Permissions::empty(),
+            Permissions::VIEW_CHANNEL,
+        );
+
+        assert_eq!(calculator.channel(&[hidden.clone()]), Permissions::empty());
+        assert!(calculator
+            .channel(&[
+                hidden,
+                overwrite(
+                    PermissionOverwriteType::Member,
+                    10,
+                    Permissions::VIEW_CHANNEL,
+                    Permissions::empty(),
+                ),
+            ])
+            .contains(Permissions::VIEW_CHANNEL | Permissions::SEND_MESSAGES));
+    }
+
+    #[test]
+    fn privileged_short_circuit() {
+        let deny_all = [
+            overwrite(
+                PermissionOverwriteType::Role,
+                1,
+                Permissions::empty(),
+                Permissions::all(),
+            ),
+            overwrite(
+                PermissionOverwriteType::Member,
+                10,
+                Permissions::empty(),
+                Permissions::all(),
+            ),
+        ];
+
+        let mut administrator_roles = roles();
+        administrator_roles[2].permissions |= Permissions::ADMINISTRATOR;
+        let administrator = Calculator::new(GUILD_ID, USER_ID, MEMBER_ROLES, &administrator_roles);
+        assert_eq!(administrator.guild(), Permissions::all());
+        assert_eq!(administrator.channel(&deny_all), Permissions::all());
+
+        let roles = roles();
+        let owner = Calculator::new(GUILD_ID, USER_ID, &[], &roles).owner_id(USER_ID);
+        assert_eq!(owner.guild(), Permissions::all());
+        assert_eq!(owner.channel(&deny_all), Permissions::all());
+        assert_eq!(owner.timed_out(true).guild(), Permissions::all());
+
+        let not_owner = Calculator::new(GUILD_ID, USER_ID, &[], &roles).owner_id(Id::new(11));
+        assert_eq!(not_owner.channel(&deny_all), Permissions::empty());
+    }
+
+    #[test]
+    fn timeout_masking() {
+        let mut roles = roles();
+        roles[0].permissions |= Permissions::READ_MESSAGE_HISTORY;
+        let calculator = Calculator::new(GUILD_ID, USER_ID, MEMBER_ROLES, &roles).timed_out(true);
+
+        assert_eq!(calculator.guild(), TIMED_OUT_PERMISSIONS);
+        assert_eq!(calculator.channel(&[]), TIMED_OUT_PERMISSIONS);
+        assert_eq!(
+            calculator.channel(&[overwrite(
+                PermissionOverwriteType::Role,
+                1,
+                Permissions::empty(),
+                Permissions::READ_MESSAGE_HISTORY,
+            )]),
+            Permissions::VIEW_CHANNEL
+        );
+    }
+}
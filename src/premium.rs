@@ -0,0 +1,635 @@
+//! Premium tiers gating commands by the entitlements of users and guilds to
+//! the application's SKUs.
+//!
+//! Tiers are named groups of SKUs, ordered from the lowest to the highest.
+//! Commands require a tier, and are either blocked with an upsell response or
+//! have their responses watermarked for users and guilds without it.
+//!
+//! Interactions include the entitlements of the user and guild that invoked
+//! them, read from the interaction's body with [`interaction_entitlements`].
+//! Where they are absent, such as when acting on a request other than an
+//! interaction, an [`EntitlementCache`] retrieves them through a
+//! [`BotClient`] and caches them in KV.
+//!
+//! ```ignore
+//! use twilight_cloudflare_workers::premium::{self, Gating, Premium};
+//!
+//! const PREMIUM: Premium = Premium::new(
+//!     &[("Supporter", &[Id::new(1)]), ("Pro", &[Id::new(2)])],
+//!     &[("export", "Pro", Gating::Block), ("stats", "Supporter", Gating::Watermark)],
+//! )
+//! .upsell("Upgrade to Pro in the App Directory to use this command.");
+//!
+//! let entitlements = premium::interaction_entitlements(interaction.body()).unwrap_or_default();
+//! let name = interaction.name().unwrap_or_default();
+//!
+//! let response = PREMIUM
+//!     .handle(&entitlements, name, |tier| async move { commands::run(name, tier).await })
+//!     .await;
+//! ```
+//!
+//! [`BotClient`]: crate::rest::BotClient
+
+use crate::{
+    rest::{BotClient, RestError},
+    table,
+    webhook_events::Entitlement,
+};
+use core::{
+    fmt::{Display, Formatter, Result as FmtResult},
+    future::Future,
+    time::Duration,
+};
+use serde::Deserialize;
+use std::error::Error;
+use twilight_model::{
+    channel::message::MessageFlags,
+    http::interaction::{InteractionResponse, InteractionResponseData, InteractionResponseType},
+    id::{
+        marker::{ApplicationMarker, GenericMarker, GuildMarker, UserMarker},
+        Id,
+    },
+};
+use worker::kv::KvStore;
+
+/// Maximum number of characters in the content of a message.
+const MESSAGE_CONTENT_LENGTH: usize = 2000;
+
+/// Minimum expiration TTL of values stored in KV, in seconds.
+const KV_TTL_MIN: u64 = 60;
+
+/// How a command is gated for users and guilds without its tier.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Gating {
+    /// Command is blocked with the upsell response.
+    Block,
+    /// Command is run with the watermark appended to its response.
+    Watermark,
+}
+
+/// Whether a command is allowed for the entitlements of a user and guild.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Access {
+    /// Command is allowed.
+    Allowed,
+    /// Command is blocked.
+    Blocked,
+    /// Command is allowed with its response watermarked.
+    Watermarked,
+}
+
+/// Premium tiers of an application and the commands that require them.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[must_use = "premium tiers have no effect if unused"]
+pub struct Premium<'a> {
+    commands: &'a [(&'a str, &'a str, Gating)],
+    tiers: &'a [(&'a str, &'a [Id<GenericMarker>])],
+    upsell: &'a str,
+    watermark: &'a str,
+}
+
+impl<'a> Premium<'a> {
+    /// Create new premium tiers from their names and SKUs, ordered from the
+    /// lowest to the highest, and the names of the commands that require a
+    /// tier along with the name of the tier and how they are gated.
+    ///
+    /// Commands that aren't listed, or whose tier isn't, are allowed for
+    /// everyone.
+    pub const fn new(
+        tiers: &'a [(&'a str, &'a [Id<GenericMarker>])],
+        commands: &'a [(&'a str, &'a str, Gating)],
+    ) -> Self {
+        Self {
+            commands,
+            tiers,
+            upsell: "This command requires a premium subscription.",
+            watermark: "-# Powered by a free plan",
+        }
+    }
+
+    /// Set the content of the ephemeral message blocked commands are responded
+    /// to with.
+    ///
+    /// Defaults to `This command requires a premium subscription.`
+    pub const fn upsell(mut self, upsell: &'a str) -> Self {
+        self.upsell = upsell;
+
+        self
+    }
+
+    /// Set the line appended to the content of the responses of watermarked
+    /// commands.
+    ///
+    /// Defaults to `-# Powered by a free plan`.
+    pub const fn watermark(mut self, watermark: &'a str) -> Self {
+        self.watermark = watermark;
+
+        self
+    }
+
+    /// Whether a command is allowed with entitlements.
+    #[must_use = "checking access is not useful on its own"]
+    pub fn access(&self, command: &str, entitlements: &[Entitlement]) -> Access {
+        let Some((_, required, gating)) = self.commands.iter().find(|(name, ..)| *name == command)
+        else {
+            return Access::Allowed;
+        };
+        let Some(required) = self.rank(required) else {
+            return Access::Allowed;
+        };
+
+        if self
+            .tier_rank(entitlements)
+            .is_some_and(|rank| rank >= required)
+        {
+            return Access::Allowed;
+        }
+
+        match gating {
+            Gating::Block => Access::Blocked,
+            Gating::Watermark => Access::Watermarked,
+        }
+    }
+
+    /// Run a command's handler with the highest tier of entitlements, unless
+    /// the command is blocked, in which case the upsell response is returned,
+    /// and watermark its response if the command is watermarked.
+    pub async fn handle<F, Fut>(
+        &self,
+        entitlements: &[Entitlement],
+        command: &str,
+        handler: F,
+    ) -> InteractionResponse
+    where
+        F: FnOnce(Option<&'a str>) -> Fut,
+        Fut: Future<Output = InteractionResponse>,
+    {
+        let access = self.access(command, entitlements);
+
+        if access == Access::Blocked {
+            return self.upsell_response();
+        }
+
+        let mut response = handler(self.tier(entitlements)).await;
+
+        if access == Access::Watermarked {
+            if let Some(data) = &mut response.data {
+                self.watermark_data(data);
+            }
+        }
+
+        response
+    }
+
+    /// Name of the highest tier of entitlements, if any.
+    ///
+    /// Entitlements that are deleted or consumed don't grant their tier.
+    #[must_use = "retrieving the tier is not useful on its own"]
+    pub fn tier(&self, entitlements: &[Entitlement]) -> Option<&'a str> {
+        self.tier_rank(entitlements).map(|rank| self.tiers[rank].0)
+    }
+
+    /// Ephemeral response of blocked commands.
+    #[must_use = "creating a response is not useful on its own"]
+    pub fn upsell_response(&self) -> InteractionResponse {
+        InteractionResponse {
+            kind: InteractionResponseType::ChannelMessageWithSource,
+            data: Some(InteractionResponseData {
+                content: Some(self.upsell.to_owned()),
+                flags: Some(MessageFlags::EPHEMERAL),
+                ..InteractionResponseData::default()
+            }),
+        }
+    }
+
+    /// Append the watermark to the content of a response, truncating the
+    /// content so that the watermark fits.
+    pub fn watermark_data(&self, data: &mut InteractionResponseData) {
+        let content = data.content.take().unwrap_or_default();
+        let watermark_length = self.watermark.chars().count() + 1;
+        let content = table::truncate(
+            content.trim_end(),
+            MESSAGE_CONTENT_LENGTH.saturating_sub(watermark_length),
+        );
+
+        data.content = Some(if content.is_empty() {
+            self.watermark.to_owned()
+        } else {
+            [&content, "\n", self.watermark].concat()
+        });
+    }
+
+    /// Rank of a tier by its name, from 0 for the lowest.
+    fn rank(&self, tier: &str) -> Option<usize> {
+        self.tiers.iter().position(|(name, _)| *name == tier)
+    }
+
+    /// Rank of the highest tier of entitlements, if any.
+    fn tier_rank(&self, entitlements: &[Entitlement]) -> Option<usize> {
+        entitlements
+            .iter()
+            .filter(|entitlement| !entitlement.deleted && entitlement.consumed != Some(true))
+            .filter_map(|entitlement| {
+                self.tiers
+                    .iter()
+                    .position(|(_, skus)| skus.contains(&entitlement.sku_id))
+            })
+            .max()
+    }
+}
+
+/// Entitlements included in the body of an interaction, or `None` if the body
+/// doesn't include them.
+#[must_use = "reading entitlements is not useful on its own"]
+pub fn interaction_entitlements(body: &[u8]) -> Option<Vec<Entitlement>> {
+    #[derive(Deserialize)]
+    struct Fields {
+        entitlements: Option<Vec<Entitlement>>,
+    }
+
+    serde_json::from_slice::<Fields>(body).ok()?.entitlements
+}
+
+/// Cache in KV of the entitlements of users and guilds retrieved from
+/// Discord, for where they aren't included in an interaction.
+#[derive(Clone)]
+#[must_use = "entitlement caches have no effect if unused"]
+pub struct EntitlementCache<'a> {
+    kv: &'a KvStore,
+    prefix: &'a str,
+    ttl: Duration,
+}
+
+impl<'a> EntitlementCache<'a> {
+    /// Create a new cache storing entitlements in a KV namespace.
+    pub const fn new(kv: &'a KvStore) -> Self {
+        Self {
+            kv,
+            prefix: "entitlements",
+            ttl: Duration::from_secs(300),
+        }
+    }
+
+    /// Set the prefix of the names entitlements are stored under, followed by
+    /// a colon and the IDs of the application, user, and guild.
+    ///
+    /// Defaults to `entitlements`.
+    pub const fn prefix(mut self, prefix: &'a str) -> Self {
+        self.prefix = prefix;
+
+        self
+    }
+
+    /// Set how long entitlements are cached for before they are retrieved
+    /// again.
+    ///
+    /// KV expires values after at least 60 seconds, so shorter TTLs are
+    /// rounded up. Defaults to 5 minutes.
+    pub const fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+
+        self
+    }
+
+    /// Entitlements of a user and the guild they are in, if any, from the
+    /// cache or else retrieved from Discord and cached.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of type [`Fetching`] if the entitlements could not be
+    /// retrieved from Discord.
+    ///
+    /// Returns an error of type [`Storing`] if the entitlements could not be
+    /// retrieved from or stored in KV.
+    ///
+    /// [`Fetching`]: PremiumErrorType::Fetching
+    /// [`Storing`]: PremiumErrorType::Storing
+    pub async fn entitlements(
+        &self,
+        client: &BotClient,
+        application_id: Id<ApplicationMarker>,
+        user_id: Id<UserMarker>,
+        guild_id: Option<Id<GuildMarker>>,
+    ) -> Result<Vec<Entitlement>, PremiumError> {
+        let name = self.name(application_id, user_id, guild_id);
+        let cached = self
+            .kv
+            .get(&name)
+            .text()
+            .await
+            .map_err(PremiumError::storing)?;
+
+        if let Some(entitlements) =
+            cached.and_then(|cached| serde_json::from_str::<Vec<Entitlement>>(&cached).ok())
+        {
+            return Ok(entitlements);
+        }
+
+        let mut entitlements = client
+            .entitlements(application_id, Some(user_id), None)
+            .await
+            .map_err(PremiumError::fetching)?;
+
+        if guild_id.is_some() {
+            entitlements.extend(
+                client
+                    .entitlements(application_id, None, guild_id)
+                    .await
+                    .map_err(PremiumError::fetching)?,
+            );
+        }
+
+        // Entitlements are always serializable.
+        if let Ok(value) = serde_json::to_string(&entitlements) {
+            self.kv
+                .put(&name, value)
+                .map_err(PremiumError::storing)?
+                .expiration_ttl(self.ttl.as_secs().max(KV_TTL_MIN))
+                .execute()
+                .await
+                .map_err(PremiumError::storing)?;
+        }
+
+        Ok(entitlements)
+    }
+
+    /// Name the entitlements of a user and guild are stored under.
+    fn name(
+        &self,
+        application_id: Id<ApplicationMarker>,
+        user_id: Id<UserMarker>,
+        guild_id: Option<Id<GuildMarker>>,
+    ) -> String {
+        let guild_id = guild_id.map_or_else(|| "-".to_owned(), |guild_id| guild_id.to_string());
+
+        format!("{}:{application_id}:{user_id}:{guild_id}", self.prefix)
+    }
+}
+
+/// Entitlements could not be retrieved.
+#[allow(clippy::module_name_repetitions)]
+#[derive(Debug)]
+pub struct PremiumError {
+    kind: PremiumErrorType,
+    source: Option<Box<dyn Error>>,
+}
+
+impl PremiumError {
+    /// Immutable reference to the type of error that occurred.
+    #[must_use = "retrieving the type has no effect if left unused"]
+    pub const fn kind(&self) -> &PremiumErrorType {
+        &self.kind
+    }
+
+    /// Consume the error, returning the source error if there is any.
+    #[must_use = "consuming the error and retrieving the source has no effect if left unused"]
+    pub fn into_source(self) -> Option<Box<dyn Error>> {
+        self.source
+    }
+
+    /// Consume the error, returning the owned error type and the source error.
+    #[must_use = "consuming the error into its parts has no effect if left unused"]
+    pub fn into_parts(self) -> (PremiumErrorType, Option<Box<dyn Error>>) {
+        (self.kind, self.source)
+    }
+
+    /// Create an error of type [`PremiumErrorType::Fetching`].
+    fn fetching(source: RestError) -> Self {
+        Self {
+            kind: PremiumErrorType::Fetching,
+            source: Some(Box::new(source)),
+        }
+    }
+
+    /// Create an error of type [`PremiumErrorType::Storing`].
+    fn storing(source: worker::kv::KvError) -> Self {
+        Self {
+            kind: PremiumErrorType::Storing,
+            source: Some(Box::new(worker::Error::from(source))),
+        }
+    }
+}
+
+impl Display for PremiumError {
+    #[cfg(feature = "minimal-errors")]
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.write_str("error ")?;
+
+        Display::fmt(&self.kind.code(), f)
+    }
+
+    #[cfg(not(feature = "minimal-errors"))]
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match &self.kind {
+            PremiumErrorType::Fetching => {
+                f.write_str("entitlements could not be retrieved from Discord")
+            }
+            PremiumErrorType::Storing => f.write_str("entitlements could not be accessed in KV"),
+        }
+    }
+}
+
+impl Error for PremiumError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.source
+            .as_ref()
+            .map(|source| &**source as &(dyn Error + 'static))
+    }
+}
+
+impl From<PremiumError> for worker::Error {
+    fn from(error: PremiumError) -> Self {
+        Self::RustError(error.to_string())
+    }
+}
+
+/// Type of [`PremiumError`] that occurred.
+#[allow(clippy::module_name_repetitions)]
+#[derive(Debug)]
+pub enum PremiumErrorType {
+    /// Entitlements could not be retrieved from Discord.
+    Fetching,
+    /// Entitlements could not be retrieved from or stored in KV.
+    Storing,
+}
+
+impl PremiumErrorType {
+    /// Numeric code of the type of error, which errors are displayed as with
+    /// the `minimal-errors` feature.
+    ///
+    /// Refer to [`ProcessRequestErrorType::code`] for more information.
+    ///
+    /// [`ProcessRequestErrorType::code`]: crate::ProcessRequestErrorType::code
+    #[must_use = "retrieving the code is not useful on its own"]
+    pub const fn code(&self) -> u16 {
+        match self {
+            Self::Fetching => 42,
+            Self::Storing => 43,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{interaction_entitlements, Access, Gating, Premium, MESSAGE_CONTENT_LENGTH};
+    use crate::webhook_events::Entitlement;
+    use futures_util::FutureExt;
+    use serde_json::json;
+    use twilight_model::{
+        http::interaction::{
+            InteractionResponse, InteractionResponseData, InteractionResponseType,
+        },
+        id::Id,
+    };
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    const PREMIUM: Premium<'static> = Premium::new(
+        &[
+            ("Supporter", &[Id::new(10)]),
+            ("Pro", &[Id::new(20), Id::new(21)]),
+        ],
+        &[
+            ("export", "Pro", Gating::Block),
+            ("stats", "Supporter", Gating::Watermark),
+            ("legacy", "Unknown", Gating::Block),
+        ],
+    );
+
+    fn entitlement(sku_id: u64) -> Entitlement {
+        serde_json::from_value(json!({
+            "application_id": "1",
+            "id": "2",
+            "sku_id": sku_id.to_string(),
+            "type": 8,
+            "user_id": "3",
+        }))
+        .unwrap()
+    }
+
+    fn message(content: &str) -> InteractionResponse {
+        InteractionResponse {
+            kind: InteractionResponseType::ChannelMessageWithSource,
+            data: Some(InteractionResponseData {
+                content: Some(content.to_owned()),
+                ..InteractionResponseData::default()
+            }),
+        }
+    }
+
+    #[test]
+    fn tier_is_highest() {
+        assert_eq!(None, PREMIUM.tier(&[]));
+        assert_eq!(None, PREMIUM.tier(&[entitlement(99)]));
+        assert_eq!(Some("Supporter"), PREMIUM.tier(&[entitlement(10)]));
+        assert_eq!(
+            Some("Pro"),
+            PREMIUM.tier(&[entitlement(10), entitlement(21)])
+        );
+    }
+
+    #[test]
+    fn tier_ignores_inactive() {
+        let deleted = Entitlement {
+            deleted: true,
+            ..entitlement(20)
+        };
+        let consumed = Entitlement {
+            consumed: Some(true),
+            ..entitlement(20)
+        };
+
+        assert_eq!(None, PREMIUM.tier(&[deleted, consumed]));
+    }
+
+    #[test]
+    fn access() {
+        let supporter = [entitlement(10)];
+        let pro = [entitlement(20)];
+
+        assert_eq!(Access::Blocked, PREMIUM.access("export", &supporter));
+        assert_eq!(Access::Allowed, PREMIUM.access("export", &pro));
+        assert_eq!(Access::Watermarked, PREMIUM.access("stats", &[]));
+        // Higher tiers include lower tiers.
+        assert_eq!(Access::Allowed, PREMIUM.access("stats", &pro));
+        assert_eq!(Access::Allowed, PREMIUM.access("help", &[]));
+        assert_eq!(Access::Allowed, PREMIUM.access("legacy", &[]));
+    }
+
+    #[test]
+    fn watermark() {
+        let mut data = InteractionResponseData {
+            content: Some("Stats\n".to_owned()),
+            ..InteractionResponseData::default()
+        };
+        PREMIUM.watermark("Free").watermark_data(&mut data);
+        assert_eq!(Some("Stats\nFree"), data.content.as_deref());
+
+        let mut data = InteractionResponseData::default();
+        PREMIUM.watermark("Free").watermark_data(&mut data);
+        assert_eq!(Some("Free"), data.content.as_deref());
+
+        let mut data = InteractionResponseData {
+            content: Some("a".repeat(MESSAGE_CONTENT_LENGTH)),
+            ..InteractionResponseData::default()
+        };
+        PREMIUM.watermark_data(&mut data);
+        let content = data.content.unwrap();
+        assert_eq!(MESSAGE_CONTENT_LENGTH, content.chars().count());
+        assert!(content.ends_with("…\n-# Powered by a free plan"));
+    }
+
+    #[test]
+    fn upsell_response() {
+        let response = PREMIUM.upsell("Upgrade").upsell_response();
+        let value = serde_json::to_value(&response).unwrap();
+
+        assert_eq!(json!(4), value["type"]);
+        assert_eq!(json!("Upgrade"), value["data"]["content"]);
+        assert_eq!(json!(64), value["data"]["flags"]);
+    }
+
+    #[test]
+    fn entitlements_from_body() {
+        let body = json!({
+            "entitlements": [{
+                "application_id": "1",
+                "deleted": false,
+                "id": "2",
+                "sku_id": "20",
+                "type": 8,
+                "user_id": "3",
+            }],
+            "id": "4",
+            "type": 2,
+        })
+        .to_string();
+
+        assert_eq!(
+            Some(vec![entitlement(20)]),
+            interaction_entitlements(body.as_bytes())
+        );
+        assert_eq!(None, interaction_entitlements(br#"{"id":"4","type":2}"#));
+        assert_eq!(None, interaction_entitlements(b"not json"));
+    }
+
+    #[test]
+    fn handle_gates_handler() {
+        let run = |command: &'static str, entitlements: Vec<Entitlement>| {
+            PREMIUM
+                .handle(&entitlements, command, |tier| async move {
+                    message(tier.unwrap_or("free"))
+                })
+                .now_or_never()
+                .unwrap()
+        };
+
+        assert_eq!(PREMIUM.upsell_response(), run("export", Vec::new()));
+        assert_eq!(message("Pro"), run("export", vec![entitlement(20)]));
+        assert_eq!(
+            message("free\n-# Powered by a free plan"),
+            run("stats", Vec::new())
+        );
+        assert_eq!(message("Supporter"), run("stats", vec![entitlement(10)]));
+    }
+}
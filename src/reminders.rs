@@ -0,0 +1,336 @@
+//! Reminders delivered to users at a time, built on the payloads scheduled
+//! with Durable Object alarms by the `scheduler` module.
+//!
+//! Reminders are scheduled with [`remind_at`] and delivered by the Durable
+//! Object's alarm handler with [`run_due`], as a followup message to the
+//! interaction they were created from while its token is valid, and else as
+//! a direct message sent through a [`BotClient`].
+//!
+//! Reminders are stored as payloads of the `scheduler` module, so the
+//! object's scheduled payloads should all be reminders, and the object
+//! shouldn't set its alarm for anything else.
+//!
+//! ```ignore
+//! use twilight_cloudflare_workers::reminders::{self, Reminder};
+//!
+//! #[durable_object]
+//! impl DurableObject for Reminders {
+//!     // When a reminder is created and forwarded to the object.
+//!     async fn fetch(&mut self, mut req: Request) -> Result<Response> {
+//!         let (when, reminder) = req.json::<(u64, Reminder)>().await?;
+//!         reminders::remind_at(&mut self.state.storage(), when, &reminder).await?;
+//!
+//!         Response::empty()
+//!     }
+//!
+//!     async fn alarm(&mut self) -> Result<Response> {
+//!         let client = BotClient::new(&self.env.secret("DISCORD_TOKEN")?.to_string());
+//!
+//!         for (user_id, source) in reminders::run_due(&mut self.state.storage(), &client).await? {
+//!             console_error!("failed to remind {user_id}: {source}");
+//!         }
+//!
+//!         Response::empty()
+//!     }
+//! }
+//! ```
+
+use crate::{
+    rest::{BotClient, FollowupClient, RestError},
+    scheduler::{self, Scheduled},
+    table,
+};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use twilight_model::{
+    application::interaction::Interaction,
+    channel::{message::AllowedMentions, Message},
+    http::interaction::InteractionResponseData,
+    id::{
+        marker::{ApplicationMarker, InteractionMarker, UserMarker},
+        Id,
+    },
+};
+use worker::{Date, Storage};
+
+/// Maximum number of characters in the content of a message.
+const MESSAGE_CONTENT_LENGTH: usize = 2000;
+
+/// Interaction a reminder was created from, to follow up on while its token
+/// is valid.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Followup {
+    /// ID of the application the interaction is for.
+    pub application_id: Id<ApplicationMarker>,
+    /// ID of the interaction.
+    pub interaction_id: Id<InteractionMarker>,
+    /// Token of the interaction.
+    pub token: String,
+}
+
+/// Reminder of a message to a user.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Reminder {
+    /// Content of the reminder.
+    pub content: String,
+    /// Interaction the reminder was created from, if any.
+    pub followup: Option<Followup>,
+    /// ID of the user to remind.
+    pub user_id: Id<UserMarker>,
+}
+
+impl Reminder {
+    /// Create a reminder for the user who invoked an interaction, following
+    /// up on the interaction if its token is still valid when the reminder is
+    /// delivered.
+    ///
+    /// Returns `None` if the interaction has no user.
+    #[must_use = "creating a reminder is not useful on its own"]
+    pub fn from_interaction(interaction: &Interaction, content: impl Into<String>) -> Option<Self> {
+        Some(Self {
+            content: content.into(),
+            followup: Some(Followup {
+                application_id: interaction.application_id,
+                interaction_id: interaction.id,
+                token: interaction.token.clone(),
+            }),
+            user_id: interaction.author_id()?,
+        })
+    }
+}
+
+/// How a reminder was delivered.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Delivery {
+    /// Reminder was sent as a direct message.
+    DirectMessage(Message),
+    /// Reminder was sent as a followup message to its interaction.
+    Followup(Message),
+    /// Reminder could not be delivered, as its interaction's token has
+    /// expired and the user can't be sent direct messages.
+    Undeliverable,
+}
+
+/// Schedule a reminder to be delivered at a time in milliseconds since the
+/// Unix epoch, returning its ID to cancel it with [`scheduler::cancel`].
+///
+/// # Errors
+///
+/// Refer to the documentation for [`scheduler::schedule_at`].
+pub async fn remind_at(
+    storage: &mut Storage,
+    when: u64,
+    reminder: &Reminder,
+) -> worker::Result<String> {
+    scheduler::schedule_at(storage, when, reminder).await
+}
+
+/// Deliver a reminder, following up on its interaction if its token hasn't
+/// expired, and else sending it as a direct message.
+///
+/// The followup mentions the user, while the direct message doesn't. If the
+/// followup fails, such as because the interaction's response was deleted,
+/// a direct message is sent instead.
+///
+/// # Errors
+///
+/// Refer to the documentation for [`BotClient::dm_user`].
+pub async fn deliver(client: &BotClient, reminder: &Reminder) -> Result<Delivery, RestError> {
+    if let Some(followup) = followup_client(reminder, Date::now().as_millis()) {
+        if let Ok(message) = followup.create_followup(&data(reminder, true)).await {
+            return Ok(Delivery::Followup(message));
+        }
+    }
+
+    Ok(client
+        .dm_user(reminder.user_id, &data(reminder, false))
+        .await?
+        .map_or(Delivery::Undeliverable, Delivery::DirectMessage))
+}
+
+/// Deliver the reminders that are due, returning the users who couldn't be
+/// reminded along with why.
+///
+/// Requests that would be rate limited are delayed until the rate limit
+/// resets. Reminders that couldn't be delivered aren't retried.
+///
+/// # Errors
+///
+/// Refer to the documentation for [`scheduler::run_due`].
+pub async fn run_due(
+    storage: &mut Storage,
+    client: &BotClient,
+) -> worker::Result<Vec<(Id<UserMarker>, RestError)>> {
+    let client = client.clone().delay_rate_limited(true);
+    let failures = RefCell::new(Vec::new());
+
+    scheduler::run_due(storage, |scheduled: Scheduled<Reminder>| {
+        let client = &client;
+        let failures = &failures;
+
+        async move {
+            let reminder = scheduled.payload;
+
+            if let Err(source) = deliver(client, &reminder).await {
+                failures.borrow_mut().push((reminder.user_id, source));
+            }
+        }
+    })
+    .await?;
+
+    Ok(failures.into_inner())
+}
+
+/// Data of the message of a reminder, mentioning the user if it is a
+/// followup.
+fn data(reminder: &Reminder, mention: bool) -> InteractionResponseData {
+    let (content, allowed_mentions) = if mention {
+        (
+            format!("<@{}> {}", reminder.user_id, reminder.content),
+            AllowedMentions {
+                users: vec![reminder.user_id],
+                ..AllowedMentions::default()
+            },
+        )
+    } else {
+        (reminder.content.clone(), AllowedMentions::default())
+    };
+
+    InteractionResponseData {
+        allowed_mentions: Some(allowed_mentions),
+        content: Some(table::truncate(&content, MESSAGE_CONTENT_LENGTH)),
+        ..InteractionResponseData::default()
+    }
+}
+
+/// Client to follow up on the interaction of a reminder with, if it has one
+/// whose token hasn't expired at a time in milliseconds since the Unix epoch.
+fn followup_client(reminder: &Reminder, now: u64) -> Option<FollowupClient> {
+    let followup = reminder.followup.as_ref()?;
+    let client = FollowupClient::new(followup.application_id, followup.token.clone())
+        .interaction_id(followup.interaction_id)
+        .delay_rate_limited(true);
+
+    (!client.is_expired_at(now)).then_some(client)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{data, followup_client, Followup, Reminder, MESSAGE_CONTENT_LENGTH};
+    use serde_json::json;
+    use twilight_model::{application::interaction::Interaction, id::Id};
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    /// Discord's epoch in milliseconds since the Unix epoch.
+    const DISCORD_EPOCH: u64 = 1_420_070_400_000;
+
+    /// Time in milliseconds since the Unix epoch the interaction of the
+    /// reminders was created at.
+    const CREATED_AT: u64 = 1_700_000_000_000;
+
+    /// Number of milliseconds an interaction token is valid for.
+    const TOKEN_LIFETIME: u64 = 15 * 60 * 1000;
+
+    fn reminder() -> Reminder {
+        Reminder {
+            content: "Water the plants".to_owned(),
+            followup: Some(Followup {
+                application_id: Id::new(1),
+                interaction_id: Id::new((CREATED_AT - DISCORD_EPOCH) << 22),
+                token: "token".to_owned(),
+            }),
+            user_id: Id::new(3),
+        }
+    }
+
+    #[test]
+    fn followup_while_token_is_valid() {
+        let client = followup_client(&reminder(), CREATED_AT + TOKEN_LIFETIME - 1).unwrap();
+
+        assert_eq!("token", client.token());
+        assert_eq!(Id::new(1), client.application_id());
+    }
+
+    #[test]
+    fn direct_message_once_token_expired() {
+        assert!(followup_client(&reminder(), CREATED_AT + TOKEN_LIFETIME).is_none());
+    }
+
+    #[test]
+    fn direct_message_without_interaction() {
+        let reminder = Reminder {
+            followup: None,
+            ..reminder()
+        };
+
+        assert!(followup_client(&reminder, CREATED_AT).is_none());
+    }
+
+    #[test]
+    fn followup_mentions_user() {
+        let data = data(&reminder(), true);
+
+        assert_eq!(Some("<@3> Water the plants"), data.content.as_deref());
+        assert_eq!(vec![Id::new(3)], data.allowed_mentions.unwrap().users);
+    }
+
+    #[test]
+    fn direct_message_has_no_mentions() {
+        let data = data(&reminder(), false);
+
+        assert_eq!(Some("Water the plants"), data.content.as_deref());
+        assert!(data.allowed_mentions.unwrap().users.is_empty());
+    }
+
+    #[test]
+    fn content_truncated() {
+        let reminder = Reminder {
+            content: "a".repeat(MESSAGE_CONTENT_LENGTH),
+            ..reminder()
+        };
+        let data = data(&reminder, true);
+
+        assert_eq!(
+            MESSAGE_CONTENT_LENGTH,
+            data.content.unwrap().chars().count()
+        );
+    }
+
+    #[test]
+    fn from_interaction() {
+        let interaction = serde_json::from_value::<Interaction>(json!({
+            "application_id": "1",
+            "data": { "id": "4", "name": "remind", "type": 1 },
+            "id": "2",
+            "token": "token",
+            "type": 2,
+            "user": {
+                "avatar": null,
+                "discriminator": "0",
+                "id": "3",
+                "username": "user",
+            },
+            "version": 1,
+        }))
+        .unwrap();
+        let reminder = Reminder::from_interaction(&interaction, "Water the plants").unwrap();
+
+        assert_eq!(Id::new(3), reminder.user_id);
+        assert_eq!(
+            Some(Followup {
+                application_id: Id::new(1),
+                interaction_id: Id::new(2),
+                token: "token".to_owned(),
+            }),
+            reminder.followup
+        );
+    }
+
+    #[test]
+    fn reminder_round_trip() {
+        let json = serde_json::to_string(&reminder()).unwrap();
+
+        assert_eq!(reminder(), serde_json::from_str(&json).unwrap());
+    }
+}
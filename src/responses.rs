@@ -0,0 +1,335 @@
+//! Interaction responses created locally, without requests to Discord's REST
+//! API, such as deferred responses to follow up on later and diagnostics for
+//! about and ping commands.
+
+use crate::RequestTiming;
+use twilight_model::{
+    application::interaction::{Interaction, InteractionType},
+    channel::message::MessageFlags,
+    http::interaction::{InteractionResponse, InteractionResponseData, InteractionResponseType},
+};
+use worker::{Date, Response};
+
+/// Metadata of the build of a Worker, reported by [`about_response`].
+///
+/// Use [`build_info`] to capture the metadata of the crate it is used in,
+/// with the git SHA and build time from the `GIT_SHA` and `BUILD_TIME`
+/// environment variables at compile time if they are set, such as with
+/// `GIT_SHA=$(git rev-parse --short HEAD) BUILD_TIME=$(date -u +%FT%TZ)` in
+/// the build command of the Worker.
+///
+/// [`build_info`]: crate::build_info
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct BuildInfo {
+    /// Time the Worker was built at, if provided.
+    pub build_time: Option<&'static str>,
+    /// Git SHA of the commit the Worker was built from, if provided.
+    pub git_sha: Option<&'static str>,
+    /// Name of the Worker's crate.
+    pub name: &'static str,
+    /// Version of the Worker's crate.
+    pub version: &'static str,
+}
+
+/// Capture the [`BuildInfo`] of the crate the macro is used in.
+///
+/// [`BuildInfo`]: crate::responses::BuildInfo
+#[macro_export]
+macro_rules! build_info {
+    () => {
+        $crate::responses::BuildInfo {
+            build_time: ::core::option_env!("BUILD_TIME"),
+            git_sha: ::core::option_env!("GIT_SHA"),
+            name: ::core::env!("CARGO_PKG_NAME"),
+            version: ::core::env!("CARGO_PKG_VERSION"),
+        }
+    };
+}
+
+/// Type of deferred response to an interaction.
+///
+/// Deferring a message component with a message sends a new message with a
+/// loading state, which is then updated when following up on the response,
+/// while deferring it with an update leaves the component's message as it is
+/// until it is updated.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum Deferral {
+    /// Defer message components with [`Update`], and other interactions with
+    /// [`Message`].
+    ///
+    /// [`Message`]: Self::Message
+    /// [`Update`]: Self::Update
+    #[default]
+    Auto,
+    /// Defer with a message to be sent later, as a
+    /// [`DeferredChannelMessageWithSource`] response.
+    ///
+    /// [`DeferredChannelMessageWithSource`]: InteractionResponseType::DeferredChannelMessageWithSource
+    Message,
+    /// Defer with an update to the message of the interaction, as a
+    /// [`DeferredUpdateMessage`] response.
+    ///
+    /// Only message components and modals submitted from them have a message
+    /// to update.
+    ///
+    /// [`DeferredUpdateMessage`]: InteractionResponseType::DeferredUpdateMessage
+    Update,
+}
+
+impl Deferral {
+    /// Type of response to defer an interaction of a type with.
+    #[must_use = "retrieving the response type is not useful on its own"]
+    pub fn response_type(self, kind: InteractionType) -> InteractionResponseType {
+        match self {
+            Self::Auto if kind == InteractionType::MessageComponent => {
+                InteractionResponseType::DeferredUpdateMessage
+            }
+            Self::Auto | Self::Message => InteractionResponseType::DeferredChannelMessageWithSource,
+            Self::Update => InteractionResponseType::DeferredUpdateMessage,
+        }
+    }
+}
+
+/// Create an ephemeral response to an about command reporting the build of
+/// the Worker, so that operators can confirm which deployment is serving
+/// requests.
+///
+/// ```ignore
+/// if interaction.command_name() == Some("about") {
+///     return Ok(twilight_cloudflare_workers::response(&responses::about_response(
+///         &twilight_cloudflare_workers::build_info!(),
+///     )));
+/// }
+/// ```
+#[must_use = "created responses must be used to actually send the response"]
+pub fn about_response(build: &BuildInfo) -> InteractionResponse {
+    let mut content = format!(
+        "{} {} (twilight-cloudflare-workers {})",
+        build.name,
+        build.version,
+        env!("CARGO_PKG_VERSION"),
+    );
+
+    if let Some(git_sha) = build.git_sha {
+        content.push_str("\nCommit: ");
+        content.push_str(git_sha);
+    }
+
+    if let Some(build_time) = build.build_time {
+        content.push_str("\nBuilt: ");
+        content.push_str(build_time);
+    }
+
+    InteractionResponse {
+        kind: InteractionResponseType::ChannelMessageWithSource,
+        data: Some(InteractionResponseData {
+            content: Some(content),
+            flags: Some(MessageFlags::EPHEMERAL),
+            ..InteractionResponseData::default()
+        }),
+    }
+}
+
+/// Create a deferred response to an interaction, for following up on later
+/// such as from a queue consumer.
+#[must_use = "created responses must be used to actually send the response"]
+pub fn deferred_response(interaction: &Interaction, deferral: Deferral) -> Response {
+    crate::response(&InteractionResponse {
+        kind: deferral.response_type(interaction.kind),
+        data: None,
+    })
+}
+
+/// Create an ephemeral response to a ping command reporting the latency of
+/// the Worker, for verifying deployments and debugging latency complaints.
+///
+/// The response reports the time since the crate received the request, and
+/// the time between Discord signing the request and the crate receiving it.
+/// The latter includes the network latency between Discord and the Worker
+/// along with any skew between their clocks, and is only accurate to a second
+/// since Discord's timestamps are in seconds.
+///
+/// The clocks of Workers only advance during I/O, so the processing time
+/// doesn't include time spent on the CPU.
+///
+/// ```ignore
+/// let interaction = twilight_cloudflare_workers::request_lazy(&mut req, &public_key).await?;
+///
+/// if let (Some("ping"), Some(timing)) = (interaction.name(), interaction.timing()) {
+///     return Ok(twilight_cloudflare_workers::response(&responses::ping_response(timing)));
+/// }
+/// ```
+#[must_use = "created responses must be used to actually send the response"]
+pub fn ping_response(timing: RequestTiming) -> InteractionResponse {
+    ping_response_at(timing, Date::now().as_millis())
+}
+
+/// Create the response to a ping command at a time in milliseconds since the
+/// Unix epoch.
+fn ping_response_at(timing: RequestTiming, now: u64) -> InteractionResponse {
+    let RequestTiming {
+        received_at,
+        signed_at,
+    } = timing;
+    let mut content = format!("Pong! Processed in {} ms.", now.saturating_sub(received_at));
+
+    if received_at >= signed_at {
+        content.push_str(&format!(
+            " Received {} ms after Discord signed the request.",
+            received_at - signed_at
+        ));
+    } else {
+        content.push_str(&format!(
+            " Received {} ms before Discord signed the request, so the clocks are skewed.",
+            signed_at - received_at
+        ));
+    }
+
+    InteractionResponse {
+        kind: InteractionResponseType::ChannelMessageWithSource,
+        data: Some(InteractionResponseData {
+            content: Some(content),
+            flags: Some(MessageFlags::EPHEMERAL),
+            ..InteractionResponseData::default()
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{about_response, ping_response_at, BuildInfo, Deferral};
+    use crate::RequestTiming;
+    use twilight_model::{
+        application::interaction::InteractionType,
+        channel::message::MessageFlags,
+        http::interaction::{InteractionResponse, InteractionResponseType},
+    };
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    const KINDS: [InteractionType; 5] = [
+        InteractionType::Ping,
+        InteractionType::ApplicationCommand,
+        InteractionType::MessageComponent,
+        InteractionType::ApplicationCommandAutocomplete,
+        InteractionType::ModalSubmit,
+    ];
+
+    const BUILD: BuildInfo = BuildInfo {
+        build_time: Some("2024-01-02T03:04:05Z"),
+        git_sha: Some("abc1234"),
+        name: "bot",
+        version: "1.2.3",
+    };
+
+    /// Content of an ephemeral message response.
+    fn ephemeral_content(response: InteractionResponse) -> String {
+        assert_eq!(
+            InteractionResponseType::ChannelMessageWithSource,
+            response.kind
+        );
+
+        let data = response.data.unwrap();
+        assert_eq!(Some(MessageFlags::EPHEMERAL), data.flags);
+
+        data.content.unwrap()
+    }
+
+    #[test]
+    fn about() {
+        assert_eq!(
+            format!(
+                "bot 1.2.3 (twilight-cloudflare-workers {})\nCommit: abc1234\nBuilt: 2024-01-02T03:04:05Z",
+                env!("CARGO_PKG_VERSION")
+            ),
+            ephemeral_content(about_response(&BUILD))
+        );
+    }
+
+    #[test]
+    fn about_without_metadata() {
+        let build = BuildInfo {
+            build_time: None,
+            git_sha: None,
+            ..BUILD
+        };
+
+        assert_eq!(
+            format!(
+                "bot 1.2.3 (twilight-cloudflare-workers {})",
+                env!("CARGO_PKG_VERSION")
+            ),
+            ephemeral_content(about_response(&build))
+        );
+    }
+
+    #[test]
+    fn about_build_info() {
+        let build = crate::build_info!();
+
+        assert_eq!("twilight-cloudflare-workers", build.name);
+        assert_eq!(env!("CARGO_PKG_VERSION"), build.version);
+    }
+
+    #[test]
+    fn deferral_auto() {
+        assert_eq!(Deferral::Auto, Deferral::default());
+
+        for kind in KINDS {
+            let expected = if kind == InteractionType::MessageComponent {
+                InteractionResponseType::DeferredUpdateMessage
+            } else {
+                InteractionResponseType::DeferredChannelMessageWithSource
+            };
+
+            assert_eq!(expected, Deferral::Auto.response_type(kind), "{kind:?}");
+        }
+    }
+
+    #[test]
+    fn deferral_message() {
+        for kind in KINDS {
+            assert_eq!(
+                InteractionResponseType::DeferredChannelMessageWithSource,
+                Deferral::Message.response_type(kind)
+            );
+        }
+    }
+
+    #[test]
+    fn deferral_update() {
+        for kind in KINDS {
+            assert_eq!(
+                InteractionResponseType::DeferredUpdateMessage,
+                Deferral::Update.response_type(kind)
+            );
+        }
+    }
+
+    #[test]
+    fn ping() {
+        let timing = RequestTiming {
+            received_at: 10_250,
+            signed_at: 10_000,
+        };
+
+        assert_eq!(
+            "Pong! Processed in 40 ms. Received 250 ms after Discord signed the request.",
+            ephemeral_content(ping_response_at(timing, 10_290))
+        );
+    }
+
+    #[test]
+    fn ping_skewed() {
+        let timing = RequestTiming {
+            received_at: 9_300,
+            signed_at: 10_000,
+        };
+
+        assert_eq!(
+            "Pong! Processed in 0 ms. Received 700 ms before Discord signed the request, so the \
+             clocks are skewed.",
+            ephemeral_content(ping_response_at(timing, 9_000))
+        );
+    }
+}
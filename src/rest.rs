@@ -0,0 +1,2455 @@
+//! Client for following up on interactions through Discord's REST API.
+//!
+//! Interactions can be acknowledged with a deferred response, after which the
+//! [`FollowupClient`] can be used to edit the response or create followup
+//! messages for up to 15 minutes, such as from a future scheduled with
+//! [`worker::Context::wait_until`] or from a queue consumer. [`defer_then`]
+//! combines deferring the response with scheduling such a future.
+//!
+//! The [`BotClient`] makes requests with a bot token instead, such as to
+//! post to a log channel, update a persistent message, or manage a guild's
+//! scheduled events.
+
+use crate::{
+    linked_roles::RoleConnectionMetadata,
+    responses::{deferred_response, Deferral},
+    webhook_events::Entitlement,
+    ResponseBytes,
+};
+use core::{
+    fmt::{Debug, Display, Formatter, Result as FmtResult},
+    future::Future,
+    time::Duration,
+};
+use futures_util::{
+    future::{self, Either},
+    stream, Stream, StreamExt,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{cell::RefCell, error::Error, str};
+use twilight_model::{
+    application::interaction::{Interaction, InteractionType},
+    channel::{Channel, Message},
+    guild::scheduled_event::{
+        EntityMetadata, EntityType, GuildScheduledEvent, PrivacyLevel, Status,
+    },
+    http::{
+        attachment::Attachment as AttachmentPayload,
+        interaction::{InteractionResponse, InteractionResponseData, InteractionResponseType},
+    },
+    id::{
+        marker::{
+            ApplicationMarker, ChannelMarker, GuildMarker, InteractionMarker, MessageMarker,
+            RoleMarker, ScheduledEventMarker, UserMarker,
+        },
+        Id,
+    },
+    util::Timestamp,
+};
+use worker::{
+    js_sys::{Math, Uint8Array},
+    wasm_bindgen::JsValue,
+    Context, Date, Delay, Fetch, Headers, Method, Request, RequestInit, Response,
+};
+
+/// Base URL of Discord's REST API.
+const API_BASE: &str = "https://discord.com/api/v10";
+
+/// Alphabet of standard base64.
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// JSON error code of Discord's responses to messages that can't be sent to a
+/// user.
+const CANNOT_MESSAGE_USER: u64 = 50007;
+
+/// Discord's epoch in milliseconds since the Unix epoch, from which snowflake
+/// timestamps count.
+const DISCORD_EPOCH: u64 = 1_420_070_400_000;
+
+/// Delimiter of code blocks in message content.
+const CODE_FENCE: &str = "```";
+
+/// First segments of routes whose following IDs, and tokens for webhooks and
+/// interactions, are major parameters that Discord buckets rate limits by.
+const MAJOR_RESOURCES: &[&str] = &["channels", "guilds", "interactions", "webhooks"];
+
+/// Maximum number of characters in the content of a message.
+const MESSAGE_CONTENT_LENGTH: usize = 2000;
+
+/// Boundary between the parts of multipart bodies, which is extended if it
+/// occurs in any of the parts.
+const MULTIPART_BOUNDARY: &str = "twilight-cloudflare-workers-boundary";
+
+/// Prefix of the filenames of attachments hidden behind a spoiler.
+const SPOILER_PREFIX: &str = "SPOILER_";
+
+/// Number of milliseconds an interaction token is valid for.
+const TOKEN_LIFETIME: u64 = 15 * 60 * 1000;
+
+/// User agent sent with requests, as required by Discord.
+const USER_AGENT: &str = concat!(
+    "DiscordBot (",
+    env!("CARGO_PKG_REPOSITORY"),
+    ", ",
+    env!("CARGO_PKG_VERSION"),
+    ")",
+);
+
+thread_local! {
+    /// Rate limits Discord responded with in this isolate.
+    static RATE_LIMITS: RefCell<RateLimits> = const {
+        RefCell::new(RateLimits {
+            buckets: Vec::new(),
+            global: 0,
+            keys: Vec::new(),
+        })
+    };
+}
+
+/// Client for following up on an interaction with its token.
+///
+/// The token of an interaction is valid for 15 minutes after the interaction
+/// was created.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FollowupClient {
+    application_id: Id<ApplicationMarker>,
+    delay_rate_limited: bool,
+    expires_at: Option<u64>,
+    token: String,
+}
+
+impl FollowupClient {
+    /// Create a new client for the interaction with the provided token.
+    pub fn new(application_id: Id<ApplicationMarker>, token: impl Into<String>) -> Self {
+        Self {
+            application_id,
+            delay_rate_limited: false,
+            expires_at: None,
+            token: token.into(),
+        }
+    }
+
+    /// Create a new client for an interaction, tracking when its token
+    /// expires.
+    #[must_use = "creating a client is not useful on its own"]
+    pub fn from_interaction(interaction: &Interaction) -> Self {
+        Self::new(interaction.application_id, interaction.token.clone())
+            .interaction_id(interaction.id)
+    }
+
+    /// ID of the application the interaction is for.
+    #[must_use = "retrieving the application ID is not useful on its own"]
+    pub const fn application_id(&self) -> Id<ApplicationMarker> {
+        self.application_id
+    }
+
+    /// Set whether requests that would be rate limited are delayed until the
+    /// rate limit resets, instead of failing with an error of type
+    /// [`RateLimited`].
+    ///
+    /// Rate limits Discord responds with are remembered for the lifetime of
+    /// the isolate, so that requests from other futures, such as other
+    /// invocations' [`Context::wait_until`] tasks, respect them too.
+    ///
+    /// Defaults to `false`.
+    ///
+    /// [`RateLimited`]: RestErrorType::RateLimited
+    #[must_use = "clients have no effect if unused"]
+    pub const fn delay_rate_limited(mut self, delay_rate_limited: bool) -> Self {
+        self.delay_rate_limited = delay_rate_limited;
+
+        self
+    }
+
+    /// Time in milliseconds since the Unix epoch at which the token expires,
+    /// if known.
+    #[must_use = "retrieving the expiry is not useful on its own"]
+    pub const fn expires_at(&self) -> Option<u64> {
+        self.expires_at
+    }
+
+    /// Whether the token is known to have expired at a time in milliseconds
+    /// since the Unix epoch.
+    ///
+    /// Tokens expire 15 minutes after their interaction was created, and
+    /// tokens whose expiry isn't known are assumed not to have expired.
+    #[must_use = "checking whether the token has expired is not useful on its own"]
+    pub fn is_expired_at(&self, now: u64) -> bool {
+        self.expires_at.is_some_and(|expires_at| expires_at <= now)
+    }
+
+    /// Set the ID of the interaction, tracking when its token expires from
+    /// the time the interaction was created.
+    ///
+    /// Once the token has expired, requests fail with an error of type
+    /// [`TokenExpired`] without being sent, such as so that queue consumers
+    /// can fall back to sending a message with a bot token.
+    ///
+    /// [`TokenExpired`]: RestErrorType::TokenExpired
+    #[must_use = "clients have no effect if unused"]
+    pub const fn interaction_id(mut self, interaction_id: Id<InteractionMarker>) -> Self {
+        let created_at = (interaction_id.get() >> 22) + DISCORD_EPOCH;
+        self.expires_at = Some(created_at + TOKEN_LIFETIME);
+
+        self
+    }
+
+    /// Token of the interaction.
+    #[must_use = "retrieving the token is not useful on its own"]
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+
+    /// Respond to the interaction through the REST API instead of in the
+    /// response to the interaction request.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of type [`RateLimited`] if the request was rate
+    /// limited, or would be and requests aren't delayed.
+    ///
+    /// Returns an error of type [`Requesting`] if the request could not be
+    /// sent.
+    ///
+    /// Returns an error of type [`Serializing`] if the response could not be
+    /// serialized.
+    ///
+    /// Returns an error of type [`Status`] if Discord responded with an
+    /// unsuccessful status code.
+    ///
+    /// Returns an error of type [`TokenExpired`] if the token of the
+    /// interaction is known to have expired.
+    ///
+    /// [`RateLimited`]: RestErrorType::RateLimited
+    /// [`Requesting`]: RestErrorType::Requesting
+    /// [`Serializing`]: RestErrorType::Serializing
+    /// [`Status`]: RestErrorType::Status
+    /// [`TokenExpired`]: RestErrorType::TokenExpired
+    pub async fn callback(
+        &self,
+        interaction_id: Id<InteractionMarker>,
+        response: &InteractionResponse,
+    ) -> Result<(), RestError> {
+        let path = format!("/interactions/{interaction_id}/{}/callback", self.token);
+
+        self.send(Method::Post, &path, Some(Body::json(response)?))
+            .await
+            .map(drop)
+    }
+
+    /// Respond to the interaction through the REST API, returning the
+    /// interaction callback and the resource it created, such as the message
+    /// of the response.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of type [`Deserializing`] if the response body could
+    /// not be deserialized.
+    ///
+    /// Refer to the documentation for [`callback`] for the other errors that
+    /// may be returned.
+    ///
+    /// [`Deserializing`]: RestErrorType::Deserializing
+    /// [`callback`]: Self::callback
+    pub async fn callback_with_response(
+        &self,
+        interaction_id: Id<InteractionMarker>,
+        response: &InteractionResponse,
+    ) -> Result<InteractionCallbackResponse, RestError> {
+        let path = format!(
+            "/interactions/{interaction_id}/{}/callback?with_response=true",
+            self.token
+        );
+
+        self.request(Method::Post, &path, Some(Body::json(response)?))
+            .await
+    }
+
+    /// Create a followup message.
+    ///
+    /// # Errors
+    ///
+    /// Refer to the documentation for [`callback_with_response`].
+    ///
+    /// [`callback_with_response`]: Self::callback_with_response
+    pub async fn create_followup(
+        &self,
+        data: &InteractionResponseData,
+    ) -> Result<Message, RestError> {
+        let path = format!("/webhooks/{}/{}", self.application_id, self.token);
+
+        self.request(Method::Post, &path, Some(Body::json(data)?))
+            .await
+    }
+
+    /// Create a followup message with files attached.
+    ///
+    /// # Errors
+    ///
+    /// Refer to the documentation for [`create_followup`].
+    ///
+    /// [`create_followup`]: Self::create_followup
+    pub async fn create_followup_with_attachments(
+        &self,
+        data: &InteractionResponseData,
+        attachments: &[Attachment],
+    ) -> Result<Message, RestError> {
+        let path = format!("/webhooks/{}/{}", self.application_id, self.token);
+
+        self.request(
+            Method::Post,
+            &path,
+            Some(Body::multipart(data, attachments)?),
+        )
+        .await
+    }
+
+    /// Create followup messages with the data, splitting content longer than
+    /// a message may be into multiple messages and returning the IDs of the
+    /// created messages in order.
+    ///
+    /// Content is split on newlines where possible, then on spaces, and code
+    /// blocks split across messages are closed and reopened with the same
+    /// language. Only the last message has the embeds, components, and other
+    /// fields of the data, while the flags and allowed mentions are used for
+    /// every message.
+    ///
+    /// # Errors
+    ///
+    /// Refer to the documentation for [`create_followup`]. Messages created
+    /// before the error occurred are not deleted.
+    ///
+    /// [`create_followup`]: Self::create_followup
+    pub async fn create_followups(
+        &self,
+        data: &InteractionResponseData,
+    ) -> Result<Vec<Id<MessageMarker>>, RestError> {
+        let mut ids = Vec::new();
+
+        for data in split(data) {
+            ids.push(self.create_followup(&data).await?.id);
+        }
+
+        Ok(ids)
+    }
+
+    /// Delete a followup message.
+    ///
+    /// # Errors
+    ///
+    /// Refer to the documentation for [`callback`].
+    ///
+    /// [`callback`]: Self::callback
+    pub async fn delete_followup(&self, message_id: Id<MessageMarker>) -> Result<(), RestError> {
+        let path = format!(
+            "/webhooks/{}/{}/messages/{message_id}",
+            self.application_id, self.token
+        );
+
+        self.send(Method::Delete, &path, None).await.map(drop)
+    }
+
+    /// Delete the original response to the interaction.
+    ///
+    /// # Errors
+    ///
+    /// Refer to the documentation for [`callback`].
+    ///
+    /// [`callback`]: Self::callback
+    pub async fn delete_response(&self) -> Result<(), RestError> {
+        let path = format!(
+            "/webhooks/{}/{}/messages/@original",
+            self.application_id, self.token
+        );
+
+        self.send(Method::Delete, &path, None).await.map(drop)
+    }
+
+    /// Retrieve the original response to the interaction.
+    ///
+    /// # Errors
+    ///
+    /// Refer to the documentation for [`callback_with_response`].
+    ///
+    /// [`callback_with_response`]: Self::callback_with_response
+    pub async fn response(&self) -> Result<Message, RestError> {
+        let path = format!(
+            "/webhooks/{}/{}/messages/@original",
+            self.application_id, self.token
+        );
+
+        self.request(Method::Get, &path, None).await
+    }
+
+    /// Edit a followup message.
+    ///
+    /// # Errors
+    ///
+    /// Refer to the documentation for [`callback_with_response`].
+    ///
+    /// [`callback_with_response`]: Self::callback_with_response
+    pub async fn update_followup(
+        &self,
+        message_id: Id<MessageMarker>,
+        data: &InteractionResponseData,
+    ) -> Result<Message, RestError> {
+        let path = format!(
+            "/webhooks/{}/{}/messages/{message_id}",
+            self.application_id, self.token
+        );
+
+        self.request(Method::Patch, &path, Some(Body::json(data)?))
+            .await
+    }
+
+    /// Edit the original response to the interaction, such as after deferring
+    /// it.
+    ///
+    /// # Errors
+    ///
+    /// Refer to the documentation for [`callback_with_response`].
+    ///
+    /// [`callback_with_response`]: Self::callback_with_response
+    pub async fn update_response(
+        &self,
+        data: &InteractionResponseData,
+    ) -> Result<Message, RestError> {
+        let path = format!(
+            "/webhooks/{}/{}/messages/@original",
+            self.application_id, self.token
+        );
+
+        self.request(Method::Patch, &path, Some(Body::json(data)?))
+            .await
+    }
+
+    /// Edit the original response to the interaction, attaching files to it.
+    ///
+    /// Attachments of the response aren't kept unless they're included in the
+    /// attachments of the data.
+    ///
+    /// # Errors
+    ///
+    /// Refer to the documentation for [`update_response`].
+    ///
+    /// [`update_response`]: Self::update_response
+    pub async fn update_response_with_attachments(
+        &self,
+        data: &InteractionResponseData,
+        attachments: &[Attachment],
+    ) -> Result<Message, RestError> {
+        let path = format!(
+            "/webhooks/{}/{}/messages/@original",
+            self.application_id, self.token
+        );
+
+        self.request(
+            Method::Patch,
+            &path,
+            Some(Body::multipart(data, attachments)?),
+        )
+        .await
+    }
+
+    /// Edit the original response to the interaction, splitting content longer
+    /// than a message may be and creating followup messages with the
+    /// remainder, returning the IDs of the original response and the created
+    /// messages in order.
+    ///
+    /// Refer to [`create_followups`] for how the data is split.
+    ///
+    /// # Errors
+    ///
+    /// Refer to the documentation for [`update_response`]. Messages created
+    /// before the error occurred are not deleted.
+    ///
+    /// [`create_followups`]: Self::create_followups
+    /// [`update_response`]: Self::update_response
+    pub async fn update_response_split(
+        &self,
+        data: &InteractionResponseData,
+    ) -> Result<Vec<Id<MessageMarker>>, RestError> {
+        let mut ids = Vec::new();
+
+        for (index, data) in split(data).iter().enumerate() {
+            let message = if index == 0 {
+                self.update_response(data).await?
+            } else {
+                self.create_followup(data).await?
+            };
+
+            ids.push(message.id);
+        }
+
+        Ok(ids)
+    }
+
+    /// Edit the original response with each update from a stream, such as
+    /// partial output of a long-running task, returning the last edited
+    /// message.
+    ///
+    /// The response is edited at most once per `min_interval`. Updates that
+    /// arrive sooner replace the pending update, so only the latest is sent
+    /// when the interval has passed, and the last update is always sent once
+    /// the stream ends. When Discord responds that the edit was rate limited,
+    /// the edit is retried after the duration Discord asks to wait for.
+    ///
+    /// Returns `None` if the stream ended without any updates.
+    ///
+    /// # Errors
+    ///
+    /// Refer to the documentation for [`update_response`].
+    ///
+    /// [`update_response`]: Self::update_response
+    pub async fn update_response_progressively(
+        &self,
+        updates: impl Stream<Item = InteractionResponseData>,
+        min_interval: Duration,
+    ) -> Result<Option<Message>, RestError> {
+        let updates = updates.fuse();
+        futures_util::pin_mut!(updates);
+
+        let mut message = None;
+        let mut next_edit = 0_u64;
+        let mut pending = None;
+
+        loop {
+            let Some(update) = pending.take() else {
+                match updates.next().await {
+                    Some(update) => pending = Some(update),
+                    None => break,
+                }
+
+                continue;
+            };
+
+            let wait = next_edit.saturating_sub(Date::now().as_millis());
+
+            if wait > 0 {
+                let delay = Delay::from(Duration::from_millis(wait));
+
+                match future::select(updates.next(), delay).await {
+                    Either::Left((Some(newer), _)) => {
+                        pending = Some(newer);
+
+                        continue;
+                    }
+                    Either::Left((None, delay)) => delay.await,
+                    Either::Right(_) => {}
+                }
+            }
+
+            match self.update_response(&update).await {
+                Ok(edited) => {
+                    message = Some(edited);
+                    next_edit = Date::now()
+                        .as_millis()
+                        .saturating_add(duration_millis(min_interval));
+                }
+                Err(source) => {
+                    let RestErrorType::RateLimited { retry_after } = source.kind else {
+                        return Err(source);
+                    };
+
+                    next_edit = Date::now()
+                        .as_millis()
+                        .saturating_add(duration_millis(retry_after));
+                    pending = Some(update);
+                }
+            }
+        }
+
+        Ok(message)
+    }
+
+    /// Send a request and deserialize the response body.
+    async fn request<T: DeserializeOwned>(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<Body>,
+    ) -> Result<T, RestError> {
+        deserialize(self.send(method, path, body).await?)
+    }
+
+    /// Send a request, returning the response body if the response was
+    /// successful.
+    async fn send(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<Body>,
+    ) -> Result<Vec<u8>, RestError> {
+        if self.is_expired_at(Date::now().as_millis()) {
+            return Err(RestError {
+                kind: RestErrorType::TokenExpired,
+                source: None,
+            });
+        }
+
+        send(method, path, body, &[], self.delay_rate_limited).await
+    }
+}
+
+/// Client for Discord's REST API authenticated with a bot token, such as for
+/// acting outside of the 15 minutes an interaction token is valid for.
+///
+/// Rate limits are tracked per bucket Discord responds with and the top-level
+/// channel, guild, or webhook of the route, as Discord buckets them.
+#[derive(Clone, Eq, PartialEq)]
+pub struct BotClient {
+    authorization: String,
+    delay_rate_limited: bool,
+}
+
+impl BotClient {
+    /// Create a new client authenticated with a bot token.
+    #[must_use = "creating a client is not useful on its own"]
+    pub fn new(token: &str) -> Self {
+        Self {
+            authorization: format!("Bot {token}"),
+            delay_rate_limited: false,
+        }
+    }
+
+    /// Set whether requests that would be rate limited are delayed until the
+    /// rate limit resets, instead of failing with an error of type
+    /// [`RateLimited`].
+    ///
+    /// Refer to [`FollowupClient::delay_rate_limited`] for more information.
+    ///
+    /// Defaults to `false`.
+    ///
+    /// [`RateLimited`]: RestErrorType::RateLimited
+    #[must_use = "clients have no effect if unused"]
+    pub const fn delay_rate_limited(mut self, delay_rate_limited: bool) -> Self {
+        self.delay_rate_limited = delay_rate_limited;
+
+        self
+    }
+
+    /// Add a role to a guild member, with a reason for the audit log.
+    ///
+    /// # Errors
+    ///
+    /// Refer to the documentation for [`create_message`].
+    ///
+    /// [`create_message`]: Self::create_message
+    pub async fn add_member_role(
+        &self,
+        guild_id: Id<GuildMarker>,
+        user_id: Id<UserMarker>,
+        role_id: Id<RoleMarker>,
+        reason: Option<&str>,
+    ) -> Result<(), RestError> {
+        let path = format!("/guilds/{guild_id}/members/{user_id}/roles/{role_id}");
+
+        self.send(Method::Put, &path, None, reason).await.map(drop)
+    }
+
+    /// Create a message in a channel.
+    ///
+    /// Messages are created from the same data as followup messages, of
+    /// which the fields only used by interaction responses, such as
+    /// autocomplete choices, are ignored by Discord.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of type [`Deserializing`] if the response body could
+    /// not be deserialized.
+    ///
+    /// Returns an error of type [`RateLimited`] if the request was rate
+    /// limited, or would be and requests aren't delayed.
+    ///
+    /// Returns an error of type [`Requesting`] if the request could not be
+    /// sent.
+    ///
+    /// Returns an error of type [`Serializing`] if the request body could not
+    /// be serialized.
+    ///
+    /// Returns an error of type [`Status`] if Discord responded with an
+    /// unsuccessful status code.
+    ///
+    /// [`Deserializing`]: RestErrorType::Deserializing
+    /// [`RateLimited`]: RestErrorType::RateLimited
+    /// [`Requesting`]: RestErrorType::Requesting
+    /// [`Serializing`]: RestErrorType::Serializing
+    /// [`Status`]: RestErrorType::Status
+    pub async fn create_message(
+        &self,
+        channel_id: Id<ChannelMarker>,
+        data: &InteractionResponseData,
+    ) -> Result<Message, RestError> {
+        let path = format!("/channels/{channel_id}/messages");
+
+        self.request(Method::Post, &path, Some(Body::json(data)?), None)
+            .await
+    }
+
+    /// Create a message in a channel with files attached.
+    ///
+    /// # Errors
+    ///
+    /// Refer to the documentation for [`create_message`].
+    ///
+    /// [`create_message`]: Self::create_message
+    pub async fn create_message_with_attachments(
+        &self,
+        channel_id: Id<ChannelMarker>,
+        data: &InteractionResponseData,
+        attachments: &[Attachment],
+    ) -> Result<Message, RestError> {
+        let path = format!("/channels/{channel_id}/messages");
+
+        self.request(
+            Method::Post,
+            &path,
+            Some(Body::multipart(data, attachments)?),
+            None,
+        )
+        .await
+    }
+
+    /// Create a scheduled event in a guild, with a reason for the audit log.
+    ///
+    /// The name, privacy level, scheduled start time, and entity type of the
+    /// event are required, along with the fields its entity type requires.
+    ///
+    /// # Errors
+    ///
+    /// Refer to the documentation for [`create_message`].
+    ///
+    /// [`create_message`]: Self::create_message
+    pub async fn create_scheduled_event(
+        &self,
+        guild_id: Id<GuildMarker>,
+        fields: &ScheduledEventFields,
+        reason: Option<&str>,
+    ) -> Result<GuildScheduledEvent, RestError> {
+        let path = format!("/guilds/{guild_id}/scheduled-events");
+
+        self.request(Method::Post, &path, Some(Body::json(fields)?), reason)
+            .await
+    }
+
+    /// Crosspost a message in an announcement channel to the channels
+    /// following it.
+    ///
+    /// # Errors
+    ///
+    /// Refer to the documentation for [`create_message`].
+    ///
+    /// [`create_message`]: Self::create_message
+    pub async fn crosspost_message(
+        &self,
+        channel_id: Id<ChannelMarker>,
+        message_id: Id<MessageMarker>,
+    ) -> Result<Message, RestError> {
+        let path = format!("/channels/{channel_id}/messages/{message_id}/crosspost");
+
+        self.request(Method::Post, &path, None, None).await
+    }
+
+    /// Delete a message in a channel, with a reason for the audit log if it
+    /// is another user's message.
+    ///
+    /// # Errors
+    ///
+    /// Refer to the documentation for [`create_message`].
+    ///
+    /// [`create_message`]: Self::create_message
+    pub async fn delete_message(
+        &self,
+        channel_id: Id<ChannelMarker>,
+        message_id: Id<MessageMarker>,
+        reason: Option<&str>,
+    ) -> Result<(), RestError> {
+        let path = format!("/channels/{channel_id}/messages/{message_id}");
+
+        self.send(Method::Delete, &path, None, reason)
+            .await
+            .map(drop)
+    }
+
+    /// Delete a scheduled event in a guild.
+    ///
+    /// # Errors
+    ///
+    /// Refer to the documentation for [`create_message`].
+    ///
+    /// [`create_message`]: Self::create_message
+    pub async fn delete_scheduled_event(
+        &self,
+        guild_id: Id<GuildMarker>,
+        event_id: Id<ScheduledEventMarker>,
+    ) -> Result<(), RestError> {
+        let path = format!("/guilds/{guild_id}/scheduled-events/{event_id}");
+
+        self.send(Method::Delete, &path, None, None).await.map(drop)
+    }
+
+    /// Send a direct message to a user, creating the direct message channel
+    /// with them if it doesn't exist yet.
+    ///
+    /// Returns `None` if the user can't be sent direct messages, such as
+    /// when they have disabled direct messages from the guild's members or
+    /// don't share a guild with the bot.
+    ///
+    /// # Errors
+    ///
+    /// Refer to the documentation for [`create_message`].
+    ///
+    /// [`create_message`]: Self::create_message
+    pub async fn dm_user(
+        &self,
+        user_id: Id<UserMarker>,
+        data: &InteractionResponseData,
+    ) -> Result<Option<Message>, RestError> {
+        let recipient = Recipient {
+            recipient_id: user_id,
+        };
+        let channel = self
+            .request::<Channel>(
+                Method::Post,
+                "/users/@me/channels",
+                Some(Body::json(&recipient)?),
+                None,
+            )
+            .await?;
+
+        match self.create_message(channel.id, data).await {
+            Ok(message) => Ok(Some(message)),
+            Err(source) if source.code() == Some(CANNOT_MESSAGE_USER) => Ok(None),
+            Err(source) => Err(source),
+        }
+    }
+
+    /// Retrieve the entitlements to the application's premium offerings that
+    /// haven't ended, of a user if a user ID is provided and of a guild if a
+    /// guild ID is provided.
+    ///
+    /// # Errors
+    ///
+    /// Refer to the documentation for [`create_message`].
+    ///
+    /// [`create_message`]: Self::create_message
+    pub async fn entitlements(
+        &self,
+        application_id: Id<ApplicationMarker>,
+        user_id: Option<Id<UserMarker>>,
+        guild_id: Option<Id<GuildMarker>>,
+    ) -> Result<Vec<Entitlement>, RestError> {
+        let mut path = format!("/applications/{application_id}/entitlements?exclude_ended=true");
+
+        if let Some(user_id) = user_id {
+            path.push_str(&format!("&user_id={user_id}"));
+        }
+
+        if let Some(guild_id) = guild_id {
+            path.push_str(&format!("&guild_id={guild_id}"));
+        }
+
+        self.request(Method::Get, &path, None, None).await
+    }
+
+    /// Remove a role from a guild member, with a reason for the audit log.
+    ///
+    /// # Errors
+    ///
+    /// Refer to the documentation for [`create_message`].
+    ///
+    /// [`create_message`]: Self::create_message
+    pub async fn remove_member_role(
+        &self,
+        guild_id: Id<GuildMarker>,
+        user_id: Id<UserMarker>,
+        role_id: Id<RoleMarker>,
+        reason: Option<&str>,
+    ) -> Result<(), RestError> {
+        let path = format!("/guilds/{guild_id}/members/{user_id}/roles/{role_id}");
+
+        self.send(Method::Delete, &path, None, reason)
+            .await
+            .map(drop)
+    }
+
+    /// Edit a message in a channel.
+    ///
+    /// # Errors
+    ///
+    /// Refer to the documentation for [`create_message`].
+    ///
+    /// [`create_message`]: Self::create_message
+    pub async fn update_message(
+        &self,
+        channel_id: Id<ChannelMarker>,
+        message_id: Id<MessageMarker>,
+        data: &InteractionResponseData,
+    ) -> Result<Message, RestError> {
+        let path = format!("/channels/{channel_id}/messages/{message_id}");
+
+        self.request(Method::Patch, &path, Some(Body::json(data)?), None)
+            .await
+    }
+
+    /// Register the metadata records that the application's linked roles
+    /// can require, replacing the records registered before.
+    ///
+    /// # Errors
+    ///
+    /// Refer to the documentation for [`create_message`].
+    ///
+    /// [`create_message`]: Self::create_message
+    pub async fn update_role_connection_metadata(
+        &self,
+        application_id: Id<ApplicationMarker>,
+        records: &[RoleConnectionMetadata],
+    ) -> Result<Vec<RoleConnectionMetadata>, RestError> {
+        let path = format!("/applications/{application_id}/role-connections/metadata");
+
+        self.request(Method::Put, &path, Some(Body::json(&records)?), None)
+            .await
+    }
+
+    /// Update the fields that are set of a scheduled event in a guild, with a
+    /// reason for the audit log.
+    ///
+    /// # Errors
+    ///
+    /// Refer to the documentation for [`create_message`].
+    ///
+    /// [`create_message`]: Self::create_message
+    pub async fn update_scheduled_event(
+        &self,
+        guild_id: Id<GuildMarker>,
+        event_id: Id<ScheduledEventMarker>,
+        fields: &ScheduledEventFields,
+        reason: Option<&str>,
+    ) -> Result<GuildScheduledEvent, RestError> {
+        let path = format!("/guilds/{guild_id}/scheduled-events/{event_id}");
+
+        self.request(Method::Patch, &path, Some(Body::json(fields)?), reason)
+            .await
+    }
+
+    /// Send a request and deserialize the response body.
+    async fn request<T: DeserializeOwned>(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<Body>,
+        reason: Option<&str>,
+    ) -> Result<T, RestError> {
+        deserialize(self.send(method, path, body, reason).await?)
+    }
+
+    /// Send a request with a reason for the audit log, returning the response
+    /// body if the response was successful.
+    async fn send(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<Body>,
+        reason: Option<&str>,
+    ) -> Result<Vec<u8>, RestError> {
+        let reason = reason.map(encode_reason);
+        let mut headers = vec![("Authorization", self.authorization.as_str())];
+
+        if let Some(reason) = &reason {
+            headers.push(("X-Audit-Log-Reason", reason));
+        }
+
+        send(method, path, body, &headers, self.delay_rate_limited).await
+    }
+}
+
+impl Debug for BotClient {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("BotClient")
+            .field("delay_rate_limited", &self.delay_rate_limited)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Times in milliseconds since the Unix epoch until which requests are rate
+/// limited.
+struct RateLimits {
+    /// Hashes of the buckets Discord responded with for the templates of
+    /// routes, which are shared by routes with the same limits.
+    buckets: Vec<(String, String)>,
+    /// Time until which all requests are rate limited.
+    global: u64,
+    /// Times until which requests with a key, made of the bucket or template
+    /// of their route and its major parameters, are rate limited.
+    keys: Vec<(String, u64)>,
+}
+
+impl RateLimits {
+    /// Record the hash of the bucket of a route.
+    fn bucket(&mut self, route: &Route, bucket: &str) {
+        if let Some((_, known)) = self
+            .buckets
+            .iter_mut()
+            .find(|(template, _)| *template == route.template)
+        {
+            if known != bucket {
+                bucket.clone_into(known);
+            }
+        } else {
+            self.buckets
+                .push((route.template.clone(), bucket.to_owned()));
+        }
+    }
+
+    /// Key requests for a route are rate limited by, which is the bucket of
+    /// the route if Discord responded with it and else the route's template,
+    /// along with its major parameters.
+    fn key(&self, route: &Route) -> String {
+        let bucket = self
+            .buckets
+            .iter()
+            .find(|(template, _)| *template == route.template)
+            .map_or(route.template.as_str(), |(_, bucket)| bucket.as_str());
+
+        [bucket, " ", &route.major].concat()
+    }
+
+    /// Time until which requests with a key are rate limited.
+    fn until(&self, key: &str) -> u64 {
+        self.keys
+            .iter()
+            .find(|(limited, _)| limited == key)
+            .map_or(self.global, |(_, until)| (*until).max(self.global))
+    }
+
+    /// Rate limit requests until a time, globally if no key is provided,
+    /// removing rate limits which have reset.
+    fn limit(&mut self, key: Option<String>, until: u64, now: u64) {
+        self.keys.retain(|(_, until)| *until > now);
+
+        let Some(key) = key else {
+            self.global = self.global.max(until);
+
+            return;
+        };
+
+        if let Some((_, limited_until)) = self.keys.iter_mut().find(|(limited, _)| *limited == key)
+        {
+            *limited_until = until;
+        } else {
+            self.keys.push((key, until));
+        }
+    }
+}
+
+/// Route of a request, as Discord buckets rate limits by.
+struct Route {
+    /// Top-level channel, guild, webhook, or interaction of the route, along
+    /// with the token of webhooks and interactions.
+    major: String,
+    /// Method and path of the route with its IDs replaced.
+    template: String,
+}
+
+impl Route {
+    /// Route of a request with a method and path.
+    ///
+    /// For example, adding different roles to different members of a guild
+    /// has the template `PUT /guilds/:id/members/:id/roles/:id` with the
+    /// guild's ID as the major parameter.
+    fn new(method: &Method, path: &str) -> Self {
+        let path = path.split_once('?').map_or(path, |(path, _)| path);
+        let mut major = String::new();
+        let mut template = String::from(method.as_ref());
+        template.push(' ');
+
+        let mut segments = path.split('/').skip(1);
+
+        if let Some(resource) = segments.next() {
+            template.push('/');
+            template.push_str(resource);
+
+            // Channels, guilds, webhooks and interactions are major
+            // parameters, along with the tokens of webhooks and interactions.
+            if MAJOR_RESOURCES.contains(&resource) {
+                let take = if matches!(resource, "interactions" | "webhooks") {
+                    2
+                } else {
+                    1
+                };
+
+                for segment in segments.by_ref().take(take) {
+                    template.push_str("/:major");
+                    major.push('/');
+                    major.push_str(segment);
+                }
+            }
+        }
+
+        for segment in segments {
+            template.push('/');
+            template.push_str(
+                if !segment.is_empty() && segment.bytes().all(|byte| byte.is_ascii_digit()) {
+                    ":id"
+                } else {
+                    segment
+                },
+            );
+        }
+
+        Self { major, template }
+    }
+}
+
+/// File to attach to a message.
+///
+/// Filenames are sanitized to the characters Discord keeps, replacing others
+/// with underscores.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[must_use = "attachments have no effect if unused"]
+pub struct Attachment {
+    content_type: Option<String>,
+    data: Vec<u8>,
+    description: Option<String>,
+    filename: String,
+    spoiler: bool,
+}
+
+impl Attachment {
+    /// Create a new attachment from the contents of a file and its name.
+    pub fn new(filename: &str, data: Vec<u8>) -> Self {
+        Self {
+            content_type: None,
+            data,
+            description: None,
+            filename: sanitize_filename(filename),
+            spoiler: false,
+        }
+    }
+
+    /// Set the media type of the file, such as `image/png`.
+    ///
+    /// Defaults to `application/octet-stream`, leaving Discord to detect the
+    /// type from the filename.
+    pub fn content_type(mut self, content_type: impl Into<String>) -> Self {
+        self.content_type = Some(content_type.into());
+
+        self
+    }
+
+    /// Set the description of the file, used as the alt text of images.
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+
+        self
+    }
+
+    /// Set whether the file is hidden behind a spoiler.
+    ///
+    /// Defaults to `false`.
+    pub const fn spoiler(mut self, spoiler: bool) -> Self {
+        self.spoiler = spoiler;
+
+        self
+    }
+
+    /// Sanitized name of the file, prefixed with `SPOILER_` if it is hidden
+    /// behind a spoiler.
+    #[must_use = "retrieving the filename is not useful on its own"]
+    pub fn filename(&self) -> String {
+        if self.spoiler {
+            [SPOILER_PREFIX, &self.filename].concat()
+        } else {
+            self.filename.clone()
+        }
+    }
+
+    /// Data URI of the file, such as for the image of a scheduled event.
+    ///
+    /// Discord only accepts images whose media type is set.
+    #[must_use = "encoding the file is not useful on its own"]
+    pub fn data_uri(&self) -> String {
+        let content_type = self
+            .content_type
+            .as_deref()
+            .unwrap_or("application/octet-stream");
+
+        format!("data:{content_type};base64,{}", base64(&self.data))
+    }
+}
+
+/// Body of a request creating a direct message channel.
+#[derive(Serialize)]
+struct Recipient {
+    recipient_id: Id<UserMarker>,
+}
+
+/// Body of a request.
+pub(crate) struct Body {
+    content_type: String,
+    value: JsValue,
+}
+
+impl Body {
+    /// Create a JSON body.
+    pub(crate) fn json(value: &impl Serialize) -> Result<Self, RestError> {
+        let json = serde_json::to_string(value).map_err(RestError::serializing)?;
+
+        Ok(Self {
+            content_type: "application/json".to_owned(),
+            value: JsValue::from_str(&json),
+        })
+    }
+
+    /// Create a multipart body with the data of a message as its JSON payload
+    /// followed by the attached files.
+    fn multipart(
+        data: &InteractionResponseData,
+        attachments: &[Attachment],
+    ) -> Result<Self, RestError> {
+        let ResponseBytes { body, content_type } =
+            multipart(&with_attachments(data, attachments), attachments)?;
+
+        Ok(Self {
+            content_type,
+            value: Uint8Array::from(body.as_slice()).into(),
+        })
+    }
+}
+
+/// Encode bytes as standard base64 with padding.
+fn base64(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+
+    for chunk in bytes.chunks(3) {
+        let first = chunk[0];
+        let second = chunk.get(1).copied().unwrap_or_default();
+        let third = chunk.get(2).copied().unwrap_or_default();
+        let indices = [
+            first >> 2,
+            (first & 0b11) << 4 | second >> 4,
+            (second & 0b1111) << 2 | third >> 6,
+            third & 0b11_1111,
+        ];
+
+        for (position, index) in indices.into_iter().enumerate() {
+            if position <= chunk.len() {
+                encoded.push(char::from(BASE64_ALPHABET[usize::from(index)]));
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+
+    encoded
+}
+
+/// Serialize a multipart body with a JSON payload followed by the attached
+/// files.
+fn multipart(
+    payload: &impl Serialize,
+    attachments: &[Attachment],
+) -> Result<ResponseBytes, RestError> {
+    let payload = serde_json::to_vec(payload).map_err(RestError::serializing)?;
+
+    // Extend the boundary until it doesn't occur in any of the parts.
+    let mut boundary = MULTIPART_BOUNDARY.to_owned();
+
+    while contains(&payload, boundary.as_bytes())
+        || attachments
+            .iter()
+            .any(|attachment| contains(&attachment.data, boundary.as_bytes()))
+    {
+        boundary.push('-');
+    }
+
+    let mut bytes = Vec::new();
+    push_part_header(
+        &mut bytes,
+        &boundary,
+        "name=\"payload_json\"",
+        "application/json",
+    );
+    bytes.extend_from_slice(&payload);
+
+    for (index, attachment) in attachments.iter().enumerate() {
+        let disposition = format!(
+            "name=\"files[{index}]\"; filename=\"{}\"",
+            attachment.filename()
+        );
+        let content_type = attachment
+            .content_type
+            .as_deref()
+            .unwrap_or("application/octet-stream");
+
+        push_part_header(&mut bytes, &boundary, &disposition, content_type);
+        bytes.extend_from_slice(&attachment.data);
+    }
+
+    bytes.extend_from_slice(format!("\r\n--{boundary}--\r\n").as_bytes());
+
+    Ok(ResponseBytes {
+        body: bytes,
+        content_type: format!("multipart/form-data; boundary={boundary}"),
+    })
+}
+
+/// Add the payloads of attachments to the data of a message.
+fn with_attachments(
+    data: &InteractionResponseData,
+    attachments: &[Attachment],
+) -> InteractionResponseData {
+    let mut data = data.clone();
+    let existing = data.attachments.get_or_insert_with(Vec::new);
+
+    for (id, attachment) in (0..).zip(attachments) {
+        existing.push(AttachmentPayload {
+            description: attachment.description.clone(),
+            file: Vec::new(),
+            filename: attachment.filename(),
+            id,
+        });
+    }
+
+    data
+}
+
+/// Whether a byte slice contains another.
+fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+    haystack
+        .windows(needle.len())
+        .any(|window| window == needle)
+}
+
+/// Percent-encode a reason for the audit log, as Discord requires of the
+/// header it is sent in.
+fn encode_reason(reason: &str) -> String {
+    let mut encoded = String::with_capacity(reason.len());
+
+    for byte in reason.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~') {
+            encoded.push(char::from(byte));
+        } else {
+            encoded.push_str(&format!("%{byte:02X}"));
+        }
+    }
+
+    encoded
+}
+
+/// Append the boundary and headers of a part to a multipart body.
+fn push_part_header(bytes: &mut Vec<u8>, boundary: &str, disposition: &str, content_type: &str) {
+    if !bytes.is_empty() {
+        bytes.extend_from_slice(b"\r\n");
+    }
+
+    let header = format!(
+        "--{boundary}\r\nContent-Disposition: form-data; {disposition}\r\nContent-Type: {content_type}\r\n\r\n"
+    );
+    bytes.extend_from_slice(header.as_bytes());
+}
+
+/// Replace the characters of a filename that Discord doesn't keep with
+/// underscores.
+fn sanitize_filename(filename: &str) -> String {
+    let sanitized = filename
+        .chars()
+        .map(|character| {
+            if character.is_ascii_alphanumeric() || matches!(character, '-' | '.' | '_') {
+                character
+            } else {
+                '_'
+            }
+        })
+        .collect::<String>();
+
+    if sanitized.trim_matches('.').is_empty() {
+        "file".to_owned()
+    } else {
+        sanitized
+    }
+}
+
+/// Split the data of a message into the data of multiple messages if its
+/// content is longer than a message may be.
+///
+/// Refer to [`FollowupClient::create_followups`] for how the data is split.
+fn split(data: &InteractionResponseData) -> Vec<InteractionResponseData> {
+    let Some(content) = &data.content else {
+        return vec![data.clone()];
+    };
+
+    let mut chunks = split_content(content, MESSAGE_CONTENT_LENGTH);
+    let last = chunks.pop();
+
+    chunks
+        .into_iter()
+        .map(|chunk| InteractionResponseData {
+            allowed_mentions: data.allowed_mentions.clone(),
+            content: Some(chunk),
+            flags: data.flags,
+            ..InteractionResponseData::default()
+        })
+        .chain(last.map(|chunk| InteractionResponseData {
+            content: Some(chunk),
+            ..data.clone()
+        }))
+        .collect()
+}
+
+/// Split content into chunks of at most `limit` characters.
+fn split_content(content: &str, limit: usize) -> Vec<String> {
+    // Room for closing a code block with a newline and a fence.
+    let close_length = CODE_FENCE.len() + 1;
+
+    let mut chunks = Vec::new();
+    let mut fence = None::<String>;
+    let mut rest = content;
+
+    loop {
+        let mut chunk = fence.take().map(|line| line + "\n").unwrap_or_default();
+        let prefix_length = chunk.chars().count();
+
+        if prefix_length + rest.chars().count() <= limit {
+            chunk.push_str(rest);
+            chunks.push(chunk);
+
+            break;
+        }
+
+        let available = limit.saturating_sub(prefix_length + close_length).max(1);
+        let end = rest
+            .char_indices()
+            .nth(available)
+            .map_or(rest.len(), |(index, _)| index);
+        let window = &rest[..end];
+        let (split, separator) = match window.rfind('\n').or_else(|| window.rfind(' ')) {
+            Some(index) if index > 0 => (index, 1),
+            _ => (end, 0),
+        };
+
+        chunk.push_str(&rest[..split]);
+        rest = &rest[split + separator..];
+
+        fence = open_fence(&chunk).map(str::to_owned);
+
+        if fence.is_some() {
+            chunk.push('\n');
+            chunk.push_str(CODE_FENCE);
+        }
+
+        chunks.push(chunk);
+    }
+
+    chunks
+}
+
+/// Opening line of the code block left open at the end of the content, if
+/// any.
+fn open_fence(content: &str) -> Option<&str> {
+    let mut open = None;
+
+    for line in content.lines() {
+        let trimmed = line.trim();
+
+        let Some(after) = trimmed.strip_prefix(CODE_FENCE) else {
+            continue;
+        };
+
+        if open.is_some() {
+            open = None;
+        // Lines with a closing fence after the opening one are inline blocks.
+        } else if !after.contains(CODE_FENCE) {
+            open = Some(trimmed);
+        }
+    }
+
+    open
+}
+
+/// Send a request to Discord's REST API with additional headers, returning the
+/// response body if the response was successful.
+///
+/// Rate limits are tracked by the bucket Discord responds with for the route
+/// of the request and its major parameters, or by the route until the bucket
+/// is known, unless Discord responds with a global rate limit.
+pub(crate) async fn send(
+    method: Method,
+    path: &str,
+    body: Option<Body>,
+    extra_headers: &[(&str, &str)],
+    delay_rate_limited: bool,
+) -> Result<Vec<u8>, RestError> {
+    let route = Route::new(&method, path);
+    let now = Date::now().as_millis();
+    let limited_until = RATE_LIMITS.with(|limits| {
+        let limits = limits.borrow();
+
+        limits.until(&limits.key(&route))
+    });
+
+    if limited_until > now {
+        let retry_after = Duration::from_millis(limited_until - now);
+
+        if !delay_rate_limited {
+            return Err(RestError {
+                kind: RestErrorType::RateLimited { retry_after },
+                source: None,
+            });
+        }
+
+        Delay::from(retry_after).await;
+    }
+
+    let mut headers = Headers::new();
+    headers
+        .set("User-Agent", USER_AGENT)
+        .map_err(RestError::requesting)?;
+
+    for (name, value) in extra_headers {
+        headers.set(name, value).map_err(RestError::requesting)?;
+    }
+
+    let mut init = RequestInit::new();
+
+    if let Some(body) = body {
+        headers
+            .set("Content-Type", &body.content_type)
+            .map_err(RestError::requesting)?;
+        init.with_body(Some(body.value));
+    }
+
+    init.with_method(method).with_headers(headers);
+
+    let url = [API_BASE, path].concat();
+    let request = Request::new_with_init(&url, &init).map_err(RestError::requesting)?;
+    let mut response = Fetch::Request(request)
+        .send()
+        .await
+        .map_err(RestError::requesting)?;
+    let status = response.status_code();
+    let header = |name: &str| response.headers().get(name).ok().flatten();
+
+    if let Some(bucket) = header("X-RateLimit-Bucket") {
+        RATE_LIMITS.with(|limits| limits.borrow_mut().bucket(&route, &bucket));
+    }
+
+    if let Some((reset_after, global)) = reported_limit(status, header) {
+        let until = Date::now()
+            .as_millis()
+            .saturating_add(duration_millis(reset_after));
+
+        RATE_LIMITS.with(|limits| {
+            let mut limits = limits.borrow_mut();
+            let key = (!global).then(|| limits.key(&route));
+            limits.limit(key, until, now);
+        });
+
+        if status == 429 {
+            return Err(RestError {
+                kind: RestErrorType::RateLimited {
+                    retry_after: reset_after,
+                },
+                source: None,
+            });
+        }
+    }
+
+    let body = response.bytes().await.map_err(RestError::requesting)?;
+
+    if !(200..300).contains(&status) {
+        return Err(RestError {
+            kind: RestErrorType::Status { body, status },
+            source: None,
+        });
+    }
+
+    Ok(body)
+}
+
+/// Rate limit a response reports, as the duration until it resets and
+/// whether it is global, if the response was rate limited or exhausted its
+/// bucket.
+fn reported_limit(
+    status: u16,
+    header: impl Fn(&str) -> Option<String>,
+) -> Option<(Duration, bool)> {
+    if status != 429 && header("X-RateLimit-Remaining").as_deref() != Some("0") {
+        return None;
+    }
+
+    let reset_after = header("X-RateLimit-Reset-After")
+        .or_else(|| header("Retry-After"))
+        .and_then(|value| value.parse().ok())
+        .and_then(|seconds| Duration::try_from_secs_f64(seconds).ok())
+        .unwrap_or_default();
+    let global = header("X-RateLimit-Global").as_deref() == Some("true")
+        || header("X-RateLimit-Scope").as_deref() == Some("global");
+
+    Some((reset_after, global))
+}
+
+/// Deserialize the body of a response.
+pub(crate) fn deserialize<T: DeserializeOwned>(body: Vec<u8>) -> Result<T, RestError> {
+    serde_json::from_slice(&body).map_err(|source| RestError {
+        kind: RestErrorType::Deserializing { body },
+        source: Some(Box::new(source)),
+    })
+}
+
+/// Number of whole milliseconds in a duration, saturating at [`u64::MAX`].
+pub(crate) fn duration_millis(duration: Duration) -> u64 {
+    duration.as_millis().try_into().unwrap_or(u64::MAX)
+}
+
+/// Defer the response to an interaction and run a future following up on it
+/// after the deferred response has been sent.
+///
+/// Returns the deferred response to respond to the interaction request with,
+/// and schedules the future created by `f` with [`Context::wait_until`],
+/// handing it a [`FollowupClient`] for the interaction. The type of deferred
+/// response is selected with [`Deferral::Auto`]: message components are
+/// deferred as an update to their message, and other interactions as a
+/// message to be sent later.
+///
+/// ```ignore
+/// use twilight_cloudflare_workers::rest;
+///
+/// let interaction = twilight_cloudflare_workers::request(&mut req, &public_key).await?;
+///
+/// return Ok(rest::defer_then(&ctx, &interaction, |client| async move {
+///     let data = InteractionResponseDataBuilder::new()
+///         .content(run_slow_task().await)
+///         .build();
+///
+///     if let Err(source) = client.update_response(&data).await {
+///         console_error!("failed to update response: {source}");
+///     }
+/// }));
+/// ```
+pub fn defer_then<F, Fut>(ctx: &Context, interaction: &Interaction, f: F) -> Response
+where
+    F: FnOnce(FollowupClient) -> Fut,
+    Fut: Future<Output = ()> + 'static,
+{
+    defer_with(ctx, interaction, Deferral::Auto, f)
+}
+
+/// Defer the response to an interaction with a type of deferred response and
+/// run a future following up on it after the deferred response has been sent.
+///
+/// Refer to [`defer_then`] for more information.
+///
+/// ```ignore
+/// use twilight_cloudflare_workers::{responses::Deferral, rest};
+///
+/// // Reply to a button with a new message instead of updating its message.
+/// return Ok(rest::defer_with(&ctx, &interaction, Deferral::Message, |client| async move {
+///     // ..
+/// }));
+/// ```
+pub fn defer_with<F, Fut>(
+    ctx: &Context,
+    interaction: &Interaction,
+    deferral: Deferral,
+    f: F,
+) -> Response
+where
+    F: FnOnce(FollowupClient) -> Fut,
+    Fut: Future<Output = ()> + 'static,
+{
+    ctx.wait_until(f(FollowupClient::from_interaction(interaction)));
+
+    deferred_response(interaction, deferral)
+}
+
+/// Create a response to an interaction with a file whose contents are
+/// streamed, such as a large file proxied from R2 or Discord's CDN, so that
+/// the file is never held in memory in full.
+///
+/// The contents of the file are the attachment's data followed by the chunks
+/// of the stream, so the attachment is usually created with no data. Unlike
+/// other multipart bodies, the stream can't be searched for the boundary
+/// between the parts, so the boundary is extended with random characters.
+///
+/// ```ignore
+/// use twilight_cloudflare_workers::rest::{self, Attachment};
+///
+/// let object = bucket.get("report.csv").execute().await?.unwrap();
+/// let stream = object.body().unwrap().stream()?;
+///
+/// return Ok(rest::streamed_response(
+///     &InteractionResponse {
+///         kind: InteractionResponseType::ChannelMessageWithSource,
+///         data: None,
+///     },
+///     &Attachment::new("report.csv", Vec::new()).content_type("text/csv"),
+///     stream,
+/// )?);
+/// ```
+///
+/// # Errors
+///
+/// Returns an error of type [`Requesting`] if the streamed response could not
+/// be created.
+///
+/// Returns an error of type [`Serializing`] if the response could not be
+/// serialized.
+///
+/// [`Requesting`]: RestErrorType::Requesting
+/// [`Serializing`]: RestErrorType::Serializing
+pub fn streamed_response<S>(
+    response: &InteractionResponse,
+    attachment: &Attachment,
+    stream: S,
+) -> Result<Response, RestError>
+where
+    S: Stream<Item = Result<Vec<u8>, worker::Error>> + 'static,
+{
+    let mut response = response.clone();
+    response
+        .data
+        .get_or_insert_with(InteractionResponseData::default)
+        .attachments
+        .get_or_insert_with(Vec::new)
+        .push(AttachmentPayload {
+            description: attachment.description.clone(),
+            file: Vec::new(),
+            filename: attachment.filename(),
+            id: 0,
+        });
+
+    let payload = serde_json::to_vec(&response).map_err(RestError::serializing)?;
+
+    let mut boundary = format!(
+        "{MULTIPART_BOUNDARY}-{:016x}{:016x}",
+        Math::random().to_bits(),
+        Math::random().to_bits()
+    );
+
+    while contains(&payload, boundary.as_bytes()) || contains(&attachment.data, boundary.as_bytes())
+    {
+        boundary.push('-');
+    }
+
+    let mut head = Vec::new();
+    push_part_header(
+        &mut head,
+        &boundary,
+        "name=\"payload_json\"",
+        "application/json",
+    );
+    head.extend_from_slice(&payload);
+    push_part_header(
+        &mut head,
+        &boundary,
+        &format!("name=\"files[0]\"; filename=\"{}\"", attachment.filename()),
+        attachment
+            .content_type
+            .as_deref()
+            .unwrap_or("application/octet-stream"),
+    );
+    head.extend_from_slice(&attachment.data);
+    let tail = format!("\r\n--{boundary}--\r\n").into_bytes();
+
+    let body = stream::once(future::ready(Ok(head)))
+        .chain(stream)
+        .chain(stream::once(future::ready(Ok(tail))));
+    let mut response = Response::from_stream(body).map_err(RestError::requesting)?;
+    response
+        .headers_mut()
+        .set(
+            "Content-Type",
+            &format!("multipart/form-data; boundary={boundary}"),
+        )
+        .map_err(RestError::requesting)?;
+
+    Ok(response)
+}
+
+/// Serialize an interaction response with files attached into the multipart
+/// body and content type of a response, such as for composing a worker
+/// response with other headers or forwarding it to another Worker.
+///
+/// Responses without data are given data to attach the files to.
+///
+/// # Errors
+///
+/// Returns an error of type [`Serializing`] if the response could not be
+/// serialized.
+///
+/// [`Serializing`]: RestErrorType::Serializing
+pub fn response_bytes_with_attachments(
+    response: &InteractionResponse,
+    attachments: &[Attachment],
+) -> Result<ResponseBytes, RestError> {
+    let data = with_attachments(
+        response
+            .data
+            .as_ref()
+            .unwrap_or(&InteractionResponseData::default()),
+        attachments,
+    );
+
+    multipart(
+        &InteractionResponse {
+            kind: response.kind,
+            data: Some(data),
+        },
+        attachments,
+    )
+}
+
+/// Interaction callback and the resource it created, returned when
+/// responding to an interaction with
+/// [`FollowupClient::callback_with_response`].
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct InteractionCallbackResponse {
+    /// Interaction the callback was for.
+    pub interaction: InteractionCallback,
+    /// Resource created by the callback, if any.
+    #[serde(default)]
+    pub resource: Option<InteractionCallbackResource>,
+}
+
+/// Interaction a callback was for.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+pub struct InteractionCallback {
+    /// ID of the interaction.
+    pub id: Id<InteractionMarker>,
+    /// Type of interaction.
+    #[serde(rename = "type")]
+    pub kind: InteractionType,
+    /// Whether the message created by the callback is ephemeral.
+    #[serde(default)]
+    pub response_message_ephemeral: Option<bool>,
+    /// ID of the message created by the callback.
+    #[serde(default)]
+    pub response_message_id: Option<Id<MessageMarker>>,
+    /// Whether the message created by the callback is in a loading state.
+    #[serde(default)]
+    pub response_message_loading: Option<bool>,
+}
+
+/// Resource created by an interaction callback.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct InteractionCallbackResource {
+    /// Type of response the callback was.
+    #[serde(rename = "type")]
+    pub kind: InteractionResponseType,
+    /// Message created or updated by the callback.
+    #[serde(default)]
+    pub message: Option<Message>,
+}
+
+/// Fields of a scheduled event to create or update with a [`BotClient`].
+///
+/// Fields that aren't set are left out of the request, leaving them as they
+/// are when updating an event.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize)]
+pub struct ScheduledEventFields {
+    /// ID of the stage or voice channel the event is in, required for events
+    /// in them.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub channel_id: Option<Id<ChannelMarker>>,
+    /// Description of the event.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Metadata of the event's entity, whose location is required for
+    /// external events.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entity_metadata: Option<EntityMetadata>,
+    /// Type of the event's entity.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entity_type: Option<EntityType>,
+    /// Cover image of the event as a data URI, such as from
+    /// [`Attachment::data_uri`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image: Option<String>,
+    /// Name of the event.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Privacy level of the event.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub privacy_level: Option<PrivacyLevel>,
+    /// Time the event ends at, required for external events.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scheduled_end_time: Option<Timestamp>,
+    /// Time the event starts at.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scheduled_start_time: Option<Timestamp>,
+    /// Status of the event, such as to start or end it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<Status>,
+}
+
+/// Request made to Discord's REST API, such as by a [`BotClient`] or
+/// [`FollowupClient`], could not be completed.
+#[allow(clippy::module_name_repetitions)]
+#[derive(Debug)]
+pub struct RestError {
+    kind: RestErrorType,
+    source: Option<Box<dyn Error>>,
+}
+
+impl RestError {
+    /// Immutable reference to the type of error that occurred.
+    #[must_use = "retrieving the type has no effect if left unused"]
+    pub const fn kind(&self) -> &RestErrorType {
+        &self.kind
+    }
+
+    /// Consume the error, returning the source error if there is any.
+    #[must_use = "consuming the error and retrieving the source has no effect if left unused"]
+    pub fn into_source(self) -> Option<Box<dyn Error>> {
+        self.source
+    }
+
+    /// Consume the error, returning the owned error type and the source error.
+    #[must_use = "consuming the error into its parts has no effect if left unused"]
+    pub fn into_parts(self) -> (RestErrorType, Option<Box<dyn Error>>) {
+        (self.kind, self.source)
+    }
+
+    /// JSON error code of the body Discord responded with, if it responded
+    /// with an unsuccessful status code.
+    fn code(&self) -> Option<u64> {
+        /// Body of an unsuccessful response.
+        #[derive(Deserialize)]
+        struct ErrorBody {
+            code: u64,
+        }
+
+        let RestErrorType::Status { body, .. } = &self.kind else {
+            return None;
+        };
+
+        serde_json::from_slice::<ErrorBody>(body)
+            .ok()
+            .map(|body| body.code)
+    }
+
+    /// Create an error of type [`RestErrorType::Requesting`].
+    fn requesting(source: worker::Error) -> Self {
+        Self {
+            kind: RestErrorType::Requesting,
+            source: Some(Box::new(source)),
+        }
+    }
+
+    /// Create an error of type [`RestErrorType::Serializing`].
+    fn serializing(source: serde_json::Error) -> Self {
+        Self {
+            kind: RestErrorType::Serializing,
+            source: Some(Box::new(source)),
+        }
+    }
+}
+
+impl Display for RestError {
+    #[cfg(feature = "minimal-errors")]
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.write_str("error ")?;
+
+        Display::fmt(&self.kind.code(), f)
+    }
+
+    #[cfg(not(feature = "minimal-errors"))]
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match &self.kind {
+            RestErrorType::Deserializing { body } => {
+                f.write_str("failed to deserialize response body: ")?;
+
+                match str::from_utf8(body) {
+                    Ok(text) => f.write_str(text),
+                    Err(_) => write!(f, "{body:?}"),
+                }
+            }
+            RestErrorType::RateLimited { retry_after } => {
+                f.write_str("request was rate limited, retry after ")?;
+                Display::fmt(&retry_after.as_millis(), f)?;
+
+                f.write_str("ms")
+            }
+            RestErrorType::Requesting => f.write_str("failed to send request"),
+            RestErrorType::Serializing => f.write_str("failed to serialize request body"),
+            RestErrorType::Status { status, .. } => {
+                f.write_str("request responded with status code ")?;
+
+                Display::fmt(status, f)
+            }
+            RestErrorType::TokenExpired => f.write_str("interaction token has expired"),
+        }
+    }
+}
+
+impl Error for RestError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.source
+            .as_ref()
+            .map(|source| &**source as &(dyn Error + 'static))
+    }
+}
+
+/// Type of [`RestError`] that occurred.
+#[allow(clippy::module_name_repetitions)]
+#[derive(Debug)]
+pub enum RestErrorType {
+    /// Failed to deserialize the response body.
+    Deserializing {
+        /// Body of the response.
+        body: Vec<u8>,
+    },
+    /// Request was rate limited by Discord, or would have been and requests
+    /// aren't delayed.
+    RateLimited {
+        /// Duration to wait for before the rate limit resets.
+        retry_after: Duration,
+    },
+    /// Failed to send a request or receive its response.
+    Requesting,
+    /// Failed to serialize the request body.
+    Serializing,
+    /// Discord responded with an unsuccessful status code.
+    Status {
+        /// Body of the response, containing details about the error.
+        body: Vec<u8>,
+        /// Status code of the response.
+        status: u16,
+    },
+    /// Token of the interaction has expired.
+    TokenExpired,
+}
+
+impl RestErrorType {
+    /// Numeric code of the type of error, which errors are displayed as with
+    /// the `minimal-errors` feature.
+    ///
+    /// Refer to [`ProcessRequestErrorType::code`] for more information.
+    ///
+    /// [`ProcessRequestErrorType::code`]: crate::ProcessRequestErrorType::code
+    #[must_use = "retrieving the code is not useful on its own"]
+    pub const fn code(&self) -> u16 {
+        match self {
+            Self::Deserializing { .. } => 18,
+            Self::RateLimited { .. } => 19,
+            Self::Requesting => 20,
+            Self::Serializing => 21,
+            Self::Status { .. } => 22,
+            Self::TokenExpired => 23,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        base64, encode_reason, multipart, open_fence, reported_limit, sanitize_filename,
+        split_content, Attachment, FollowupClient, RateLimits, Route, CODE_FENCE, DISCORD_EPOCH,
+        MESSAGE_CONTENT_LENGTH, MULTIPART_BOUNDARY, TOKEN_LIFETIME,
+    };
+    use core::time::Duration;
+    use serde_json::json;
+    use twilight_model::id::Id;
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+    use worker::Method;
+
+    const fn rate_limits() -> RateLimits {
+        RateLimits {
+            buckets: Vec::new(),
+            global: 0,
+            keys: Vec::new(),
+        }
+    }
+
+    /// Assert that every chunk fits in a message.
+    fn assert_fits(chunks: &[String]) {
+        for chunk in chunks {
+            assert!(
+                chunk.chars().count() <= MESSAGE_CONTENT_LENGTH,
+                "chunk of {} characters",
+                chunk.chars().count(),
+            );
+        }
+    }
+
+    #[test]
+    fn split_content_exact_limit() {
+        let content = "a".repeat(MESSAGE_CONTENT_LENGTH);
+
+        assert_eq!(
+            split_content(&content, MESSAGE_CONTENT_LENGTH),
+            [content.clone()]
+        );
+
+        let content = "a".repeat(MESSAGE_CONTENT_LENGTH + 1);
+        let chunks = split_content(&content, MESSAGE_CONTENT_LENGTH);
+
+        assert_eq!(chunks.len(), 2);
+        assert_fits(&chunks);
+        assert_eq!(chunks.concat(), content);
+    }
+
+    #[test]
+    fn split_content_lines() {
+        let line = "a".repeat(99);
+        let content = vec![line.as_str(); 30].join("\n");
+        let chunks = split_content(&content, MESSAGE_CONTENT_LENGTH);
+
+        assert_eq!(chunks.len(), 2);
+        assert_fits(&chunks);
+        // The newline that was split on is dropped.
+        assert_eq!(chunks.join("\n"), content);
+        assert!(chunks.iter().all(|chunk| chunk.starts_with('a')));
+    }
+
+    #[test]
+    fn split_content_reopens_fence() {
+        let line = "let x = 1;";
+        let code = vec![line; 300].join("\n");
+        let content = format!("Output:\n```rust\n{code}\n```\nDone.");
+        let chunks = split_content(&content, MESSAGE_CONTENT_LENGTH);
+
+        assert!(chunks.len() >= 2);
+        assert_fits(&chunks);
+
+        let first = &chunks[0];
+        assert!(first.starts_with("Output:\n```rust\n"));
+        assert!(first.ends_with(&format!("\n{CODE_FENCE}")));
+
+        for chunk in &chunks[1..] {
+            assert!(chunk.starts_with("```rust\n"), "{chunk:?}");
+        }
+
+        assert!(chunks.last().unwrap().ends_with("```\nDone."));
+        assert!(chunks.iter().all(|chunk| open_fence(chunk).is_none()));
+    }
+
+    #[test]
+    fn split_content_multi_byte_boundary() {
+        let content = "é".repeat(MESSAGE_CONTENT_LENGTH + 1);
+        let chunks = split_content(&content, MESSAGE_CONTENT_LENGTH);
+
+        assert_eq!(chunks.len(), 2);
+        assert_fits(&chunks);
+        assert_eq!(chunks.concat(), content);
+
+        // Four byte characters straddling the byte offset of the limit.
+        let content = format!("{}{}", "a".repeat(1998), "🦀".repeat(10));
+        let chunks = split_content(&content, MESSAGE_CONTENT_LENGTH);
+
+        assert_fits(&chunks);
+        assert_eq!(chunks.concat(), content);
+    }
+
+    #[test]
+    fn split_content_long_line() {
+        let content = format!("short\n{}", "b".repeat(MESSAGE_CONTENT_LENGTH * 2));
+        let chunks = split_content(&content, MESSAGE_CONTENT_LENGTH);
+
+        assert_eq!(chunks[0], "short");
+        assert_fits(&chunks);
+        assert_eq!(chunks[1..].concat(), "b".repeat(MESSAGE_CONTENT_LENGTH * 2));
+    }
+
+    #[test]
+    fn open_fence_blocks() {
+        assert_eq!(open_fence("text"), None);
+        assert_eq!(open_fence("```\ncode"), Some("```"));
+        assert_eq!(open_fence("text\n  ```rust\ncode"), Some("```rust"));
+        assert_eq!(open_fence("```rust\ncode\n```"), None);
+        assert_eq!(open_fence("```rust\ncode\n```\n```\nmore"), Some("```"));
+        // Inline blocks don't leave a block open.
+        assert_eq!(open_fence("```inline```\ntext"), None);
+        assert_eq!(open_fence("```\ncode ``` not a fence"), Some("```"));
+    }
+
+    #[test]
+    fn multipart_layout() {
+        let attachments = [Attachment::new("a.txt", b"hello".to_vec()).content_type("text/plain")];
+        let bytes = multipart(&json!({ "content": "hi" }), &attachments).unwrap();
+
+        assert_eq!(
+            bytes.content_type,
+            format!("multipart/form-data; boundary={MULTIPART_BOUNDARY}")
+        );
+        assert_eq!(
+            String::from_utf8(bytes.body).unwrap(),
+            format!(
+                "--{MULTIPART_BOUNDARY}\r\n\
+                 Content-Disposition: form-data; name=\"payload_json\"\r\n\
+                 Content-Type: application/json\r\n\r\n\
+                 {{\"content\":\"hi\"}}\r\n\
+                 --{MULTIPART_BOUNDARY}\r\n\
+                 Content-Disposition: form-data; name=\"files[0]\"; filename=\"a.txt\"\r\n\
+                 Content-Type: text/plain\r\n\r\n\
+                 hello\r\n\
+                 --{MULTIPART_BOUNDARY}--\r\n"
+            )
+        );
+    }
+
+    #[test]
+    fn multipart_boundary_extended() {
+        let data = format!("x{MULTIPART_BOUNDARY}-y").into_bytes();
+        let bytes = multipart(&json!({}), &[Attachment::new("a", data)]).unwrap();
+        let boundary = format!("{MULTIPART_BOUNDARY}--");
+
+        assert_eq!(
+            bytes.content_type,
+            format!("multipart/form-data; boundary={boundary}")
+        );
+        assert!(String::from_utf8(bytes.body)
+            .unwrap()
+            .ends_with(&format!("\r\n--{boundary}--\r\n")));
+
+        let bytes = multipart(&json!({ "content": MULTIPART_BOUNDARY }), &[]).unwrap();
+
+        assert_eq!(
+            bytes.content_type,
+            format!("multipart/form-data; boundary={MULTIPART_BOUNDARY}-")
+        );
+    }
+
+    #[test]
+    fn sanitize_hostile_filenames() {
+        assert_eq!(sanitize_filename("image.png"), "image.png");
+        assert_eq!(sanitize_filename("a\"b.png"), "a_b.png");
+        assert_eq!(
+            sanitize_filename("x.txt\"\r\nContent-Type: text/html"),
+            "x.txt___Content-Type__text_html"
+        );
+        assert_eq!(sanitize_filename("../../etc/passwd"), ".._.._etc_passwd");
+        assert_eq!(sanitize_filename("C:\\Windows\\x.exe"), "C__Windows_x.exe");
+        assert_eq!(sanitize_filename("résumé.pdf"), "r_sum_.pdf");
+        assert_eq!(sanitize_filename(".."), "file");
+        assert_eq!(sanitize_filename(""), "file");
+
+        let attachment = Attachment::new("my \"cat\".png", Vec::new()).spoiler(true);
+        assert_eq!(attachment.filename(), "SPOILER_my__cat_.png");
+
+        let bytes = multipart(&json!({}), &[attachment]).unwrap();
+        let body = String::from_utf8(bytes.body).unwrap();
+        assert!(body.contains("name=\"files[0]\"; filename=\"SPOILER_my__cat_.png\"\r\n"));
+    }
+
+    #[test]
+    fn base64_vectors() {
+        // Test vectors from RFC 4648.
+        for (input, output) in [
+            ("", ""),
+            ("f", "Zg=="),
+            ("fo", "Zm8="),
+            ("foo", "Zm9v"),
+            ("foob", "Zm9vYg=="),
+            ("fooba", "Zm9vYmE="),
+            ("foobar", "Zm9vYmFy"),
+        ] {
+            assert_eq!(base64(input.as_bytes()), output, "{input:?}");
+        }
+
+        assert_eq!(base64(&[0xFF, 0xFE]), "//4=");
+        assert_eq!(base64(&[0xFB, 0xEF, 0xBE]), "++++");
+        assert_eq!(
+            Attachment::new("a.png", b"png".to_vec())
+                .content_type("image/png")
+                .data_uri(),
+            "data:image/png;base64,cG5n"
+        );
+    }
+
+    #[test]
+    fn encode_reason_percent() {
+        assert_eq!(encode_reason("a-b.c_d~e"), "a-b.c_d~e");
+        assert_eq!(encode_reason("Spam & abuse"), "Spam%20%26%20abuse");
+        assert_eq!(
+            encode_reason("line\r\nX-Header: 1"),
+            "line%0D%0AX-Header%3A%201"
+        );
+        assert_eq!(encode_reason("café 🦀"), "caf%C3%A9%20%F0%9F%A6%80");
+        assert_eq!(encode_reason("100%"), "100%25");
+    }
+
+    /// Time in milliseconds since the Unix epoch the interaction of the token
+    /// was created at.
+    const CREATED_AT: u64 = 1_700_000_000_000;
+
+    fn client() -> FollowupClient {
+        let interaction_id = Id::new((CREATED_AT - DISCORD_EPOCH) << 22);
+
+        FollowupClient::new(Id::new(1), "token").interaction_id(interaction_id)
+    }
+
+    #[test]
+    fn token_expires_after_lifetime() {
+        let client = client();
+
+        assert_eq!(Some(CREATED_AT + TOKEN_LIFETIME), client.expires_at());
+        assert!(!client.is_expired_at(CREATED_AT));
+        assert!(!client.is_expired_at(CREATED_AT + TOKEN_LIFETIME - 1));
+        assert!(client.is_expired_at(CREATED_AT + TOKEN_LIFETIME));
+        assert!(client.is_expired_at(u64::MAX));
+    }
+
+    #[test]
+    fn token_not_expired_before_creation() {
+        assert!(!client().is_expired_at(CREATED_AT - 60_000));
+        assert!(!client().is_expired_at(0));
+    }
+
+    #[test]
+    fn token_expiry_unknown() {
+        let client = FollowupClient::new(Id::new(1), "token");
+
+        assert_eq!(None, client.expires_at());
+        assert!(!client.is_expired_at(u64::MAX));
+    }
+
+    #[test]
+    fn route_major_parameters() {
+        let route = Route::new(&Method::Post, "/channels/123/messages");
+        assert_eq!("/123", route.major);
+        assert_eq!("POST /channels/:major/messages", route.template);
+
+        let route = Route::new(&Method::Put, "/guilds/1/members/2/roles/3");
+        assert_eq!("/1", route.major);
+        assert_eq!("PUT /guilds/:major/members/:id/roles/:id", route.template);
+
+        let other = Route::new(&Method::Put, "/guilds/4/members/5/roles/6");
+        assert_eq!(route.template, other.template);
+        assert_ne!(route.major, other.major);
+    }
+
+    #[test]
+    fn route_templates_ids() {
+        let route = Route::new(&Method::Get, "/applications/1/entitlements?user_id=2");
+        assert_eq!("", route.major);
+        assert_eq!("GET /applications/:id/entitlements", route.template);
+
+        let route = Route::new(&Method::Post, "/users/@me/channels");
+        assert_eq!("", route.major);
+        assert_eq!("POST /users/@me/channels", route.template);
+
+        // Segments that aren't entirely digits aren't IDs.
+        let route = Route::new(&Method::Delete, "/channels/1/messages/2/reactions/a1/@me");
+        assert_eq!(
+            "DELETE /channels/:major/messages/:id/reactions/a1/@me",
+            route.template
+        );
+    }
+
+    #[test]
+    fn route_tokens() {
+        let route = Route::new(&Method::Patch, "/webhooks/1/token/messages/@original");
+        assert_eq!("/1/token", route.major);
+        assert_eq!(
+            "PATCH /webhooks/:major/:major/messages/@original",
+            route.template
+        );
+
+        let route = Route::new(&Method::Post, "/interactions/2/token/callback");
+        assert_eq!("/2/token", route.major);
+        assert_eq!("POST /interactions/:major/:major/callback", route.template);
+
+        let other = Route::new(&Method::Post, "/interactions/2/other/callback");
+        assert_ne!(route.major, other.major);
+    }
+
+    #[test]
+    fn rate_limit_keys_by_bucket() {
+        let mut limits = rate_limits();
+        let route = Route::new(&Method::Post, "/channels/1/messages");
+        assert_eq!("POST /channels/:major/messages /1", limits.key(&route));
+
+        limits.bucket(&route, "abc");
+        assert_eq!("abc /1", limits.key(&route));
+        assert_eq!(
+            "abc /2",
+            limits.key(&Route::new(&Method::Post, "/channels/2/messages"))
+        );
+
+        limits.bucket(&route, "def");
+        assert_eq!("def /1", limits.key(&route));
+        assert_eq!(1, limits.buckets.len());
+    }
+
+    #[test]
+    fn rate_limit_until() {
+        let mut limits = rate_limits();
+        assert_eq!(0, limits.until("a /1"));
+
+        limits.limit(Some("a /1".to_owned()), 1000, 0);
+        assert_eq!(1000, limits.until("a /1"));
+        assert_eq!(0, limits.until("a /2"));
+
+        // Resets replace the previous time of the key.
+        limits.limit(Some("a /1".to_owned()), 500, 0);
+        assert_eq!(500, limits.until("a /1"));
+        assert_eq!(1, limits.keys.len());
+
+        // Global rate limits apply to every key and only extend.
+        limits.limit(None, 2000, 0);
+        limits.limit(None, 1500, 0);
+        assert_eq!(2000, limits.until("a /1"));
+        assert_eq!(2000, limits.until("a /2"));
+    }
+
+    #[test]
+    fn rate_limit_reset_removed() {
+        let mut limits = rate_limits();
+        limits.limit(Some("a /1".to_owned()), 1000, 0);
+        limits.limit(Some("b /1".to_owned()), 3000, 0);
+
+        limits.limit(Some("c /1".to_owned()), 4000, 1000);
+        assert_eq!(
+            vec!["b /1", "c /1"],
+            limits
+                .keys
+                .iter()
+                .map(|(key, _)| key.as_str())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn reported_limit_remaining() {
+        let headers = |remaining: &'static str| {
+            move |name: &str| match name {
+                "X-RateLimit-Remaining" => Some(remaining.to_owned()),
+                "X-RateLimit-Reset-After" => Some("1.5".to_owned()),
+                _ => None,
+            }
+        };
+
+        assert_eq!(None, reported_limit(200, headers("1")));
+        assert_eq!(
+            Some((Duration::from_millis(1500), false)),
+            reported_limit(200, headers("0"))
+        );
+        assert_eq!(
+            Some((Duration::from_millis(1500), false)),
+            reported_limit(429, headers("1"))
+        );
+    }
+
+    #[test]
+    fn reported_limit_global() {
+        let header = |name: &str| match name {
+            "Retry-After" => Some("2".to_owned()),
+            "X-RateLimit-Global" => Some("true".to_owned()),
+            _ => None,
+        };
+        assert_eq!(
+            Some((Duration::from_secs(2), true)),
+            reported_limit(429, header)
+        );
+
+        let header = |name: &str| (name == "X-RateLimit-Scope").then(|| "global".to_owned());
+        assert_eq!(Some((Duration::ZERO, true)), reported_limit(429, header));
+
+        let header = |name: &str| (name == "Retry-After").then(|| "soon".to_owned());
+        assert_eq!(Some((Duration::ZERO, false)), reported_limit(429, header));
+    }
+}
@@ -0,0 +1,345 @@
+//! Self-role menus, select menus with which members toggle configured roles
+//! on themselves.
+//!
+//! Roles selected in the menu are added to the member and the menu's roles
+//! that aren't selected are removed, through the [`BotClient`]. The bot needs
+//! the `MANAGE_ROLES` permission and a role above the menu's roles.
+//!
+//! ```ignore
+//! use twilight_cloudflare_workers::role_menu::RoleMenu;
+//!
+//! const ROLES: &[(Id<RoleMarker>, &str)] = &[
+//!     (Id::new(1_000_000_000_000_000_001), "Announcements"),
+//!     (Id::new(1_000_000_000_000_000_002), "Events"),
+//! ];
+//!
+//! let menu = RoleMenu::new("roles", ROLES).placeholder("Pick your roles");
+//!
+//! // Posting the menu.
+//! let row = ActionRow {
+//!     components: menu.component(&[]).into_iter().collect(),
+//! };
+//!
+//! // Responding to selections in the menu.
+//! if let Some(response) = menu.handle(&client, &interaction).await? {
+//!     return Ok(twilight_cloudflare_workers::response(&response));
+//! }
+//! ```
+//!
+//! [`BotClient`]: crate::rest::BotClient
+
+use crate::{
+    accessors::InteractionExt,
+    rest::{BotClient, RestError},
+};
+use twilight_model::{
+    application::interaction::{Interaction, InteractionData},
+    channel::message::{
+        component::{SelectMenu, SelectMenuOption},
+        AllowedMentions, Component, MessageFlags,
+    },
+    http::interaction::{InteractionResponse, InteractionResponseData, InteractionResponseType},
+    id::{marker::RoleMarker, Id},
+};
+
+/// Reason for the audit log of roles toggled with a role menu.
+const REASON: &str = "Role menu";
+
+/// Maximum number of options in a select menu.
+const SELECT_MENU_OPTIONS_MAX: usize = 25;
+
+/// Select menu toggling configured roles.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[must_use = "role menus have no effect if unused"]
+pub struct RoleMenu<'a> {
+    custom_id: &'a str,
+    placeholder: Option<&'a str>,
+    roles: &'a [(Id<RoleMarker>, &'a str)],
+}
+
+impl<'a> RoleMenu<'a> {
+    /// Create a new role menu with the custom ID of its select menu and the
+    /// IDs and labels of its roles.
+    ///
+    /// Select menus have at most 25 options, so further roles are ignored.
+    pub const fn new(custom_id: &'a str, roles: &'a [(Id<RoleMarker>, &'a str)]) -> Self {
+        Self {
+            custom_id,
+            placeholder: None,
+            roles,
+        }
+    }
+
+    /// Set the placeholder shown when no roles are selected.
+    pub const fn placeholder(mut self, placeholder: &'a str) -> Self {
+        self.placeholder = Some(placeholder);
+
+        self
+    }
+
+    /// Select menu of the roles, with the roles of a member selected by
+    /// default, such as when sending the menu in an ephemeral response.
+    ///
+    /// Returns `None` if the menu has no roles, as Discord rejects select
+    /// menus without options.
+    #[must_use = "creating the component is not useful on its own"]
+    pub fn component(&self, selected: &[Id<RoleMarker>]) -> Option<Component> {
+        if self.roles.is_empty() {
+            return None;
+        }
+
+        let options = self
+            .roles()
+            .iter()
+            .map(|(id, label)| SelectMenuOption {
+                default: selected.contains(id),
+                description: None,
+                emoji: None,
+                label: (*label).to_owned(),
+                value: id.to_string(),
+            })
+            .collect::<Vec<_>>();
+
+        Some(Component::SelectMenu(SelectMenu {
+            custom_id: self.custom_id.to_owned(),
+            disabled: false,
+            max_values: options.len().try_into().ok(),
+            min_values: Some(0),
+            options,
+            placeholder: self.placeholder.map(str::to_owned),
+        }))
+    }
+
+    /// Toggle the roles of the member who selected roles in the menu,
+    /// returning an ephemeral response listing the roles added and removed.
+    ///
+    /// Returns `None` if the interaction isn't a selection in the menu, or
+    /// wasn't invoked by a guild member. Each toggled role is a request, so
+    /// menus with many roles may need to defer the response to respond within
+    /// the three seconds Discord allows.
+    ///
+    /// # Errors
+    ///
+    /// Refer to the documentation for [`BotClient::add_member_role`]. Roles
+    /// toggled before the error occurred are left toggled.
+    pub async fn handle(
+        &self,
+        client: &BotClient,
+        interaction: &Interaction,
+    ) -> Result<Option<InteractionResponse>, RestError> {
+        let (Some(InteractionData::MessageComponent(data)), Some(guild_id), Some(member)) = (
+            &interaction.data,
+            interaction.guild_id(),
+            &interaction.member,
+        ) else {
+            return Ok(None);
+        };
+
+        let Some(user) = &member.user else {
+            return Ok(None);
+        };
+
+        if data.custom_id != self.custom_id {
+            return Ok(None);
+        }
+
+        let (added, removed) = self.toggled(&member.roles, &data.values);
+
+        for id in &added {
+            client
+                .add_member_role(guild_id, user.id, *id, Some(REASON))
+                .await?;
+        }
+
+        for id in &removed {
+            client
+                .remove_member_role(guild_id, user.id, *id, Some(REASON))
+                .await?;
+        }
+
+        Ok(Some(InteractionResponse {
+            kind: InteractionResponseType::ChannelMessageWithSource,
+            data: Some(InteractionResponseData {
+                allowed_mentions: Some(AllowedMentions::default()),
+                content: Some(content(&added, &removed)),
+                flags: Some(MessageFlags::EPHEMERAL),
+                ..InteractionResponseData::default()
+            }),
+        }))
+    }
+
+    /// Roles of the menu, up to the number of options select menus have.
+    fn roles(&self) -> &'a [(Id<RoleMarker>, &'a str)] {
+        &self.roles[..self.roles.len().min(SELECT_MENU_OPTIONS_MAX)]
+    }
+
+    /// Roles of the menu to add to and remove from a member with roles, from
+    /// the values selected in the menu.
+    fn toggled(
+        &self,
+        current: &[Id<RoleMarker>],
+        values: &[String],
+    ) -> (Vec<Id<RoleMarker>>, Vec<Id<RoleMarker>>) {
+        let selected = values
+            .iter()
+            .filter_map(|value| value.parse().ok())
+            .collect::<Vec<Id<RoleMarker>>>();
+        let mut added = Vec::new();
+        let mut removed = Vec::new();
+
+        for (id, _) in self.roles() {
+            match (selected.contains(id), current.contains(id)) {
+                (true, false) => added.push(*id),
+                (false, true) => removed.push(*id),
+                _ => {}
+            }
+        }
+
+        (added, removed)
+    }
+}
+
+/// Content of the response listing the roles added and removed.
+fn content(added: &[Id<RoleMarker>], removed: &[Id<RoleMarker>]) -> String {
+    let mentions = |ids: &[Id<RoleMarker>]| {
+        ids.iter()
+            .map(|id| format!("<@&{id}>"))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    match (added.is_empty(), removed.is_empty()) {
+        (true, true) => "Your roles are unchanged.".to_owned(),
+        (false, true) => format!("Added {}.", mentions(added)),
+        (true, false) => format!("Removed {}.", mentions(removed)),
+        (false, false) => format!(
+            "Added {} and removed {}.",
+            mentions(added),
+            mentions(removed)
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{content, RoleMenu, SELECT_MENU_OPTIONS_MAX};
+    use twilight_model::{
+        channel::message::Component,
+        id::{marker::RoleMarker, Id},
+    };
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    const ROLES: &[(Id<RoleMarker>, &str)] = &[
+        (Id::new(1), "Announcements"),
+        (Id::new(2), "Events"),
+        (Id::new(3), "Giveaways"),
+    ];
+
+    fn values(ids: &[u64]) -> Vec<String> {
+        ids.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn component_selects_current_roles() {
+        let menu = RoleMenu::new("roles", ROLES).placeholder("Pick your roles");
+        let Some(Component::SelectMenu(select)) = menu.component(&[Id::new(2), Id::new(4)]) else {
+            panic!("menu with roles should have a select menu");
+        };
+
+        assert_eq!("roles", select.custom_id);
+        assert_eq!(Some("Pick your roles"), select.placeholder.as_deref());
+        assert_eq!(Some(0), select.min_values);
+        assert_eq!(Some(3), select.max_values);
+        assert_eq!(
+            vec![
+                ("1", "Announcements", false),
+                ("2", "Events", true),
+                ("3", "Giveaways", false)
+            ],
+            select
+                .options
+                .iter()
+                .map(|option| (option.value.as_str(), option.label.as_str(), option.default))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn component_without_roles() {
+        assert!(RoleMenu::new("roles", &[]).component(&[]).is_none());
+    }
+
+    #[test]
+    fn component_options_capped() {
+        let roles = (1..=30).map(|id| (Id::new(id), "Role")).collect::<Vec<_>>();
+        let Some(Component::SelectMenu(select)) =
+            RoleMenu::new("roles", &roles).component(&[Id::new(30)])
+        else {
+            panic!("menu with roles should have a select menu");
+        };
+
+        assert_eq!(SELECT_MENU_OPTIONS_MAX, select.options.len());
+        assert_eq!(Some(25), select.max_values);
+        assert_eq!("25", select.options[24].value);
+        assert!(select.options.iter().all(|option| !option.default));
+    }
+
+    #[test]
+    fn toggled_adds_and_removes() {
+        let menu = RoleMenu::new("roles", ROLES);
+
+        assert_eq!(
+            (vec![Id::new(3)], vec![Id::new(1)]),
+            menu.toggled(&[Id::new(1), Id::new(2)], &values(&[2, 3]))
+        );
+    }
+
+    #[test]
+    fn toggled_unchanged() {
+        let menu = RoleMenu::new("roles", ROLES);
+
+        assert_eq!(
+            (Vec::new(), Vec::new()),
+            menu.toggled(&[Id::new(1)], &values(&[1]))
+        );
+        assert_eq!((Vec::new(), Vec::new()), menu.toggled(&[], &[]));
+    }
+
+    #[test]
+    fn toggled_ignores_other_roles() {
+        let menu = RoleMenu::new("roles", ROLES);
+        let mut selected = values(&[4]);
+        selected.push("everyone".to_owned());
+
+        assert_eq!(
+            (Vec::new(), vec![Id::new(2)]),
+            menu.toggled(&[Id::new(2), Id::new(5)], &selected)
+        );
+    }
+
+    #[test]
+    fn toggled_ignores_roles_beyond_cap() {
+        let roles = (1..=30).map(|id| (Id::new(id), "Role")).collect::<Vec<_>>();
+        let menu = RoleMenu::new("roles", &roles);
+
+        assert_eq!(
+            (vec![Id::new(25)], Vec::new()),
+            menu.toggled(&[Id::new(30)], &values(&[25, 26]))
+        );
+    }
+
+    #[test]
+    fn content_lists_roles() {
+        assert_eq!("Your roles are unchanged.", content(&[], &[]));
+        assert_eq!(
+            "Added <@&1>, <@&2>.",
+            content(&[Id::new(1), Id::new(2)], &[])
+        );
+        assert_eq!("Removed <@&3>.", content(&[], &[Id::new(3)]));
+        assert_eq!(
+            "Added <@&1> and removed <@&3>.",
+            content(&[Id::new(1)], &[Id::new(3)])
+        );
+    }
+}
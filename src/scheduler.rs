@@ -0,0 +1,288 @@
+//! Payloads scheduled to be delivered at a time with Durable Object alarms,
+//! the building block of time-delayed behavior such as reminders or
+//! followups.
+//!
+//! Payloads are stored in the storage of a Durable Object with
+//! [`schedule_at`], which sets the object's alarm to the earliest payload,
+//! and the object's alarm handler delivers the payloads that are due to a
+//! callback with [`run_due`]. The callback has access to whatever the
+//! handler does, such as a `BotClient` created from the object's
+//! environment, and to the payload itself, which can hold an interaction
+//! token to follow up on.
+//!
+//! The module manages the object's alarm, so the object shouldn't set its
+//! alarm for anything else.
+//!
+//! ```ignore
+//! use twilight_cloudflare_workers::scheduler;
+//!
+//! #[derive(Deserialize, Serialize)]
+//! struct Announcement {
+//!     channel_id: Id<ChannelMarker>,
+//!     content: String,
+//! }
+//!
+//! #[durable_object]
+//! impl DurableObject for Scheduler {
+//!     // When an announcement is scheduled and forwarded to the object.
+//!     async fn fetch(&mut self, mut req: Request) -> Result<Response> {
+//!         let (when, announcement) = req.json::<(u64, Announcement)>().await?;
+//!         let id = scheduler::schedule_at(&mut self.state.storage(), when, &announcement).await?;
+//!
+//!         Response::ok(id)
+//!     }
+//!
+//!     async fn alarm(&mut self) -> Result<Response> {
+//!         let client = BotClient::new(&self.env.secret("DISCORD_TOKEN")?.to_string());
+//!
+//!         scheduler::run_due(&mut self.state.storage(), |scheduled: Scheduled<Announcement>| {
+//!             let client = &client;
+//!
+//!             async move {
+//!                 let data = InteractionResponseData {
+//!                     content: Some(scheduled.payload.content),
+//!                     ..InteractionResponseData::default()
+//!                 };
+//!
+//!                 if let Err(source) = client.create_message(scheduled.payload.channel_id, &data).await {
+//!                     console_error!("failed to announce: {source}");
+//!                 }
+//!             }
+//!         })
+//!         .await?;
+//!
+//!         Response::empty()
+//!     }
+//! }
+//! ```
+
+use core::{cell::Cell, future::Future, time::Duration};
+use serde::{de::DeserializeOwned, Serialize};
+use worker::{Date, ListOptions, Storage};
+
+/// Maximum number of payloads delivered by a single alarm, keeping within the
+/// subrequest limit of an invocation.
+const DUE_PER_ALARM: usize = 25;
+
+/// Prefix of the keys payloads are stored under, followed by their ID.
+const KEY_PREFIX: &str = "scheduled:";
+
+thread_local! {
+    /// Number of payloads scheduled in this isolate, distinguishing payloads
+    /// scheduled for the same time in the same millisecond.
+    static SEQUENCE: Cell<u32> = const { Cell::new(0) };
+}
+
+/// Payload that is due, delivered by [`run_due`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Scheduled<T> {
+    /// ID of the payload, as returned by [`schedule_at`].
+    pub id: String,
+    /// Payload that was scheduled.
+    pub payload: T,
+    /// Time in milliseconds since the Unix epoch the payload was scheduled
+    /// at.
+    pub scheduled_at: u64,
+}
+
+/// Schedule a payload to be delivered at a time in milliseconds since the
+/// Unix epoch, returning its ID to cancel it with.
+///
+/// The payload is serialized as JSON and stored until it is delivered. The
+/// alarm of the Durable Object the storage belongs to is set to the time if
+/// it isn't already set to an earlier time.
+///
+/// # Errors
+///
+/// Returns an error if the payload could not be serialized or stored, or the
+/// alarm could not be set.
+pub async fn schedule_at<T: Serialize>(
+    storage: &mut Storage,
+    when: u64,
+    payload: &T,
+) -> worker::Result<String> {
+    let sequence = SEQUENCE.with(|sequence| {
+        let current = sequence.get();
+        sequence.set(current.wrapping_add(1));
+
+        current
+    });
+    let id = id(when, Date::now().as_millis(), sequence);
+    let value = serde_json::to_string(payload).map_err(|source| source.to_string())?;
+    storage.put(&[KEY_PREFIX, &id].concat(), value).await?;
+
+    let alarm = storage.get_alarm().await?;
+
+    if alarm.map_or(true, |alarm| {
+        u64::try_from(alarm).map_or(true, |alarm| alarm > when)
+    }) {
+        set_alarm_at(storage, when).await?;
+    }
+
+    Ok(id)
+}
+
+/// Cancel a scheduled payload by its ID, returning whether it was scheduled.
+///
+/// # Errors
+///
+/// Returns an error if the payload could not be removed.
+pub async fn cancel(storage: &mut Storage, id: &str) -> worker::Result<bool> {
+    storage.delete(&[KEY_PREFIX, id].concat()).await
+}
+
+/// Deliver the payloads that are due to a callback, returning how many were
+/// delivered, and set the alarm of the Durable Object the storage belongs to
+/// for the next payload.
+///
+/// Up to 25 payloads are delivered at a time, in the order they are due, with
+/// the alarm set to run again immediately if more are due. Payloads are
+/// removed once the callback completes for all of them, and payloads that
+/// can't be deserialized are removed without being delivered.
+///
+/// # Errors
+///
+/// Returns an error if the payloads could not be retrieved or removed, or if
+/// the alarm could not be set.
+pub async fn run_due<T, F, Fut>(storage: &mut Storage, mut deliver: F) -> worker::Result<usize>
+where
+    T: DeserializeOwned,
+    F: FnMut(Scheduled<T>) -> Fut,
+    Fut: Future<Output = ()>,
+{
+    let end = due_before(Date::now().as_millis());
+    let mut due = Vec::new();
+    storage
+        .list_with_options(
+            ListOptions::new()
+                .start(KEY_PREFIX)
+                .end(&end)
+                .limit(DUE_PER_ALARM),
+        )
+        .await?
+        .for_each(&mut |value, key| due.push((key.as_string(), value.as_string())));
+
+    let mut delivered = 0;
+    let mut keys = Vec::with_capacity(due.len());
+
+    for (key, value) in due {
+        let Some(key) = key else {
+            continue;
+        };
+
+        let id = key.strip_prefix(KEY_PREFIX).unwrap_or_default();
+        let scheduled_at = scheduled_at(id);
+        let payload = value.and_then(|value| serde_json::from_str(&value).ok());
+
+        if let (Some(scheduled_at), Some(payload)) = (scheduled_at, payload) {
+            deliver(Scheduled {
+                id: id.to_owned(),
+                payload,
+                scheduled_at,
+            })
+            .await;
+            delivered += 1;
+        }
+
+        keys.push(key);
+    }
+
+    if !keys.is_empty() {
+        storage.delete_multiple(keys).await?;
+    }
+
+    let mut next = None;
+    storage
+        .list_with_options(ListOptions::new().prefix(KEY_PREFIX).limit(1))
+        .await?
+        .for_each(&mut |_, key| {
+            next = key
+                .as_string()
+                .and_then(|key| scheduled_at(key.strip_prefix(KEY_PREFIX)?));
+        });
+
+    if let Some(next) = next {
+        set_alarm_at(storage, next).await?;
+    }
+
+    Ok(delivered)
+}
+
+/// Set the alarm of a Durable Object to a time in milliseconds since the Unix
+/// epoch.
+async fn set_alarm_at(storage: &Storage, when: u64) -> worker::Result<()> {
+    let now = Date::now().as_millis();
+
+    storage
+        .set_alarm(Duration::from_millis(when.saturating_sub(now)))
+        .await
+}
+
+/// Key before which the keys of payloads that are due at a time sort.
+fn due_before(now: u64) -> String {
+    format!("{KEY_PREFIX}{:020}:", now.saturating_add(1))
+}
+
+/// ID of a payload scheduled at a time, ordered by the time and then by when
+/// and in which sequence it was scheduled.
+fn id(when: u64, now: u64, sequence: u32) -> String {
+    format!("{when:020}:{now:020}:{sequence:010}")
+}
+
+/// Time in milliseconds since the Unix epoch a payload is scheduled at, from
+/// its ID.
+fn scheduled_at(id: &str) -> Option<u64> {
+    let (when, _) = id.split_once(':')?;
+
+    when.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{due_before, id, scheduled_at, KEY_PREFIX};
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    fn key(id: &str) -> String {
+        [KEY_PREFIX, id].concat()
+    }
+
+    #[test]
+    fn ids_sort_by_time() {
+        let earlier = id(999, u64::MAX, u32::MAX);
+        let later = id(1000, 0, 0);
+
+        assert!(earlier < later);
+        assert!(key(&earlier) < key(&later));
+    }
+
+    #[test]
+    fn ids_sort_by_sequence() {
+        assert!(id(1000, 1, 9) < id(1000, 1, 10));
+        assert!(id(1000, 1, u32::MAX) < id(1000, 2, 0));
+        assert_ne!(id(1000, 1, 0), id(1000, 1, 1));
+    }
+
+    #[test]
+    fn due_boundary() {
+        let end = due_before(1000);
+
+        assert!(key(&id(999, 0, 0)) < end);
+        assert!(key(&id(1000, u64::MAX, u32::MAX)) < end);
+        assert!(key(&id(1001, 0, 0)) >= end);
+        assert!(KEY_PREFIX < end.as_str());
+    }
+
+    #[test]
+    fn due_boundary_saturates() {
+        assert!(key(&id(u64::MAX - 1, 0, 0)) < due_before(u64::MAX));
+    }
+
+    #[test]
+    fn ids_parse_time() {
+        assert_eq!(Some(1000), scheduled_at(&id(1000, 1, 2)));
+        assert_eq!(Some(u64::MAX), scheduled_at(&id(u64::MAX, 0, 0)));
+        assert_eq!(None, scheduled_at("soon"));
+        assert_eq!(None, scheduled_at("soon:1:2"));
+    }
+}
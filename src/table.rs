@@ -0,0 +1,547 @@
+//! Rendering tabular data, such as leaderboards and lists, into aligned code
+//! blocks or embed fields split into pages that fit within Discord's limits.
+//!
+//! ```ignore
+//! use twilight_cloudflare_workers::table::Table;
+//!
+//! let pages = Table::new(["#", "User", "Points"])
+//!     .rows(leaderboard.iter().enumerate().map(|(index, entry)| {
+//!         [(index + 1).to_string(), entry.name.clone(), entry.points.to_string()]
+//!     }))
+//!     .code_blocks(2000);
+//! ```
+//!
+//! Widths and lengths are counted in characters, so characters displayed
+//! wider than others, such as emojis, misalign their columns.
+
+#[cfg(feature = "twilight-model")]
+use twilight_model::channel::message::embed::EmbedField;
+
+/// Fence of code blocks.
+const CODE_FENCE: &str = "```";
+
+/// Maximum number of characters in the fields of an embed, counting their
+/// names and values.
+#[cfg(feature = "twilight-model")]
+const EMBED_LENGTH_MAX: usize = 6000;
+
+/// Maximum number of fields of an embed.
+#[cfg(feature = "twilight-model")]
+const FIELDS_MAX: usize = 25;
+
+/// Maximum number of characters in the name of an embed field.
+#[cfg(feature = "twilight-model")]
+const FIELD_NAME_LENGTH_MAX: usize = 256;
+
+/// Maximum number of characters in the value of an embed field.
+#[cfg(feature = "twilight-model")]
+const FIELD_VALUE_LENGTH_MAX: usize = 1024;
+
+/// Separator between the columns of code blocks.
+const SEPARATOR: &str = "  ";
+
+/// Character ending truncated cells.
+const TRUNCATION: char = '…';
+
+/// Character backticks in cells are replaced with, so that they can't close
+/// code blocks.
+const BACKTICK_REPLACEMENT: char = 'ˋ';
+
+/// Value of embed fields in place of empty cells, as embed fields with empty
+/// values are rejected by Discord.
+#[cfg(feature = "twilight-model")]
+const PLACEHOLDER: &str = "-";
+
+/// Table of rows of cells under headers.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[must_use = "tables have no effect if unused"]
+pub struct Table {
+    headers: Vec<String>,
+    max_width: usize,
+    rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    /// Create a new table with the headers of its columns.
+    ///
+    /// Refer to [`row`] for how headers are escaped.
+    ///
+    /// [`row`]: Self::row
+    pub fn new(headers: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        Self {
+            headers: headers
+                .into_iter()
+                .map(|header| escape(&header.into()))
+                .collect(),
+            max_width: 32,
+            rows: Vec::new(),
+        }
+    }
+
+    /// Set the maximum width of cells in characters, beyond which they are
+    /// truncated with an ellipsis.
+    ///
+    /// Defaults to 32 characters.
+    pub const fn max_width(mut self, max_width: usize) -> Self {
+        self.max_width = max_width;
+
+        self
+    }
+
+    /// Add a row with the cells of the columns.
+    ///
+    /// Rows with fewer cells than there are headers are padded with empty
+    /// cells, and further cells are ignored.
+    ///
+    /// Newlines in cells are replaced with spaces, and backticks with `ˋ`, so
+    /// that cells can't break the layout of rows or close code blocks.
+    pub fn row(mut self, cells: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        let mut row = cells
+            .into_iter()
+            .take(self.headers.len())
+            .map(|cell| escape(&cell.into()))
+            .collect::<Vec<_>>();
+        row.resize(self.headers.len(), String::new());
+        self.rows.push(row);
+
+        self
+    }
+
+    /// Add rows with the cells of the columns.
+    ///
+    /// Refer to [`row`] for more information.
+    ///
+    /// [`row`]: Self::row
+    pub fn rows(
+        self,
+        rows: impl IntoIterator<Item = impl IntoIterator<Item = impl Into<String>>>,
+    ) -> Self {
+        rows.into_iter().fold(self, Self::row)
+    }
+
+    /// Render the table into pages of code blocks of at most a length, such as
+    /// 2000 characters for message content or 4096 for embed descriptions.
+    ///
+    /// Every page starts with the headers. Pages have at least one row even if
+    /// it doesn't fit within the length, which is avoided with a small enough
+    /// maximum width. A table without rows is rendered as a page of its
+    /// headers.
+    #[must_use = "rendering the table is not useful on its own"]
+    pub fn code_blocks(&self, length: usize) -> Vec<String> {
+        let widths = self.widths();
+        let header = line(&self.headers, &widths, self.max_width);
+        let rule = line(
+            &widths
+                .iter()
+                .map(|width| "-".repeat(*width))
+                .collect::<Vec<_>>(),
+            &widths,
+            self.max_width,
+        );
+        let start = format!("{CODE_FENCE}\n{header}\n{rule}\n");
+        let start_length = start.chars().count() + CODE_FENCE.len();
+
+        let mut pages = Vec::new();
+        let mut page = start.clone();
+        let mut page_length = start_length;
+        let mut page_rows = 0;
+
+        for row in &self.rows {
+            let line = line(row, &widths, self.max_width);
+            let line_length = line.chars().count() + 1;
+
+            if page_rows > 0 && page_length + line_length > length {
+                page.push_str(CODE_FENCE);
+                pages.push(core::mem::replace(&mut page, start.clone()));
+                page_length = start_length;
+                page_rows = 0;
+            }
+
+            page.push_str(&line);
+            page.push('\n');
+            page_length += line_length;
+            page_rows += 1;
+        }
+
+        page.push_str(CODE_FENCE);
+        pages.push(page);
+
+        pages
+    }
+
+    /// Render the table into pages of inline embed fields, one per column
+    /// named by its header, that fit within Discord's limits for an embed: at
+    /// most 1024 characters in a field's value, 25 fields, and 6000
+    /// characters across the names and values of the fields.
+    ///
+    /// Discord displays at most three inline fields side by side, so tables
+    /// with more columns are displayed across multiple lines of fields.
+    /// Columns after the 25th are ignored.
+    ///
+    /// Cells are also truncated to the length of a field's value and headers
+    /// to the length of a field's name, if the maximum width is larger. Empty
+    /// cells are displayed as `-`.
+    #[cfg(feature = "twilight-model")]
+    #[must_use = "rendering the table is not useful on its own"]
+    pub fn embed_fields(&self) -> Vec<Vec<EmbedField>> {
+        let columns = self.headers.len().min(FIELDS_MAX);
+        let names_length = self.headers[..columns]
+            .iter()
+            .map(|header| self.name(header).chars().count())
+            .sum::<usize>();
+        let mut pages = Vec::new();
+        let mut values = vec![String::new(); columns];
+        let mut page_length = names_length;
+        let mut page_rows = 0;
+        let max_width = self.max_width.min(FIELD_VALUE_LENGTH_MAX);
+
+        for row in &self.rows {
+            let cells = row[..columns]
+                .iter()
+                .map(|cell| {
+                    if cell.is_empty() {
+                        PLACEHOLDER.to_owned()
+                    } else {
+                        truncate(cell, max_width)
+                    }
+                })
+                .collect::<Vec<_>>();
+            // Rows after the first are preceded by a newline.
+            let lengths = cells
+                .iter()
+                .map(|cell| cell.chars().count() + usize::from(page_rows > 0))
+                .collect::<Vec<_>>();
+            let full = page_length + lengths.iter().sum::<usize>() > EMBED_LENGTH_MAX
+                || values
+                    .iter()
+                    .zip(&lengths)
+                    .any(|(value, length)| value.chars().count() + length > FIELD_VALUE_LENGTH_MAX);
+
+            if page_rows > 0 && full {
+                pages.push(self.fields(&mut values));
+                page_length = names_length;
+                page_rows = 0;
+            }
+
+            for (value, cell) in values.iter_mut().zip(cells) {
+                if page_rows > 0 {
+                    value.push('\n');
+                    page_length += 1;
+                }
+
+                page_length += cell.chars().count();
+                value.push_str(&cell);
+            }
+
+            page_rows += 1;
+        }
+
+        if page_rows > 0 || pages.is_empty() {
+            pages.push(self.fields(&mut values));
+        }
+
+        pages
+    }
+
+    /// Take the values of the fields of a page.
+    #[cfg(feature = "twilight-model")]
+    fn fields(&self, values: &mut [String]) -> Vec<EmbedField> {
+        self.headers
+            .iter()
+            .zip(values)
+            .map(|(header, value)| EmbedField {
+                inline: true,
+                name: self.name(header),
+                value: if value.is_empty() {
+                    PLACEHOLDER.to_owned()
+                } else {
+                    core::mem::take(value)
+                },
+            })
+            .collect()
+    }
+
+    /// Name of the embed field of a column, from its header.
+    #[cfg(feature = "twilight-model")]
+    fn name(&self, header: &str) -> String {
+        truncate(header, self.max_width.min(FIELD_NAME_LENGTH_MAX))
+    }
+
+    /// Widths of the columns, in characters.
+    fn widths(&self) -> Vec<usize> {
+        self.headers
+            .iter()
+            .enumerate()
+            .map(|(index, header)| {
+                self.rows
+                    .iter()
+                    .map(|row| row[index].chars().count())
+                    .fold(header.chars().count(), usize::max)
+                    .min(self.max_width)
+            })
+            .collect()
+    }
+}
+
+/// Escape a cell, replacing newlines with spaces and backticks with a
+/// lookalike.
+fn escape(cell: &str) -> String {
+    cell.chars()
+        .map(|character| match character {
+            '\n' | '\r' => ' ',
+            '`' => BACKTICK_REPLACEMENT,
+            other => other,
+        })
+        .collect()
+}
+
+/// Render a line of cells padded to the widths of their columns.
+fn line(cells: &[String], widths: &[usize], max_width: usize) -> String {
+    let mut line = String::new();
+
+    for (index, (cell, width)) in cells.iter().zip(widths).enumerate() {
+        if index > 0 {
+            line.push_str(SEPARATOR);
+        }
+
+        let cell = truncate(cell, max_width);
+        let padding = width.saturating_sub(cell.chars().count());
+        line.push_str(&cell);
+        line.extend(core::iter::repeat(' ').take(padding));
+    }
+
+    line.trim_end().to_owned()
+}
+
+/// Truncate a cell to a width in characters, ending it with an ellipsis if
+/// it is truncated.
+pub(crate) fn truncate(cell: &str, width: usize) -> String {
+    if cell.chars().count() <= width {
+        return cell.to_owned();
+    }
+
+    let mut truncated = cell
+        .chars()
+        .take(width.saturating_sub(1))
+        .collect::<String>();
+    truncated.push(TRUNCATION);
+
+    truncated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Table;
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    fn leaderboard(rows: usize) -> Table {
+        Table::new(["#", "Name"])
+            .rows((1..=rows).map(|index| [index.to_string(), format!("user{index}")]))
+    }
+
+    #[test]
+    fn code_blocks_layout() {
+        let table = Table::new(["#", "Name", "Points"])
+            .row(["1", "Alice", "300"])
+            .row(["2", "Bob"])
+            .row(["3", "Carol", "100", "ignored"]);
+
+        assert_eq!(
+            table.code_blocks(2000),
+            ["```\n\
+              #  Name   Points\n\
+              -  -----  ------\n\
+              1  Alice  300\n\
+              2  Bob\n\
+              3  Carol  100\n\
+              ```"]
+        );
+    }
+
+    #[test]
+    fn code_blocks_no_rows() {
+        assert_eq!(
+            Table::new(["a", "bb"]).code_blocks(2000),
+            ["```\na  bb\n-  --\n```"]
+        );
+    }
+
+    #[test]
+    fn code_blocks_overflow_at_length() {
+        let table = leaderboard(20);
+        let [page] = &table.code_blocks(2000)[..] else {
+            panic!("expected one page");
+        };
+        let length = page.chars().count();
+
+        assert_eq!(table.code_blocks(length), [page.clone()]);
+
+        let pages = table.code_blocks(length - 1);
+        assert_eq!(pages.len(), 2);
+
+        for page in &pages {
+            assert!(page.chars().count() < length);
+            assert!(page.starts_with("```\n#   Name\n--  ------\n"));
+            assert!(page.ends_with("\n```"));
+        }
+
+        assert!(pages[1].ends_with("20  user20\n```"));
+    }
+
+    #[test]
+    fn code_blocks_oversized_row() {
+        let table = Table::new(["a"]).row(["x".repeat(20)]).row(["y"]);
+        let pages = table.code_blocks(10);
+
+        // Pages have at least one row even if it doesn't fit.
+        assert_eq!(pages.len(), 2);
+        assert!(pages[0].contains(&"x".repeat(20)));
+        assert!(pages[1].contains("\ny\n"));
+    }
+
+    #[test]
+    fn truncation() {
+        let table = Table::new(["Name"])
+            .max_width(4)
+            .row(["abcdefg"])
+            .row(["abcd"]);
+
+        assert_eq!(
+            table.code_blocks(2000),
+            ["```\nName\n----\nabc…\nabcd\n```"]
+        );
+
+        let table = Table::new(["Long header"]).max_width(4).row(["a"]);
+        assert_eq!(table.code_blocks(2000), ["```\nLon…\n----\na\n```"]);
+    }
+
+    #[cfg(feature = "twilight-model")]
+    #[test]
+    fn embed_fields_placeholders() {
+        let pages = Table::new(["a", "b"]).embed_fields();
+
+        assert_eq!(pages.len(), 1);
+        assert!(pages[0]
+            .iter()
+            .all(|field| field.inline && field.value == "-"));
+
+        let pages = Table::new(["a", "b"])
+            .row(["1"])
+            .row(["", "2"])
+            .embed_fields();
+        let values = pages[0]
+            .iter()
+            .map(|field| field.value.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(values, ["1\n-", "-\n2"]);
+    }
+
+    #[cfg(feature = "twilight-model")]
+    #[test]
+    fn embed_fields_limit() {
+        let table = Table::new(["Cell"]).rows((0..100).map(|_| ["x".repeat(20)]));
+        let pages = table.embed_fields();
+
+        // 48 rows of 20 characters and their separating newlines fit in 1024.
+        assert_eq!(pages.len(), 3);
+        assert_eq!(pages[0][0].value.lines().count(), 48);
+        assert_eq!(pages[0][0].value.chars().count(), 48 * 21 - 1);
+
+        let rows = pages
+            .iter()
+            .map(|page| page[0].value.lines().count())
+            .sum::<usize>();
+        assert_eq!(rows, 100);
+
+        let table = Table::new(["h".repeat(300)])
+            .max_width(2000)
+            .row(["x".repeat(1500)])
+            .row(["y"]);
+        let pages = table.embed_fields();
+
+        assert_eq!(pages.len(), 2);
+        assert_eq!(pages[0][0].name.chars().count(), 256);
+        assert_eq!(pages[0][0].value.chars().count(), 1024);
+        assert!(pages[0][0].value.ends_with('…'));
+        assert_eq!(pages[1][0].value, "y");
+    }
+
+    #[test]
+    fn cells_escaped() {
+        let table = Table::new(["a`b"]).row(["```\nfence\r\n```"]);
+
+        assert_eq!(
+            table.code_blocks(2000),
+            ["```\naˋb\n--------------\nˋˋˋ fence  ˋˋˋ\n```"]
+        );
+    }
+
+    #[cfg(feature = "twilight-model")]
+    #[test]
+    fn embed_fields_placeholder_length() {
+        // A placeholder and its newline fill the value exactly.
+        let table = Table::new(["a"])
+            .max_width(2000)
+            .row(["x".repeat(1022)])
+            .row([""]);
+        let pages = table.embed_fields();
+
+        assert_eq!(pages.len(), 1);
+        assert_eq!(pages[0][0].value.chars().count(), 1024);
+        assert!(pages[0][0].value.ends_with("\n-"));
+
+        let table = Table::new(["a"])
+            .max_width(2000)
+            .row(["x".repeat(1023)])
+            .row([""]);
+        let pages = table.embed_fields();
+
+        assert_eq!(pages.len(), 2);
+        assert_eq!(pages[0][0].value.chars().count(), 1023);
+        assert_eq!(pages[1][0].value, "-");
+    }
+
+    #[cfg(feature = "twilight-model")]
+    #[test]
+    fn embed_fields_total_length() {
+        fn length(page: &[twilight_model::channel::message::embed::EmbedField]) -> usize {
+            page.iter()
+                .map(|field| field.name.chars().count() + field.value.chars().count())
+                .sum()
+        }
+
+        // 6 names, 6 cells of 990 and 6 of 8 with their newlines total 6000.
+        let table = Table::new(["a"; 6])
+            .max_width(1000)
+            .row(vec!["x".repeat(990); 6])
+            .row(vec!["y".repeat(8); 6]);
+        let pages = table.embed_fields();
+
+        assert_eq!(pages.len(), 1);
+        assert_eq!(length(&pages[0]), 6000);
+
+        let table = Table::new(["a"; 6])
+            .max_width(1000)
+            .row(vec!["x".repeat(990); 6])
+            .row(vec!["y".repeat(9); 6]);
+        let pages = table.embed_fields();
+
+        assert_eq!(pages.len(), 2);
+        assert_eq!(length(&pages[0]), 5946);
+        assert_eq!(pages[1][0].value, "y".repeat(9));
+    }
+
+    #[cfg(feature = "twilight-model")]
+    #[test]
+    fn embed_fields_count() {
+        let table = Table::new((0..30).map(|index| index.to_string()))
+            .row((0..30).map(|index| index.to_string()));
+        let pages = table.embed_fields();
+
+        assert_eq!(pages.len(), 1);
+        assert_eq!(pages[0].len(), 25);
+        assert_eq!(pages[0][24].name, "24");
+        assert_eq!(pages[0][24].value, "24");
+    }
+}
@@ -0,0 +1,260 @@
+//! Processing the interactions of multiple applications in one Worker.
+
+use crate::{match_path, Config, LazyInteraction, ProcessRequestError, ProcessRequestErrorType};
+#[cfg(feature = "twilight-model")]
+use twilight_model::application::interaction::Interaction;
+use worker::Request;
+
+/// Application whose interactions are served by a Worker along with others.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[must_use = "tenants have no effect if unused"]
+pub struct Tenant {
+    application_id: String,
+    config: Config,
+    host: Option<String>,
+}
+
+impl Tenant {
+    /// Create a new tenant for an application, whose interactions are
+    /// requested on the path of its configuration.
+    pub fn new(application_id: impl Into<String>, config: Config) -> Self {
+        Self {
+            application_id: application_id.into(),
+            config,
+            host: None,
+        }
+    }
+
+    /// Set the hostname the application's interactions are requested on, such
+    /// as when each application has its own domain.
+    ///
+    /// Hostnames are matched case-insensitively. Defaults to any hostname.
+    pub fn host(mut self, host: impl Into<String>) -> Self {
+        self.host = Some(host.into());
+
+        self
+    }
+
+    /// ID of the application.
+    #[must_use = "retrieving the application ID is not useful on its own"]
+    pub fn application_id(&self) -> &str {
+        &self.application_id
+    }
+
+    /// Configuration the application's interactions are processed with.
+    #[must_use = "retrieving the configuration is not useful on its own"]
+    pub const fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Whether a request on a hostname and path is for the application's
+    /// interactions.
+    fn matches(&self, host: Option<&str>, path: &str) -> bool {
+        if let Some(expected) = &self.host {
+            if !host.is_some_and(|host| host.eq_ignore_ascii_case(expected)) {
+                return false;
+            }
+        }
+
+        match_path(self.config.path(), path).is_some()
+    }
+}
+
+/// Applications whose interactions are served by the same Worker, each keyed
+/// by the path or hostname their interactions are requested on.
+///
+/// ```ignore
+/// use twilight_cloudflare_workers::{Config, Tenant, Tenants};
+///
+/// let tenants = Tenants::new()
+///     .tenant(Tenant::new("1", Config::builder(first_key).path("/first").build()))
+///     .tenant(Tenant::new("2", Config::new(second_key)).host("second.example.com"));
+///
+/// let (tenant, interaction) = match tenants.request_lazy(&mut req).await {
+///     Ok(processed) => processed,
+///     Err(source) => return Ok(source.response()),
+/// };
+///
+/// match tenant.application_id() {
+///     "1" => first::handle(interaction).await,
+///     _ => second::handle(interaction).await,
+/// }
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[must_use = "tenants have no effect if unused"]
+pub struct Tenants {
+    tenants: Vec<Tenant>,
+}
+
+impl Tenants {
+    /// Create a new set of tenants without any tenants.
+    pub const fn new() -> Self {
+        Self {
+            tenants: Vec::new(),
+        }
+    }
+
+    /// Add a tenant.
+    ///
+    /// Requests are processed for the first tenant they match.
+    pub fn tenant(mut self, tenant: Tenant) -> Self {
+        self.tenants.push(tenant);
+
+        self
+    }
+
+    /// Tenant a request is for, if any.
+    #[must_use = "resolving the tenant is not useful on its own"]
+    pub fn resolve(&self, req: &Request) -> Option<&Tenant> {
+        let url = req.url().ok();
+
+        self.resolve_parts(url.as_ref().and_then(|url| url.host_str()), &req.path())
+    }
+
+    /// Tenant a request on a hostname and path is for, if any.
+    fn resolve_parts(&self, host: Option<&str>, path: &str) -> Option<&Tenant> {
+        self.tenants
+            .iter()
+            .find(|tenant| tenant.matches(host, path))
+    }
+
+    /// Process a request for the tenant it is for, returning the tenant along
+    /// with the request's interaction body if the request is valid.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of type [`RouteIncorrect`] if the request isn't for
+    /// any tenant.
+    ///
+    /// Refer to the documentation for [`Config::request`] for the other
+    /// errors that may be returned.
+    ///
+    /// [`RouteIncorrect`]: ProcessRequestErrorType::RouteIncorrect
+    #[cfg(feature = "twilight-model")]
+    pub async fn request(
+        &self,
+        req: &mut Request,
+    ) -> Result<(&Tenant, Interaction), ProcessRequestError> {
+        let tenant = self.resolve_or_error(req)?;
+
+        Ok((tenant, tenant.config.request(req).await?))
+    }
+
+    /// Process a request for the tenant it is for, returning the tenant along
+    /// with the request's interaction body if the request is valid,
+    /// deserializing only what is required to route the interaction.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of type [`RouteIncorrect`] if the request isn't for
+    /// any tenant.
+    ///
+    /// Refer to the documentation for [`Config::request_lazy`] for the other
+    /// errors that may be returned.
+    ///
+    /// [`RouteIncorrect`]: ProcessRequestErrorType::RouteIncorrect
+    pub async fn request_lazy(
+        &self,
+        req: &mut Request,
+    ) -> Result<(&Tenant, LazyInteraction), ProcessRequestError> {
+        let tenant = self.resolve_or_error(req)?;
+
+        Ok((tenant, tenant.config.request_lazy(req).await?))
+    }
+
+    /// Tenant a request is for, or an error if it is for no tenant.
+    fn resolve_or_error(&self, req: &Request) -> Result<&Tenant, ProcessRequestError> {
+        self.resolve(req).ok_or_else(|| ProcessRequestError {
+            kind: ProcessRequestErrorType::RouteIncorrect {
+                method: req.method().into(),
+                path: req.path().into(),
+            },
+            source: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Tenant, Tenants};
+    use crate::Config;
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    fn tenants() -> Tenants {
+        Tenants::new()
+            .tenant(Tenant::new("1", Config::new("key")).host("Bot.Example.com"))
+            .tenant(Tenant::new(
+                "2",
+                Config::builder("key").path("/second/*").build(),
+            ))
+            .tenant(Tenant::new("3", Config::new("key")))
+    }
+
+    fn resolve<'a>(tenants: &'a Tenants, host: Option<&str>, path: &str) -> Option<&'a str> {
+        tenants
+            .resolve_parts(host, path)
+            .map(Tenant::application_id)
+    }
+
+    #[test]
+    fn host_case_insensitive() {
+        let tenant = Tenant::new("1", Config::new("key")).host("Bot.Example.com");
+
+        assert!(tenant.matches(Some("bot.example.com"), "/"));
+        assert!(tenant.matches(Some("BOT.EXAMPLE.COM"), "/"));
+        assert!(!tenant.matches(Some("other.example.com"), "/"));
+        assert!(!tenant.matches(None, "/"));
+    }
+
+    #[test]
+    fn host_and_path() {
+        let tenant =
+            Tenant::new("1", Config::builder("key").path("/bot").build()).host("bot.example.com");
+
+        assert!(tenant.matches(Some("bot.example.com"), "/bot"));
+        assert!(!tenant.matches(Some("bot.example.com"), "/"));
+        assert!(!tenant.matches(Some("other.example.com"), "/bot"));
+    }
+
+    #[test]
+    fn resolve_by_host() {
+        let tenants = tenants();
+
+        assert_eq!(Some("1"), resolve(&tenants, Some("bot.example.com"), "/"));
+        assert_eq!(Some("1"), resolve(&tenants, Some("BOT.example.com"), "/"));
+        // The host's tenant only serves its own path.
+        assert_eq!(
+            Some("2"),
+            resolve(&tenants, Some("bot.example.com"), "/second/a")
+        );
+    }
+
+    #[test]
+    fn resolve_by_path_prefix() {
+        let tenants = tenants();
+
+        assert_eq!(
+            Some("2"),
+            resolve(&tenants, Some("other.example.com"), "/second/a")
+        );
+        assert_eq!(
+            None,
+            resolve(&tenants, Some("other.example.com"), "/second")
+        );
+        assert_eq!(
+            None,
+            resolve(&tenants, Some("other.example.com"), "/second/a/b")
+        );
+    }
+
+    #[test]
+    fn resolve_fallback() {
+        let tenants = tenants();
+
+        assert_eq!(Some("3"), resolve(&tenants, Some("other.example.com"), "/"));
+        assert_eq!(Some("3"), resolve(&tenants, None, "/"));
+        assert_eq!(None, resolve(&tenants, None, "/other"));
+        assert_eq!(None, resolve(&Tenants::new(), None, "/"));
+    }
+}
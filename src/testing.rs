@@ -0,0 +1,474 @@
+//! Utilities for testing interaction request handling.
+//!
+//! [`SignedRequest`] signs request bodies with a known secret key, so that the
+//! verification of requests can be exercised without requests from Discord,
+//! and [`TEST_VECTORS`] are known requests for validating other
+//! implementations and adapters. With the `proptest` feature, the
+//! `strategies` module provides strategies generating signed requests and
+//! malformed inputs.
+
+#[cfg(feature = "worker")]
+use crate::InteractionRequestHeaderName;
+use crate::{ProcessRequestError, ProcessRequestErrorType};
+use ed25519_dalek::{Keypair, PublicKey, SecretKey, Signer, SECRET_KEY_LENGTH};
+#[cfg(feature = "worker")]
+use worker::{js_sys::Uint8Array, Headers, Method, Request, RequestInit};
+
+/// Request body signed with a known secret key, along with the hex encoded
+/// public key and signature that verify it.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SignedRequest {
+    body: Vec<u8>,
+    public_key: String,
+    signature: String,
+    timestamp: String,
+}
+
+impl SignedRequest {
+    /// Sign a request body and timestamp with a secret key.
+    #[allow(clippy::missing_panics_doc)]
+    pub fn sign(
+        secret_key: [u8; SECRET_KEY_LENGTH],
+        timestamp: impl Into<String>,
+        body: impl Into<Vec<u8>>,
+    ) -> Self {
+        let secret = SecretKey::from_bytes(&secret_key).expect("secret key length is valid");
+        let public = PublicKey::from(&secret);
+        let keypair = Keypair { secret, public };
+
+        let body = body.into();
+        let timestamp = timestamp.into();
+        let message = [timestamp.as_bytes(), &body].concat();
+        let signature = keypair.sign(&message);
+
+        Self {
+            body,
+            public_key: hex::encode(public.as_bytes()),
+            signature: hex::encode(signature.to_bytes()),
+            timestamp,
+        }
+    }
+
+    /// Body of the request.
+    #[must_use = "retrieving the body is not useful on its own"]
+    pub fn body(&self) -> &[u8] {
+        &self.body
+    }
+
+    /// Hex encoded public key of the secret key the request was signed with.
+    #[must_use = "retrieving the public key is not useful on its own"]
+    pub fn public_key(&self) -> &str {
+        &self.public_key
+    }
+
+    /// Hex encoded signature of the request, as sent in the
+    /// [`Signature`] header.
+    ///
+    /// [`Signature`]: crate::InteractionRequestHeaderName::Signature
+    #[must_use = "retrieving the signature is not useful on its own"]
+    pub fn signature(&self) -> &str {
+        &self.signature
+    }
+
+    /// Timestamp of the request, as sent in the [`Timestamp`] header.
+    ///
+    /// [`Timestamp`]: crate::InteractionRequestHeaderName::Timestamp
+    #[must_use = "retrieving the timestamp is not useful on its own"]
+    pub fn timestamp(&self) -> &str {
+        &self.timestamp
+    }
+
+    /// Create a `POST` request to a URL with the body and the headers Discord
+    /// sends, for processing with [`request_lazy`] or a [`Config`].
+    ///
+    /// Requests can only be created in a JavaScript environment, such as in
+    /// tests run with `wasm-bindgen-test`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the URL is invalid or the request could not be
+    /// created.
+    ///
+    /// [`Config`]: crate::Config
+    /// [`request_lazy`]: crate::request_lazy
+    #[cfg(feature = "worker")]
+    pub fn to_request(&self, url: &str) -> worker::Result<Request> {
+        let mut headers = Headers::new();
+        headers.set(
+            InteractionRequestHeaderName::Signature.name(),
+            &self.signature,
+        )?;
+        headers.set(
+            InteractionRequestHeaderName::Timestamp.name(),
+            &self.timestamp,
+        )?;
+
+        let mut init = RequestInit::new();
+        init.with_method(Method::Post)
+            .with_headers(headers)
+            .with_body(Some(Uint8Array::from(self.body.as_slice()).into()));
+
+        Request::new_with_init(url, &init)
+    }
+
+    /// Verify the request with [`verify`].
+    ///
+    /// # Errors
+    ///
+    /// Refer to the documentation for [`verify`].
+    ///
+    /// [`verify`]: crate::verify
+    pub fn verify(&self) -> Result<(), ProcessRequestError> {
+        crate::verify(
+            &self.public_key,
+            &self.timestamp,
+            &self.signature,
+            &self.body,
+        )
+    }
+}
+
+/// Body of the ping in the test vectors.
+const PING: &str = r#"{"application_id":"100000000000000001","id":"100000000000000002","token":"token","type":1,"version":1}"#;
+
+/// Body of the ping in the test vectors with its type changed.
+const PING_TAMPERED: &str = r#"{"application_id":"100000000000000001","id":"100000000000000002","token":"token","type":2,"version":1}"#;
+
+/// Body of the command in the test vectors.
+const COMMAND: &str = r#"{"application_id":"100000000000000001","data":{"id":"100000000000000003","name":"ping","type":1},"id":"100000000000000002","token":"token","type":2,"version":1}"#;
+
+/// Known requests and whether they verify, covering the same cases as the
+/// tests of Discord's official `discord-interactions` libraries: valid
+/// requests, and requests whose body, timestamp, signature, or public key
+/// don't match or are malformed.
+///
+/// The requests are signed with the secret keys of the first two test vectors
+/// of [RFC 8032], so that other implementations can reproduce them.
+///
+/// ```ignore
+/// use twilight_cloudflare_workers::testing::TEST_VECTORS;
+///
+/// for vector in TEST_VECTORS {
+///     let result = my_adapter::verify(vector.public_key, vector.timestamp, vector.signature, vector.body);
+///
+///     assert_eq!(result.is_ok(), vector.outcome == Outcome::Valid, "{}", vector.name);
+/// }
+/// ```
+///
+/// [RFC 8032]: https://www.rfc-editor.org/rfc/rfc8032#section-7.1
+pub const TEST_VECTORS: &[TestVector] = &[
+    TestVector {
+        body: PING,
+        name: "valid ping",
+        outcome: Outcome::Valid,
+        public_key: "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+        signature: "900d3714afe6c1af06438215cb89dad03c6091c653057d3eda305c9ba1a87360d6a2aaa0daf9ea1cf84903e918e19af4c6cc7ed14076c56e776ab75c552ec905",
+        timestamp: "1682410000",
+    },
+    TestVector {
+        body: COMMAND,
+        name: "valid command",
+        outcome: Outcome::Valid,
+        public_key: "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+        signature: "1374fa0348e0f110010431ed2c21be2def567b656b946797c93e273556de0217baf520c8d7e7996b0139d2d3da1b8ed4eb13bf63ab944283e87b115cf3fba80e",
+        timestamp: "1682410001",
+    },
+    TestVector {
+        body: "",
+        name: "valid empty body",
+        outcome: Outcome::Valid,
+        public_key: "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+        signature: "484e39f347b763159901ada26f76fcbddc9f45e05b8a7a470f6ac33ce1e3891d6ed8c8a7ef73e10476f80a91ef2148f68b22740828bbb29a51d74ecf22136606",
+        timestamp: "1682410000",
+    },
+    TestVector {
+        body: PING,
+        name: "valid ping signed with another key",
+        outcome: Outcome::Valid,
+        public_key: "3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c",
+        signature: "2372cf740cad6f83c5d916168ccedbe3898f066410ee56fa4e8dee900267867acac5828d1f47fa113f88756bf49aebbbf7c2c23fdf22c272ffdfb185856d4001",
+        timestamp: "1682410000",
+    },
+    TestVector {
+        body: PING,
+        name: "public key of another key",
+        outcome: Outcome::InvalidSignature,
+        public_key: "3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c",
+        signature: "900d3714afe6c1af06438215cb89dad03c6091c653057d3eda305c9ba1a87360d6a2aaa0daf9ea1cf84903e918e19af4c6cc7ed14076c56e776ab75c552ec905",
+        timestamp: "1682410000",
+    },
+    TestVector {
+        body: PING_TAMPERED,
+        name: "tampered body",
+        outcome: Outcome::InvalidSignature,
+        public_key: "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+        signature: "900d3714afe6c1af06438215cb89dad03c6091c653057d3eda305c9ba1a87360d6a2aaa0daf9ea1cf84903e918e19af4c6cc7ed14076c56e776ab75c552ec905",
+        timestamp: "1682410000",
+    },
+    TestVector {
+        body: PING,
+        name: "tampered timestamp",
+        outcome: Outcome::InvalidSignature,
+        public_key: "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+        signature: "900d3714afe6c1af06438215cb89dad03c6091c653057d3eda305c9ba1a87360d6a2aaa0daf9ea1cf84903e918e19af4c6cc7ed14076c56e776ab75c552ec905",
+        timestamp: "1682410001",
+    },
+    TestVector {
+        body: PING,
+        name: "signature of another request",
+        outcome: Outcome::InvalidSignature,
+        public_key: "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+        signature: "1374fa0348e0f110010431ed2c21be2def567b656b946797c93e273556de0217baf520c8d7e7996b0139d2d3da1b8ed4eb13bf63ab944283e87b115cf3fba80e",
+        timestamp: "1682410000",
+    },
+    TestVector {
+        body: PING,
+        name: "truncated signature",
+        outcome: Outcome::SignatureLength,
+        public_key: "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+        signature: "900d3714afe6c1af06438215cb89dad03c6091c653057d3eda305c9ba1a87360d6a2aaa0daf9ea1cf84903e918e19af4c6cc7ed14076c56e776ab75c552ec9",
+        timestamp: "1682410000",
+    },
+    TestVector {
+        body: PING,
+        name: "signature with a non-hex character",
+        outcome: Outcome::SignatureFromHex,
+        public_key: "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+        signature: "g00d3714afe6c1af06438215cb89dad03c6091c653057d3eda305c9ba1a87360d6a2aaa0daf9ea1cf84903e918e19af4c6cc7ed14076c56e776ab75c552ec905",
+        timestamp: "1682410000",
+    },
+    TestVector {
+        body: PING,
+        name: "empty signature",
+        outcome: Outcome::SignatureLength,
+        public_key: "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+        signature: "",
+        timestamp: "1682410000",
+    },
+    TestVector {
+        body: PING,
+        name: "timestamp that is not a number",
+        outcome: Outcome::InvalidTimestamp,
+        public_key: "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+        signature: "900d3714afe6c1af06438215cb89dad03c6091c653057d3eda305c9ba1a87360d6a2aaa0daf9ea1cf84903e918e19af4c6cc7ed14076c56e776ab75c552ec905",
+        timestamp: "now",
+    },
+    TestVector {
+        body: PING,
+        name: "truncated public key",
+        outcome: Outcome::FromHex,
+        public_key: "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f70751",
+        signature: "900d3714afe6c1af06438215cb89dad03c6091c653057d3eda305c9ba1a87360d6a2aaa0daf9ea1cf84903e918e19af4c6cc7ed14076c56e776ab75c552ec905",
+        timestamp: "1682410000",
+    },
+    TestVector {
+        body: PING,
+        name: "public key that is not a curve point",
+        outcome: Outcome::InvalidPublicKey,
+        public_key: "0202020202020202020202020202020202020202020202020202020202020202",
+        signature: "900d3714afe6c1af06438215cb89dad03c6091c653057d3eda305c9ba1a87360d6a2aaa0daf9ea1cf84903e918e19af4c6cc7ed14076c56e776ab75c552ec905",
+        timestamp: "1682410000",
+    },
+];
+
+/// Known request and whether it verifies.
+///
+/// Refer to [`TEST_VECTORS`] for more information.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct TestVector {
+    /// Body of the request.
+    pub body: &'static str,
+    /// Description of the case the vector covers.
+    pub name: &'static str,
+    /// Outcome of verifying the request.
+    pub outcome: Outcome,
+    /// Hex encoded public key to verify the request with.
+    pub public_key: &'static str,
+    /// Hex encoded signature of the request.
+    pub signature: &'static str,
+    /// Timestamp of the request.
+    pub timestamp: &'static str,
+}
+
+impl TestVector {
+    /// Verify the request with [`verify`].
+    ///
+    /// # Errors
+    ///
+    /// Refer to the documentation for [`verify`].
+    ///
+    /// [`verify`]: crate::verify
+    pub fn verify(&self) -> Result<(), ProcessRequestError> {
+        crate::verify(
+            self.public_key,
+            self.timestamp,
+            self.signature,
+            self.body.as_bytes(),
+        )
+    }
+}
+
+/// Outcome of verifying a [`TestVector`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Outcome {
+    /// Request verifies.
+    Valid,
+    /// Request fails with an error of type [`FromHex`].
+    ///
+    /// [`FromHex`]: ProcessRequestErrorType::FromHex
+    FromHex,
+    /// Request fails with an error of type [`InvalidPublicKey`].
+    ///
+    /// [`InvalidPublicKey`]: ProcessRequestErrorType::InvalidPublicKey
+    InvalidPublicKey,
+    /// Request fails with an error of type [`InvalidSignature`].
+    ///
+    /// [`InvalidSignature`]: ProcessRequestErrorType::InvalidSignature
+    InvalidSignature,
+    /// Request fails with an error of type [`InvalidTimestamp`].
+    ///
+    /// [`InvalidTimestamp`]: ProcessRequestErrorType::InvalidTimestamp
+    InvalidTimestamp,
+    /// Request fails with an error of type [`SignatureFromHex`].
+    ///
+    /// [`SignatureFromHex`]: ProcessRequestErrorType::SignatureFromHex
+    SignatureFromHex,
+    /// Request fails with an error of type [`SignatureLength`].
+    ///
+    /// [`SignatureLength`]: ProcessRequestErrorType::SignatureLength
+    SignatureLength,
+}
+
+impl Outcome {
+    /// Whether the result of verifying a request is this outcome.
+    #[must_use = "checking the outcome is not useful on its own"]
+    pub const fn matches(self, result: &Result<(), ProcessRequestError>) -> bool {
+        match result {
+            Ok(()) => matches!(self, Self::Valid),
+            Err(source) => matches!(
+                (self, source.kind()),
+                (Self::FromHex, ProcessRequestErrorType::FromHex)
+                    | (
+                        Self::InvalidPublicKey,
+                        ProcessRequestErrorType::InvalidPublicKey
+                    )
+                    | (
+                        Self::InvalidSignature,
+                        ProcessRequestErrorType::InvalidSignature
+                    )
+                    | (
+                        Self::InvalidTimestamp,
+                        ProcessRequestErrorType::InvalidTimestamp
+                    )
+                    | (
+                        Self::SignatureFromHex,
+                        ProcessRequestErrorType::SignatureFromHex
+                    )
+                    | (
+                        Self::SignatureLength,
+                        ProcessRequestErrorType::SignatureLength { .. }
+                    )
+            ),
+        }
+    }
+}
+
+/// Strategies generating signed requests, interaction bodies, and malformed
+/// inputs with `proptest`.
+///
+/// ```ignore
+/// use twilight_cloudflare_workers::testing::strategies;
+///
+/// proptest! {
+///     #[test]
+///     fn verifies(request in strategies::signed_request()) {
+///         prop_assert!(request.verify().is_ok());
+///     }
+/// }
+/// ```
+#[cfg(feature = "proptest")]
+pub mod strategies {
+    use super::SignedRequest;
+    use proptest::{collection, option, prelude::*};
+
+    /// Secret key to sign requests with.
+    pub fn secret_key() -> impl Strategy<Value = [u8; 32]> {
+        any::<[u8; 32]>()
+    }
+
+    /// Timestamp of a request, made of digits like Discord's.
+    pub fn timestamp() -> impl Strategy<Value = String> {
+        "[0-9]{1,12}"
+    }
+
+    /// Interaction body with a valid type and optionally the data used to
+    /// route interactions, serialized as JSON.
+    #[allow(clippy::missing_panics_doc)]
+    pub fn interaction_json() -> impl Strategy<Value = Vec<u8>> {
+        (
+            1_u8..=5,
+            option::of(".{0,32}"),
+            option::of(".{0,100}"),
+            "[0-9]{17,19}",
+        )
+            .prop_map(|(kind, name, custom_id, id)| {
+                let mut interaction = serde_json::json!({
+                    "application_id": id,
+                    "id": id,
+                    "token": "token",
+                    "type": kind,
+                    "version": 1,
+                });
+
+                if name.is_some() || custom_id.is_some() {
+                    interaction["data"] = serde_json::json!({
+                        "custom_id": custom_id,
+                        "name": name,
+                    });
+                }
+
+                serde_json::to_vec(&interaction).expect("interaction is serializable")
+            })
+    }
+
+    /// Interaction body signed with a random secret key and timestamp.
+    pub fn signed_request() -> impl Strategy<Value = SignedRequest> {
+        (secret_key(), timestamp(), interaction_json()).prop_map(|(secret_key, timestamp, body)| {
+            SignedRequest::sign(secret_key, timestamp, body)
+        })
+    }
+
+    /// Hex string that is not a valid signature or public key, such as a
+    /// truncated or overlong string or one with characters other than hex
+    /// digits.
+    pub fn malformed_hex() -> impl Strategy<Value = String> {
+        prop_oneof![
+            "[0-9a-f]{0,63}",
+            "[0-9a-f]{65,127}",
+            "[0-9a-f]{129,160}",
+            "[0-9a-f]{0,64}[g-z][0-9a-f]{0,64}",
+            ".{0,160}",
+        ]
+    }
+
+    /// Arbitrary bytes, which are frequently not valid UTF-8.
+    pub fn malformed_body() -> impl Strategy<Value = Vec<u8>> {
+        collection::vec(any::<u8>(), 0..512)
+    }
+
+    /// JSON arrays and objects nested up to 1024 levels deep, which is mostly
+    /// deeper than `serde_json` accepts.
+    pub fn deep_json() -> impl Strategy<Value = Vec<u8>> {
+        (1_usize..1024, any::<bool>()).prop_map(|(depth, objects)| {
+            let (open, close) = if objects {
+                (r#"{"data":"#, "}")
+            } else {
+                ("[", "]")
+            };
+
+            [open.repeat(depth), "null".to_owned(), close.repeat(depth)]
+                .concat()
+                .into_bytes()
+        })
+    }
+}
@@ -0,0 +1,312 @@
+//! Verification of interaction request signatures.
+
+use crate::{key, InteractionRequestHeaderName, ProcessRequestError, ProcessRequestErrorType};
+use ed25519_dalek::{Signature, Verifier, SIGNATURE_LENGTH};
+use hex::FromHex;
+use std::cell::RefCell;
+#[cfg(feature = "worker")]
+use worker::Request;
+
+/// Capacity above which the reused message buffer is released after use, so
+/// that an unusually large request doesn't pin its memory for the lifetime of
+/// the isolate.
+const MESSAGE_BUFFER_RETAINED_CAPACITY: usize = 64 * 1024;
+
+thread_local! {
+    /// Buffer reused to construct the signed message of requests.
+    static MESSAGE: RefCell<Vec<u8>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Signature of an interaction request along with the timestamp it signs.
+///
+/// The signature is decoded onto the stack and the timestamp is borrowed from
+/// the request's header, so no allocations are required to hold them.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct RequestSignature<'a> {
+    signature: Signature,
+    timestamp: &'a str,
+}
+
+impl<'a> RequestSignature<'a> {
+    /// Decode the hex encoded signature of a request along with the
+    /// timestamp it signs.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of type [`InvalidSignature`] if the signature is not a
+    /// valid Ed25519 signature.
+    ///
+    /// Returns an error of type [`InvalidTimestamp`] if the timestamp is not
+    /// made of digits.
+    ///
+    /// Returns an error of type [`SignatureFromHex`] if the signature is not
+    /// hex encoded.
+    ///
+    /// Returns an error of type [`SignatureLength`] if the signature is not of
+    /// the length of an Ed25519 signature.
+    ///
+    /// [`InvalidSignature`]: ProcessRequestErrorType::InvalidSignature
+    /// [`InvalidTimestamp`]: ProcessRequestErrorType::InvalidTimestamp
+    /// [`SignatureFromHex`]: ProcessRequestErrorType::SignatureFromHex
+    /// [`SignatureLength`]: ProcessRequestErrorType::SignatureLength
+    pub fn new(timestamp: &'a str, signature: &str) -> Result<Self, ProcessRequestError> {
+        if signature.len() != SIGNATURE_LENGTH * 2 {
+            return Err(ProcessRequestError {
+                kind: ProcessRequestErrorType::SignatureLength {
+                    length: signature.len(),
+                },
+                source: None,
+            });
+        }
+
+        let bytes = <[u8; SIGNATURE_LENGTH] as FromHex>::from_hex(signature).map_err(|source| {
+            ProcessRequestError {
+                kind: ProcessRequestErrorType::SignatureFromHex,
+                source: Some(Box::new(source)),
+            }
+        })?;
+        let signature = Signature::from_bytes(&bytes).map_err(|source| ProcessRequestError {
+            kind: ProcessRequestErrorType::InvalidSignature,
+            source: Some(Box::new(source)),
+        })?;
+
+        // Discord's timestamps are seconds since the Unix epoch.
+        if timestamp.is_empty() || !timestamp.bytes().all(|byte| byte.is_ascii_digit()) {
+            return Err(ProcessRequestError {
+                kind: ProcessRequestErrorType::InvalidTimestamp,
+                source: None,
+            });
+        }
+
+        Ok(Self {
+            signature,
+            timestamp,
+        })
+    }
+
+    /// Decoded bytes of the signature.
+    #[must_use = "retrieving the signature is not useful on its own"]
+    pub fn signature(&self) -> [u8; SIGNATURE_LENGTH] {
+        self.signature.to_bytes()
+    }
+
+    /// Timestamp the signature signs along with the body.
+    #[must_use = "retrieving the timestamp is not useful on its own"]
+    pub const fn timestamp(&self) -> &'a str {
+        self.timestamp
+    }
+
+    /// Verify the signature against the body of a request.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of type [`FromHex`] if the provided public key is not
+    /// in a valid format.
+    ///
+    /// Returns an error of type [`InvalidPublicKey`] if the provided public key
+    /// is invalid.
+    ///
+    /// Returns an error of type [`InvalidSignature`] if the signature could not
+    /// be verified.
+    ///
+    /// [`FromHex`]: ProcessRequestErrorType::FromHex
+    /// [`InvalidPublicKey`]: ProcessRequestErrorType::InvalidPublicKey
+    /// [`InvalidSignature`]: ProcessRequestErrorType::InvalidSignature
+    pub fn verify(&self, public_key: &str, body: &[u8]) -> Result<(), ProcessRequestError> {
+        MESSAGE.with(|message| {
+            let mut message = message.borrow_mut();
+            message.clear();
+            message.extend_from_slice(self.timestamp.as_bytes());
+            message.extend_from_slice(body);
+
+            let result = self.verify_message(public_key, &message);
+
+            if message.capacity() > MESSAGE_BUFFER_RETAINED_CAPACITY {
+                *message = Vec::new();
+            }
+
+            result
+        })
+    }
+
+    /// Verify the signature against a message made of the timestamp followed
+    /// by the body of a request.
+    pub(crate) fn verify_message(
+        &self,
+        public_key: &str,
+        message: &[u8],
+    ) -> Result<(), ProcessRequestError> {
+        let key = key::parse(public_key)?;
+
+        // Check if the signature matches and else return a error response.
+        key.verify(message, &self.signature)
+            .map_err(|source| ProcessRequestError {
+                kind: ProcessRequestErrorType::InvalidSignature,
+                source: Some(Box::new(source)),
+            })
+    }
+}
+
+/// Timestamp and signature headers of an interaction request, extracted and
+/// validated so that the request can be verified elsewhere, such as by a
+/// gateway that verifies requests before passing them on to be processed.
+///
+/// ```ignore
+/// let headers = VerificationHeaders::from_request(&req)?;
+/// let body = req.bytes().await?;
+///
+/// twilight_cloudflare_workers::verify(public_key, headers.timestamp(), headers.signature(), &body)?;
+/// ```
+#[allow(clippy::module_name_repetitions)]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VerificationHeaders {
+    decoded: Signature,
+    signature: String,
+    timestamp: String,
+}
+
+impl VerificationHeaders {
+    /// Extract the verification headers from the headers of a request, whose
+    /// names are matched case-insensitively.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of type [`MissingHeader`] if a verification header is
+    /// not present.
+    ///
+    /// Refer to the documentation for [`RequestSignature::new`] for the errors
+    /// returned when the headers are not in a valid format.
+    ///
+    /// [`MissingHeader`]: ProcessRequestErrorType::MissingHeader
+    pub fn from_headers<K: AsRef<str>, V: AsRef<str>>(
+        headers: impl IntoIterator<Item = (K, V)>,
+    ) -> Result<Self, ProcessRequestError> {
+        let mut signature = None;
+        let mut timestamp = None;
+
+        for (name, value) in headers {
+            let name = name.as_ref();
+
+            if name.eq_ignore_ascii_case(InteractionRequestHeaderName::Signature.name()) {
+                signature = Some(value.as_ref().to_owned());
+            } else if name.eq_ignore_ascii_case(InteractionRequestHeaderName::Timestamp.name()) {
+                timestamp = Some(value.as_ref().to_owned());
+            }
+        }
+
+        Self::from_values(signature, timestamp)
+    }
+
+    /// Extract the verification headers of a Worker request.
+    ///
+    /// # Errors
+    ///
+    /// Refer to the documentation for [`from_headers`].
+    ///
+    /// [`from_headers`]: Self::from_headers
+    #[cfg(feature = "worker")]
+    pub fn from_request(req: &Request) -> Result<Self, ProcessRequestError> {
+        // Only the two headers are retrieved, rather than every header of the
+        // request, and their values are moved into the struct as they are.
+        let headers = req.headers();
+        let header = |name: InteractionRequestHeaderName| headers.get(name.name()).ok().flatten();
+
+        Self::from_values(
+            header(InteractionRequestHeaderName::Signature),
+            header(InteractionRequestHeaderName::Timestamp),
+        )
+    }
+
+    /// Validate the values of the verification headers, if present.
+    fn from_values(
+        signature: Option<String>,
+        timestamp: Option<String>,
+    ) -> Result<Self, ProcessRequestError> {
+        let missing = |header| ProcessRequestError {
+            kind: ProcessRequestErrorType::MissingHeader { header },
+            source: None,
+        };
+        let timestamp =
+            timestamp.ok_or_else(|| missing(InteractionRequestHeaderName::Timestamp))?;
+        let signature =
+            signature.ok_or_else(|| missing(InteractionRequestHeaderName::Signature))?;
+        let decoded = RequestSignature::new(&timestamp, &signature)?.signature;
+
+        Ok(Self {
+            decoded,
+            signature,
+            timestamp,
+        })
+    }
+
+    /// Decoded signature along with the timestamp it signs.
+    #[must_use = "retrieving the signature is not useful on its own"]
+    pub fn request_signature(&self) -> RequestSignature<'_> {
+        RequestSignature {
+            signature: self.decoded,
+            timestamp: &self.timestamp,
+        }
+    }
+
+    /// Hex encoded signature of the request.
+    #[must_use = "retrieving the signature is not useful on its own"]
+    pub fn signature(&self) -> &str {
+        &self.signature
+    }
+
+    /// Timestamp of the request.
+    #[must_use = "retrieving the timestamp is not useful on its own"]
+    pub fn timestamp(&self) -> &str {
+        &self.timestamp
+    }
+
+    /// Verify the headers against the body of a request.
+    ///
+    /// # Errors
+    ///
+    /// Refer to the documentation for [`RequestSignature::verify`].
+    pub fn verify(&self, public_key: &str, body: &[u8]) -> Result<(), ProcessRequestError> {
+        self.request_signature().verify(public_key, body)
+    }
+}
+
+/// Verify the signature of an interaction request's body.
+///
+/// This is the verification performed when processing Worker requests, for
+/// those who need to verify a request that isn't a Worker request, such as when
+/// the body has already been read or was received through a queue.
+///
+/// # Errors
+///
+/// Returns an error of type [`FromHex`] if the provided public key is not in a
+/// valid format.
+///
+/// Returns an error of type [`InvalidPublicKey`] if the provided public key is
+/// invalid.
+///
+/// Returns an error of type [`InvalidSignature`] if the signature could not be
+/// verified.
+///
+/// Returns an error of type [`InvalidTimestamp`] if the timestamp is not made
+/// of digits.
+///
+/// Returns an error of type [`SignatureFromHex`] if the signature is not hex
+/// encoded.
+///
+/// Returns an error of type [`SignatureLength`] if the signature is not of the
+/// length of an Ed25519 signature.
+///
+/// [`FromHex`]: ProcessRequestErrorType::FromHex
+/// [`InvalidPublicKey`]: ProcessRequestErrorType::InvalidPublicKey
+/// [`InvalidSignature`]: ProcessRequestErrorType::InvalidSignature
+/// [`InvalidTimestamp`]: ProcessRequestErrorType::InvalidTimestamp
+/// [`SignatureFromHex`]: ProcessRequestErrorType::SignatureFromHex
+/// [`SignatureLength`]: ProcessRequestErrorType::SignatureLength
+pub fn verify(
+    public_key: &str,
+    timestamp: &str,
+    signature: &str,
+    body: &[u8],
+) -> Result<(), ProcessRequestError> {
+    RequestSignature::new(timestamp, signature)?.verify(public_key, body)
+}
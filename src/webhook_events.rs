@@ -0,0 +1,320 @@
+//! Webhook events sent by Discord to an application's webhook events URL.
+//!
+//! Webhook events are signed in the same way as interactions and must be
+//! acknowledged with a 204 (No Content) response, which can be created with
+//! `webhook_event_response`. This includes the [`WebhookEventType::Ping`]
+//! events Discord sends to check the endpoint.
+
+use serde::{de::Error as DeError, Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+use twilight_model::{
+    id::{
+        marker::{ApplicationMarker, GenericMarker, GuildMarker, UserMarker},
+        Id,
+    },
+    user::User,
+};
+
+/// Webhook event received from Discord.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+pub struct WebhookEvent {
+    /// ID of the application the event is for.
+    pub application_id: Id<ApplicationMarker>,
+    /// Body of the event, present when the type is [`WebhookEventType::Event`].
+    #[serde(default)]
+    pub event: Option<WebhookEventBody>,
+    /// Type of webhook event.
+    #[serde(rename = "type")]
+    pub kind: WebhookEventType,
+    /// Version of the webhook event payload.
+    pub version: u8,
+}
+
+/// Type of webhook event.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq)]
+#[serde(from = "u8")]
+pub enum WebhookEventType {
+    /// Ping sent by Discord to check the endpoint.
+    Ping,
+    /// Event with a body.
+    Event,
+    /// Type not known to this crate.
+    Unknown(u8),
+}
+
+impl From<u8> for WebhookEventType {
+    fn from(value: u8) -> Self {
+        match value {
+            0 => Self::Ping,
+            1 => Self::Event,
+            other => Self::Unknown(other),
+        }
+    }
+}
+
+/// Body of a webhook event.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WebhookEventBody {
+    /// Data of the event.
+    pub data: WebhookEventData,
+    /// ISO 8601 timestamp of when the event occurred.
+    pub timestamp: String,
+}
+
+impl<'de> Deserialize<'de> for WebhookEventBody {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        struct RawBody {
+            #[serde(default)]
+            data: Value,
+            #[serde(rename = "type")]
+            kind: String,
+            timestamp: String,
+        }
+
+        let RawBody {
+            data,
+            kind,
+            timestamp,
+        } = RawBody::deserialize(deserializer)?;
+
+        let data = match kind.as_str() {
+            "APPLICATION_AUTHORIZED" => {
+                serde_json::from_value(data).map(WebhookEventData::ApplicationAuthorized)
+            }
+            "APPLICATION_DEAUTHORIZED" => {
+                serde_json::from_value(data).map(WebhookEventData::ApplicationDeauthorized)
+            }
+            "ENTITLEMENT_CREATE" => {
+                serde_json::from_value(data).map(WebhookEventData::EntitlementCreate)
+            }
+            _ => Ok(WebhookEventData::Unknown { data, kind }),
+        }
+        .map_err(DeError::custom)?;
+
+        Ok(Self { data, timestamp })
+    }
+}
+
+/// Data of a webhook event, depending on the type of event.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum WebhookEventData {
+    /// Application was added to a guild or user account.
+    ApplicationAuthorized(ApplicationAuthorized),
+    /// Application was removed from a user account.
+    ApplicationDeauthorized(ApplicationDeauthorized),
+    /// Entitlement was created.
+    EntitlementCreate(Entitlement),
+    /// Event not known to this crate.
+    Unknown {
+        /// Data of the event.
+        data: Value,
+        /// Type of the event, such as `QUEST_USER_ENROLLMENT`.
+        kind: String,
+    },
+}
+
+/// Data of an [`WebhookEventData::ApplicationAuthorized`] event.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+pub struct ApplicationAuthorized {
+    /// Guild the application was added to, if it was added to a guild.
+    #[serde(default)]
+    pub guild: Option<Value>,
+    /// Installation context of the authorization, where `0` is a guild and
+    /// `1` is a user account.
+    #[serde(default)]
+    pub integration_type: Option<u8>,
+    /// `OAuth2` scopes the application was authorized with.
+    pub scopes: Vec<String>,
+    /// User who authorized the application.
+    pub user: User,
+}
+
+/// Data of an [`WebhookEventData::ApplicationDeauthorized`] event.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+pub struct ApplicationDeauthorized {
+    /// User who deauthorized the application.
+    pub user: User,
+}
+
+/// Entitlement of a user or guild to a premium offering of an application.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Entitlement {
+    /// ID of the application the entitlement is for.
+    pub application_id: Id<ApplicationMarker>,
+    /// Whether the entitlement has been consumed, for consumable SKUs.
+    #[serde(default)]
+    pub consumed: Option<bool>,
+    /// Whether the entitlement has been deleted.
+    #[serde(default)]
+    pub deleted: bool,
+    /// ISO 8601 timestamp of when the entitlement ends, if it does.
+    #[serde(default)]
+    pub ends_at: Option<String>,
+    /// ID of the guild that is granted the entitlement.
+    #[serde(default)]
+    pub guild_id: Option<Id<GuildMarker>>,
+    /// ID of the entitlement.
+    pub id: Id<GenericMarker>,
+    /// Type of entitlement.
+    #[serde(rename = "type")]
+    pub kind: u8,
+    /// ID of the SKU the entitlement is for.
+    pub sku_id: Id<GenericMarker>,
+    /// ISO 8601 timestamp of when the entitlement starts, if it does.
+    #[serde(default)]
+    pub starts_at: Option<String>,
+    /// ID of the user that is granted the entitlement.
+    #[serde(default)]
+    pub user_id: Option<Id<UserMarker>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{WebhookEvent, WebhookEventData, WebhookEventType};
+    use serde_json::{json, Value};
+    use twilight_model::id::Id;
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    fn event(kind: &str, data: &Value) -> WebhookEvent {
+        serde_json::from_value(json!({
+            "application_id": "1234560123453231555",
+            "event": {
+                "data": data,
+                "timestamp": "2024-10-18T14:42:53.064834",
+                "type": kind,
+            },
+            "type": 1,
+            "version": 1,
+        }))
+        .unwrap()
+    }
+
+    fn user() -> Value {
+        json!({
+            "avatar": "7b3f5b6d7f6a6b9f6f5b6f7a6b9f6f5b",
+            "discriminator": "0",
+            "global_name": "Test",
+            "id": "111111111111111111",
+            "public_flags": 0,
+            "username": "test",
+        })
+    }
+
+    #[test]
+    fn kind_from_u8() {
+        assert_eq!(WebhookEventType::Ping, WebhookEventType::from(0));
+        assert_eq!(WebhookEventType::Event, WebhookEventType::from(1));
+        assert_eq!(WebhookEventType::Unknown(2), WebhookEventType::from(2));
+        assert_eq!(
+            WebhookEventType::Unknown(u8::MAX),
+            WebhookEventType::from(u8::MAX)
+        );
+    }
+
+    #[test]
+    fn ping() {
+        let event = serde_json::from_value::<WebhookEvent>(json!({
+            "application_id": "1234560123453231555",
+            "type": 0,
+            "version": 1,
+        }))
+        .unwrap();
+
+        assert_eq!(WebhookEventType::Ping, event.kind);
+        assert_eq!(None, event.event);
+        assert_eq!(Id::new(1_234_560_123_453_231_555), event.application_id);
+    }
+
+    #[test]
+    fn application_authorized() {
+        let event = event(
+            "APPLICATION_AUTHORIZED",
+            &json!({
+                "integration_type": 1,
+                "scopes": ["applications.commands"],
+                "user": user(),
+            }),
+        );
+        let body = event.event.unwrap();
+
+        assert_eq!("2024-10-18T14:42:53.064834", body.timestamp);
+        let WebhookEventData::ApplicationAuthorized(authorized) = body.data else {
+            panic!("expected an application authorized event");
+        };
+        assert_eq!(None, authorized.guild);
+        assert_eq!(Some(1), authorized.integration_type);
+        assert_eq!(["applications.commands"], authorized.scopes.as_slice());
+        assert_eq!(Id::new(111_111_111_111_111_111), authorized.user.id);
+    }
+
+    #[test]
+    fn application_deauthorized() {
+        let event = event("APPLICATION_DEAUTHORIZED", &json!({ "user": user() }));
+
+        let WebhookEventData::ApplicationDeauthorized(deauthorized) = event.event.unwrap().data
+        else {
+            panic!("expected an application deauthorized event");
+        };
+        assert_eq!("test", deauthorized.user.name);
+    }
+
+    #[test]
+    fn entitlement_create() {
+        let event = event(
+            "ENTITLEMENT_CREATE",
+            &json!({
+                "application_id": "1234560123453231555",
+                "consumed": false,
+                "deleted": false,
+                "gift_code_flags": 0,
+                "id": "1234567890123456789",
+                "promotion_id": null,
+                "sku_id": "123456789123456789",
+                "type": 4,
+                "user_id": "111111111111111111",
+            }),
+        );
+
+        let WebhookEventData::EntitlementCreate(entitlement) = event.event.unwrap().data else {
+            panic!("expected an entitlement create event");
+        };
+        assert_eq!(Some(false), entitlement.consumed);
+        assert!(!entitlement.deleted);
+        assert_eq!(None, entitlement.guild_id);
+        assert_eq!(4, entitlement.kind);
+        assert_eq!(Id::new(123_456_789_123_456_789), entitlement.sku_id);
+        assert_eq!(Some(Id::new(111_111_111_111_111_111)), entitlement.user_id);
+    }
+
+    #[test]
+    fn unknown_event() {
+        let data = json!({ "quest_id": "1" });
+        let event = event("QUEST_USER_ENROLLMENT", &data);
+
+        assert_eq!(
+            WebhookEventData::Unknown {
+                data,
+                kind: "QUEST_USER_ENROLLMENT".to_owned(),
+            },
+            event.event.unwrap().data
+        );
+    }
+
+    #[test]
+    fn invalid_known_event() {
+        let result = serde_json::from_value::<WebhookEvent>(json!({
+            "application_id": "1",
+            "event": {
+                "data": { "user": "not a user" },
+                "timestamp": "2024-10-18T14:42:53.064834",
+                "type": "APPLICATION_DEAUTHORIZED",
+            },
+            "type": 1,
+            "version": 1,
+        }));
+
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,105 @@
+//! Properties of request verification and interaction parsing over generated
+//! and malformed inputs.
+
+use proptest::prelude::*;
+use twilight_cloudflare_workers::{
+    testing::{strategies, SignedRequest},
+    LazyInteraction, RequestSignature,
+};
+
+proptest! {
+    #[test]
+    fn signed_requests_verify(request in strategies::signed_request()) {
+        prop_assert!(request.verify().is_ok());
+    }
+
+    #[test]
+    fn tampered_bodies_do_not_verify(
+        request in strategies::signed_request(),
+        index in any::<prop::sample::Index>(),
+    ) {
+        let mut body = request.body().to_vec();
+        let index = index.index(body.len());
+        body[index] ^= 1;
+
+        prop_assert!(twilight_cloudflare_workers::verify(
+            request.public_key(),
+            request.timestamp(),
+            request.signature(),
+            &body,
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn malformed_signatures_are_rejected(
+        request in strategies::signed_request(),
+        signature in strategies::malformed_hex(),
+    ) {
+        prop_assert!(RequestSignature::new(request.timestamp(), &signature).is_err());
+    }
+
+    #[test]
+    fn malformed_public_keys_are_rejected(
+        request in strategies::signed_request(),
+        public_key in strategies::malformed_hex(),
+    ) {
+        let signature = RequestSignature::new(request.timestamp(), request.signature())?;
+
+        prop_assert!(signature.verify(&public_key, request.body()).is_err());
+    }
+
+    #[test]
+    fn signed_parts_verify(request in strategies::signed_request(), uppercase: bool) {
+        let (signature, timestamp) = if uppercase {
+            ("X-SIGNATURE-ED25519", "X-SIGNATURE-TIMESTAMP")
+        } else {
+            ("x-signature-ed25519", "x-signature-timestamp")
+        };
+
+        prop_assert!(twilight_cloudflare_workers::verify_parts(
+            "POST",
+            "/",
+            [
+                ("content-type", "application/json"),
+                (signature, request.signature()),
+                (timestamp, request.timestamp()),
+            ],
+            request.body(),
+            request.public_key(),
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn generated_interactions_parse(body in strategies::interaction_json()) {
+        prop_assert!(LazyInteraction::new(body).is_ok());
+    }
+
+    #[test]
+    fn malformed_bodies_do_not_panic(body in strategies::malformed_body()) {
+        let _ = LazyInteraction::new(body);
+    }
+
+    #[test]
+    fn deep_json_does_not_overflow(body in strategies::deep_json()) {
+        let _ = LazyInteraction::new(body);
+    }
+
+    #[test]
+    fn requests_signed_with_other_keys_do_not_verify(
+        request in strategies::signed_request(),
+        secret_key in strategies::secret_key(),
+    ) {
+        let other = SignedRequest::sign(secret_key, request.timestamp(), request.body());
+        prop_assume!(other.public_key() != request.public_key());
+
+        prop_assert!(twilight_cloudflare_workers::verify(
+            other.public_key(),
+            request.timestamp(),
+            request.signature(),
+            request.body(),
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,20 @@
+//! Verification of the test vectors.
+
+use twilight_cloudflare_workers::testing::TEST_VECTORS;
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen_test::wasm_bindgen_test as test;
+
+#[test]
+fn test_vectors() {
+    for vector in TEST_VECTORS {
+        let result = vector.verify();
+
+        assert!(
+            vector.outcome.matches(&result),
+            "{}: expected {:?}, got {:?}",
+            vector.name,
+            vector.outcome,
+            result.map_err(|source| source.to_string()),
+        );
+    }
+}
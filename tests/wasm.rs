@@ -0,0 +1,218 @@
+//! Round trips of signed requests through request processing, run in a
+//! JavaScript environment with `wasm-bindgen-test`:
+//!
+//! ```sh
+//! cargo install wasm-bindgen-cli --version 0.2.84
+//! cargo test --target wasm32-unknown-unknown --features testing
+//! ```
+
+#![cfg(target_arch = "wasm32")]
+
+use twilight_cloudflare_workers::{
+    testing::SignedRequest, Config, ConfigBuilder, EnvErrorType, ProcessRequestErrorType, Route,
+};
+use twilight_model::application::interaction::InteractionType;
+use wasm_bindgen_test::wasm_bindgen_test;
+use worker::{
+    js_sys::{Array, Object, Reflect},
+    wasm_bindgen::{JsCast, JsValue},
+    Env, Method, Request, RequestInit, ResponseBody,
+};
+
+const PING: &str = r#"{"application_id":"1","id":"2","token":"token","type":1,"version":1}"#;
+
+const SECRET_KEY: [u8; 32] = [7; 32];
+
+const URL: &str = "https://example.com/";
+
+/// Environment with bindings set to values.
+fn env(bindings: &[(&str, JsValue)]) -> Env {
+    let env = Object::new();
+
+    for (name, value) in bindings {
+        Reflect::set(&env, &JsValue::from_str(name), value).unwrap();
+    }
+
+    env.unchecked_into()
+}
+
+#[wasm_bindgen_test]
+async fn request() {
+    let signed = SignedRequest::sign(SECRET_KEY, "1682410000", PING);
+    let mut req = signed.to_request(URL).unwrap();
+
+    let interaction = twilight_cloudflare_workers::request(&mut req, signed.public_key())
+        .await
+        .unwrap();
+
+    assert_eq!(InteractionType::Ping, interaction.kind);
+}
+
+#[wasm_bindgen_test]
+async fn request_lazy() {
+    let signed = SignedRequest::sign(SECRET_KEY, "1682410000", PING);
+    let mut req = signed.to_request(URL).unwrap();
+
+    let interaction = twilight_cloudflare_workers::request_lazy(&mut req, signed.public_key())
+        .await
+        .unwrap();
+
+    assert_eq!(InteractionType::Ping, interaction.kind());
+    assert_eq!(PING.as_bytes(), interaction.body());
+}
+
+#[wasm_bindgen_test]
+async fn request_with_other_public_key() {
+    let signed = SignedRequest::sign(SECRET_KEY, "1682410000", PING);
+    let other = SignedRequest::sign([8; 32], "1682410000", PING);
+    let mut req = signed.to_request(URL).unwrap();
+
+    let error = twilight_cloudflare_workers::request_lazy(&mut req, other.public_key())
+        .await
+        .unwrap_err();
+
+    assert!(matches!(
+        error.kind(),
+        ProcessRequestErrorType::InvalidSignature
+    ));
+}
+
+#[wasm_bindgen_test]
+async fn request_on_other_path() {
+    let signed = SignedRequest::sign(SECRET_KEY, "1682410000", PING);
+    let mut req = signed.to_request("https://example.com/other").unwrap();
+
+    let error = twilight_cloudflare_workers::request_lazy(&mut req, signed.public_key())
+        .await
+        .unwrap_err();
+
+    assert!(matches!(
+        error.kind(),
+        ProcessRequestErrorType::RouteIncorrect { .. }
+    ));
+}
+
+#[wasm_bindgen_test]
+async fn route() {
+    let signed = SignedRequest::sign(SECRET_KEY, "1682410000", PING);
+    let config = Config::builder(signed.public_key())
+        .path("/interactions")
+        .max_body_size(1024)
+        .build();
+
+    let mut req = signed
+        .to_request("https://example.com/interactions")
+        .unwrap();
+    assert!(matches!(
+        config.route(&mut req).await,
+        Route::Interaction(_)
+    ));
+
+    let mut req = signed.to_request(URL).unwrap();
+    assert!(matches!(config.route(&mut req).await, Route::Unmatched));
+}
+
+#[wasm_bindgen_test]
+async fn route_body_too_large() {
+    let signed = SignedRequest::sign(SECRET_KEY, "1682410000", PING);
+    let config = Config::builder(signed.public_key())
+        .max_body_size(8)
+        .build();
+    let mut req = signed.to_request(URL).unwrap();
+
+    let Route::Response(response) = config.route(&mut req).await else {
+        panic!("request with a large body is routed to an interaction");
+    };
+
+    assert_eq!(413, response.status_code());
+}
+
+#[wasm_bindgen_test]
+fn from_env() {
+    let signed = SignedRequest::sign(SECRET_KEY, "1682410000", PING);
+    let env = env(&[
+        (
+            "FEATURES",
+            Array::of2(&"beta".into(), &"economy".into()).into(),
+        ),
+        ("MAX_BODY_SIZE", JsValue::from_f64(65536.0)),
+        ("OWNER_IDS", "2, 3".into()),
+        ("PUBLIC_KEY", signed.public_key().into()),
+    ]);
+
+    let config = ConfigBuilder::from_env(&env).unwrap().build();
+
+    assert_eq!(config.features(), ["beta", "economy"]);
+    assert_eq!(config.max_body_size(), Some(65536));
+    assert_eq!(config.owner_ids(), ["2", "3"]);
+}
+
+#[wasm_bindgen_test]
+fn from_env_wrong_types() {
+    let env = env(&[
+        ("APPLICATION_ID", JsValue::from_f64(1.5)),
+        ("BOT_TOKEN", Object::new().into()),
+        ("FEATURES", Array::of1(&JsValue::TRUE).into()),
+        ("MAX_BODY_SIZE", JsValue::TRUE),
+    ]);
+
+    let EnvErrorType::Bindings { invalid, missing } =
+        ConfigBuilder::from_env(&env).unwrap_err().into_parts().0;
+
+    assert_eq!(
+        invalid,
+        ["APPLICATION_ID", "BOT_TOKEN", "FEATURES", "MAX_BODY_SIZE"]
+    );
+    assert_eq!(missing, ["PUBLIC_KEY"]);
+}
+
+#[wasm_bindgen_test]
+async fn route_health_check() {
+    let config = Config::builder("")
+        .health_check("/healthz", "1.0.0")
+        .build();
+
+    let mut req = Request::new("https://example.com/healthz", Method::Get).unwrap();
+    let Route::Response(mut get) = config.route(&mut req).await else {
+        panic!("health check isn't responded to");
+    };
+    assert_eq!(200, get.status_code());
+    assert_eq!(
+        r#"{"status":"ok","version":"1.0.0"}"#,
+        get.text().await.unwrap()
+    );
+
+    let mut req = Request::new_with_init(
+        "https://example.com/healthz",
+        RequestInit::new().with_method(Method::Head),
+    )
+    .unwrap();
+    let Route::Response(head) = config.route(&mut req).await else {
+        panic!("health check isn't responded to");
+    };
+    assert_eq!(200, head.status_code());
+    assert!(matches!(head.body(), ResponseBody::Empty));
+    assert_eq!(
+        Some("application/json".to_owned()),
+        head.headers().get("Content-Type").unwrap()
+    );
+}
+
+#[wasm_bindgen_test]
+async fn request_if_matched() {
+    let signed = SignedRequest::sign(SECRET_KEY, "1682410000", PING);
+
+    let mut req = signed.to_request(URL).unwrap();
+    let interaction =
+        twilight_cloudflare_workers::request_if_matched(&mut req, signed.public_key())
+            .await
+            .unwrap();
+    assert_eq!(InteractionType::Ping, interaction.unwrap().kind);
+
+    let mut req = signed.to_request("https://example.com/other").unwrap();
+    let interaction =
+        twilight_cloudflare_workers::request_if_matched(&mut req, signed.public_key())
+            .await
+            .unwrap();
+    assert!(interaction.is_none());
+}